@@ -1,3 +1,6 @@
+use crate::airline::{Airline, ParsedCallsign};
+use crate::config::CallsignDisplay;
+use crate::icao::{self, AddressBlock, AddressKind};
 use serde::{Deserialize, Serialize};
 use chrono::{DateTime, Utc};
 use geo_types::Point;
@@ -13,6 +16,8 @@ pub struct Aircraft {
     pub longitude: Option<f64>,
     pub latitude: Option<f64>,
     pub altitude: Option<f64>,
+    #[serde(default)]
+    pub on_ground: Option<bool>,
     pub velocity: Option<f64>,
     pub true_track: Option<f64>,
     pub vertical_rate: Option<f64>,
@@ -35,6 +40,7 @@ impl Aircraft {
             longitude: None,
             latitude: None,
             altitude: None,
+            on_ground: None,
             velocity: None,
             true_track: None,
             vertical_rate: None,
@@ -72,9 +78,79 @@ impl Aircraft {
         self.callsign
             .as_ref()
             .map(|c| c.trim().to_string())
+            .filter(|c| !c.is_empty())
             .unwrap_or_else(|| self.icao24.clone())
     }
 
+    pub fn parsed_callsign(&self) -> Option<ParsedCallsign> {
+        self.callsign.as_deref().and_then(ParsedCallsign::parse)
+    }
+
+    pub fn airline(&self) -> Option<&'static Airline> {
+        self.parsed_callsign().and_then(|c| c.airline())
+    }
+
+    // Falls back to the plain callsign when the airline is unknown
+    pub fn label(&self, display: CallsignDisplay) -> String {
+        let name = self.display_name();
+        let decoded = self.parsed_callsign().and_then(|c| match display {
+            CallsignDisplay::Callsign => None,
+            CallsignDisplay::Airline => c.airline_flight().map(|f| format!("{} / {}", name, f)),
+            CallsignDisplay::Iata => c.iata_flight(),
+            CallsignDisplay::Telephony => c.telephony(),
+        });
+        decoded.unwrap_or(name)
+    }
+
+    pub fn icao_address(&self) -> Option<u32> {
+        icao::parse_address(&self.icao24)
+    }
+
+    pub fn registration(&self) -> Option<&'static AddressBlock> {
+        self.icao_address().and_then(icao::lookup)
+    }
+
+    pub fn address_kind(&self) -> AddressKind {
+        icao::classify(&self.icao24)
+    }
+
+    pub fn is_military(&self) -> bool {
+        self.address_kind() == AddressKind::Military
+    }
+
+    // Prefer the feed-supplied country, fall back to the address allocation
+    pub fn country(&self) -> Option<&str> {
+        self.origin_country
+            .as_deref()
+            .filter(|c| !c.trim().is_empty())
+            .or_else(|| self.registration().map(|b| b.country))
+    }
+
+    pub fn country_code(&self) -> Option<&'static str> {
+        self.registration().map(|b| b.iso_code)
+    }
+
+    // Short wake/emitter category from the OpenSky category field
+    pub fn category_code(&self) -> Option<&'static str> {
+        match self.category? {
+            2 => Some("L"),
+            3 => Some("S"),
+            4 => Some("M"),
+            5 => Some("B757"),
+            6 => Some("H"),
+            7 => Some("HP"),
+            8 => Some("ROT"),
+            9 => Some("GLD"),
+            10 => Some("LTA"),
+            11 => Some("PAR"),
+            12 => Some("ULT"),
+            14 => Some("UAV"),
+            15 => Some("SPC"),
+            16 | 17 => Some("VEH"),
+            _ => None,
+        }
+    }
+
     pub fn is_active(&self) -> bool {
         if let Some(time) = self.time_position {
             let now = Utc::now();
@@ -106,10 +182,22 @@ impl AltitudeBand {
     }
 }
 
+// One trail entry. Position, altitude and speed are kept together so they
+// can't drift out of step.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct TrailSample {
+    pub pos: Point<f64>,
+    // Feet
+    pub altitude: Option<f64>,
+    // Ground speed, km/h
+    pub speed: Option<f64>,
+    pub time: DateTime<Utc>,
+}
+
 #[derive(Debug, Clone)]
 pub struct AircraftTrail {
     pub icao24: String,
-    pub positions: Vec<(Point<f64>, DateTime<Utc>)>,
+    samples: Vec<TrailSample>,
     pub max_points: usize,
 }
 
@@ -117,19 +205,68 @@ impl AircraftTrail {
     pub fn new(icao24: String, max_points: usize) -> Self {
         Self {
             icao24,
-            positions: Vec::new(),
+            samples: Vec::new(),
             max_points,
         }
     }
 
     pub fn add_position(&mut self, position: Point<f64>, timestamp: DateTime<Utc>) {
-        self.positions.push((position, timestamp));
-        if self.positions.len() > self.max_points {
-            self.positions.remove(0);
+        self.add_sample(position, None, None, timestamp);
+    }
+
+    pub fn add_sample(&mut self, position: Point<f64>, altitude: Option<f64>, speed: Option<f64>, timestamp: DateTime<Utc>) {
+        self.samples.push(TrailSample {
+            pos: position,
+            altitude,
+            speed,
+            time: timestamp,
+        });
+        if self.samples.len() > self.max_points {
+            self.samples.remove(0);
         }
     }
 
+    // Oldest first
+    pub fn samples(&self) -> &[TrailSample] {
+        &self.samples
+    }
+
+    pub fn positions(&self) -> impl Iterator<Item = Point<f64>> + '_ {
+        self.samples.iter().map(|s| s.pos)
+    }
+
+    pub fn len(&self) -> usize {
+        self.samples.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.samples.is_empty()
+    }
+
     pub fn clear(&mut self) {
-        self.positions.clear();
+        self.samples.clear();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::Duration;
+
+    #[test]
+    fn test_trail_keeps_newest_samples() {
+        let mut trail = AircraftTrail::new("a12345".to_string(), 3);
+        let start = Utc::now();
+        for i in 0..5 {
+            let speed = (i % 2 == 0).then_some(400.0 + i as f64);
+            trail.add_sample(Point::new(i as f64, 51.0), Some(1000.0 * i as f64), speed, start + Duration::seconds(i));
+        }
+
+        assert_eq!(trail.len(), 3);
+        let first = trail.samples()[0];
+        assert_eq!(first.pos, Point::new(2.0, 51.0));
+        assert_eq!(first.altitude, Some(2000.0));
+        assert_eq!(first.speed, Some(402.0));
+        assert_eq!(first.time, start + Duration::seconds(2));
     }
-} 
\ No newline at end of file
+}
@@ -0,0 +1,304 @@
+use crate::aircraft::{Aircraft, AircraftTrail, TrailSample};
+use crate::config::CallsignDisplay;
+use crate::cpa;
+use crate::flight_phase::FlightPhase;
+use crate::format::format_duration;
+use crate::geo::Location;
+use chrono::{DateTime, Utc};
+use egui::{Color32, Pos2, Rect, Sense, Shape, Stroke, Ui, Vec2};
+
+const FEET_TO_KM: f64 = 0.0003048;
+const ALTITUDE_COLOR: Color32 = Color32::from_rgb(0, 200, 255);
+const SPEED_COLOR: Color32 = Color32::from_rgb(255, 170, 0);
+
+#[derive(Debug, Clone)]
+pub struct DetailField {
+    pub label: &'static str,
+    pub value: String,
+    // Seconds since the report this value came from, when the feed says
+    pub age_secs: Option<f64>,
+}
+
+#[derive(Debug, Clone)]
+pub struct DetailSection {
+    pub title: &'static str,
+    pub fields: Vec<DetailField>,
+}
+
+impl DetailSection {
+    fn new(title: &'static str) -> Self {
+        Self { title, fields: Vec::new() }
+    }
+
+    fn add(&mut self, label: &'static str, value: Option<String>, age_secs: Option<f64>) {
+        self.fields.push(DetailField {
+            label,
+            value: value.unwrap_or_else(|| "-".to_string()),
+            age_secs,
+        });
+    }
+}
+
+// Everything known about one aircraft, laid out for the details panel and
+// the clipboard. Missing values are listed as "-" so every field shows.
+#[derive(Debug, Clone)]
+pub struct AircraftDetails {
+    pub title: String,
+    pub sections: Vec<DetailSection>,
+}
+
+impl AircraftDetails {
+    pub fn new(
+        aircraft: &Aircraft,
+        home: &Location,
+        phase: FlightPhase,
+        now: DateTime<Utc>,
+        display: CallsignDisplay,
+        overhead_km: f64,
+    ) -> Self {
+        let age = |time: Option<DateTime<Utc>>| time.map(|t| ((now - t).num_milliseconds() as f64 / 1000.0).max(0.0));
+        let position_age = age(aircraft.time_position);
+        let velocity_age = age(aircraft.time_velocity);
+        let clock = |time: Option<DateTime<Utc>>| time.map(|t| t.format("%H:%M:%S UTC").to_string());
+        let parsed = aircraft.parsed_callsign();
+
+        let mut identity = DetailSection::new("Identity");
+        identity.add("Callsign", aircraft.callsign.as_ref().map(|c| c.trim().to_string()), None);
+        identity.add(
+            "Airline",
+            aircraft.airline().map(|a| format!("{} ({}/{})", a.name, a.icao, a.iata)),
+            None,
+        );
+        identity.add("Flight", parsed.as_ref().and_then(|p| p.iata_flight()), None);
+        identity.add("Radio", parsed.as_ref().and_then(|p| p.telephony()), None);
+        identity.add(
+            "ICAO24",
+            Some(format!("{} ({})", aircraft.icao24, aircraft.address_kind().name())),
+            None,
+        );
+        identity.add("Origin country", aircraft.origin_country.clone(), None);
+        identity.add("Registered in", aircraft.registration().map(|b| b.country.to_string()), None);
+        identity.add(
+            "Category",
+            aircraft
+                .category
+                .map(|c| format!("{} ({})", aircraft.category_code().unwrap_or("-"), c)),
+            None,
+        );
+
+        let mut position = DetailSection::new("Position");
+        position.add("Latitude", aircraft.latitude.map(|v| format!("{:.5}", v)), position_age);
+        position.add("Longitude", aircraft.longitude.map(|v| format!("{:.5}", v)), position_age);
+        position.add("Baro altitude", aircraft.altitude.map(|v| format!("{:.0} ft", v)), position_age);
+        position.add("Geo altitude", aircraft.geo_altitude.map(|v| format!("{:.0} ft", v)), position_age);
+        position.add("On ground", aircraft.on_ground.map(yes_no), position_age);
+        position.add("Position time", clock(aircraft.time_position), position_age);
+
+        let mut motion = DetailSection::new("Motion");
+        motion.add("Speed", aircraft.velocity.map(|v| format!("{:.0} km/h", v)), velocity_age);
+        motion.add("Track", aircraft.true_track.map(|v| format!("{:03.0}°", v)), velocity_age);
+        motion.add("Vertical rate", aircraft.vertical_rate.map(|v| format!("{:+.0} fpm", v)), velocity_age);
+        motion.add("Velocity time", clock(aircraft.time_velocity), velocity_age);
+
+        let mut transponder = DetailSection::new("Transponder");
+        transponder.add("Squawk", aircraft.squawk.clone(), None);
+        transponder.add("SPI", aircraft.spi.map(yes_no), None);
+        transponder.add(
+            "Position source",
+            aircraft.position_source.map(|s| position_source_name(s).to_string()),
+            position_age,
+        );
+        transponder.add(
+            "Sensors",
+            aircraft
+                .sensors
+                .as_ref()
+                .filter(|s| !s.is_empty())
+                .map(|s| format!("{} ({})", s.join(", "), s.len())),
+            None,
+        );
+
+        let mut derived = DetailSection::new("From home");
+        let location = aircraft.position().map(Location::from_point);
+        let distance_km = location.as_ref().map(|l| home.distance_to(l));
+        derived.add("Distance", distance_km.map(|d| format!("{:.1} km", d)), position_age);
+        derived.add(
+            "Bearing",
+            location.as_ref().map(|l| format!("{:03.0}°", home.bearing_to(l))),
+            position_age,
+        );
+        let elevation = distance_km
+            .zip(aircraft.altitude)
+            .map(|(distance, altitude)| (altitude * FEET_TO_KM).atan2(distance).to_degrees());
+        derived.add("Elevation", elevation.map(|e| format!("{:.1}°", e)), position_age);
+        derived.add("Phase", Some(phase.name().to_string()), None);
+
+        let approach = cpa::closest_approach(aircraft, home, now);
+        derived.add(
+            "Closest approach",
+            approach.as_ref().map(|a| {
+                if a.time_to_cpa_secs > 0.0 {
+                    format!("{:.1} km in {}", a.min_distance_km, format_duration(a.time_to_cpa_secs))
+                } else {
+                    format!("{:.1} km now, moving away", a.min_distance_km)
+                }
+            }),
+            velocity_age,
+        );
+        derived.add(
+            "Overhead ETA",
+            approach
+                .filter(|a| a.is_overhead(overhead_km) && a.time_to_cpa_secs > 0.0)
+                .map(|a| format!("{} (in {})", a.eta.format("%H:%M:%S"), format_duration(a.time_to_cpa_secs))),
+            velocity_age,
+        );
+
+        Self {
+            title: aircraft.label(display),
+            sections: vec![identity, position, motion, transponder, derived],
+        }
+    }
+
+    pub fn field(&self, label: &str) -> Option<&DetailField> {
+        self.sections.iter().flat_map(|s| &s.fields).find(|f| f.label == label)
+    }
+
+    // Plain text for pasting into chat or a log
+    pub fn to_text(&self) -> String {
+        let mut text = self.title.clone();
+        for section in &self.sections {
+            text.push_str(&format!("\n\n[{}]", section.title));
+            for field in &section.fields {
+                text.push_str(&format!("\n{}: {}", field.label, field.value));
+                if let Some(age) = field.age_secs {
+                    text.push_str(&format!(" ({} ago)", format_duration(age)));
+                }
+            }
+        }
+        text
+    }
+}
+
+fn yes_no(value: bool) -> String {
+    if value { "Yes" } else { "No" }.to_string()
+}
+
+// OpenSky position_source codes
+pub fn position_source_name(code: i32) -> &'static str {
+    match code {
+        0 => "ADS-B",
+        1 => "ASTERIX",
+        2 => "MLAT",
+        3 => "FLARM",
+        _ => "Unknown",
+    }
+}
+
+// Altitude and speed over the trail, each scaled to its own range so both
+// shapes are readable in a small strip
+pub fn draw_history(ui: &mut Ui, trail: &AircraftTrail) {
+    let history = trail.samples();
+    let (Some(first), Some(last)) = (history.first(), history.last()) else {
+        return;
+    };
+    let span_secs = ((last.time - first.time).num_milliseconds() as f64 / 1000.0).max(1.0);
+
+    let (response, painter) = ui.allocate_painter(Vec2::new(ui.available_width(), 70.0), Sense::hover());
+    let rect = response.rect.shrink(2.0);
+    painter.rect_filled(rect, 2.0, ui.visuals().extreme_bg_color);
+
+    let font = egui::FontId::proportional(10.0);
+    let series: [(fn(&TrailSample) -> Option<f64>, Color32, &str, egui::Align2); 2] = [
+        (|s| s.altitude, ALTITUDE_COLOR, "ft", egui::Align2::LEFT_TOP),
+        (|s| s.speed, SPEED_COLOR, "km/h", egui::Align2::RIGHT_TOP),
+    ];
+    for (value, color, unit, align) in series {
+        let samples: Vec<(f64, f64)> = history
+            .iter()
+            .filter_map(|s| Some(((s.time - first.time).num_milliseconds() as f64 / 1000.0, value(s)?)))
+            .collect();
+        let Some(range) = value_range(samples.iter().map(|s| s.1)) else {
+            continue;
+        };
+
+        let points: Vec<Pos2> = samples
+            .iter()
+            .map(|(t, v)| {
+                let x = rect.left() + (t / span_secs) as f32 * rect.width();
+                let y = rect.bottom() - ((v - range.0) / (range.1 - range.0)) as f32 * rect.height();
+                Pos2::new(x, y)
+            })
+            .collect();
+        if points.len() > 1 {
+            painter.add(Shape::line(points, Stroke::new(1.5, color)));
+        } else {
+            painter.circle_filled(points[0], 2.0, color);
+        }
+
+        let anchor = Rect::from_min_max(rect.min + Vec2::splat(3.0), rect.max - Vec2::splat(3.0));
+        painter.text(
+            align.pos_in_rect(&anchor),
+            align,
+            format!("{:.0}–{:.0} {}", range.0, range.1, unit),
+            font.clone(),
+            color,
+        );
+    }
+    response.on_hover_text(format!("Last {} of trail", format_duration(span_secs)));
+}
+
+// Min and max, widened when flat so a level line sits mid-strip
+fn value_range(values: impl Iterator<Item = f64>) -> Option<(f64, f64)> {
+    let (min, max) = values.fold((f64::INFINITY, f64::NEG_INFINITY), |(lo, hi), v| (lo.min(v), hi.max(v)));
+    if min > max {
+        return None;
+    }
+    let pad = if max - min < 1.0 { 50.0 } else { 0.0 };
+    Some((min - pad, max + pad))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::Duration;
+
+    #[test]
+    fn test_details() {
+        let home = Location::london();
+        let now = Utc::now();
+        let fix = home.offset_by_km(0.0, -10.0);
+        let mut aircraft = Aircraft::new("400abc".to_string());
+        aircraft.callsign = Some("BAW123  ".to_string());
+        aircraft.latitude = Some(fix.lat);
+        aircraft.longitude = Some(fix.lon);
+        aircraft.altitude = Some(3280.84);
+        aircraft.velocity = Some(360.0);
+        aircraft.true_track = Some(0.0);
+        aircraft.time_position = Some(now - Duration::seconds(4));
+        aircraft.time_velocity = Some(now - Duration::seconds(65));
+        aircraft.sensors = Some(vec!["1234".to_string(), "5678".to_string()]);
+        aircraft.position_source = Some(2);
+
+        let details = AircraftDetails::new(&aircraft, &home, FlightPhase::Cruise, now, CallsignDisplay::Callsign, 1.5);
+        let value = |label| details.field(label).unwrap().value.as_str();
+
+        assert_eq!(value("Callsign"), "BAW123");
+        assert_eq!(value("Distance"), "10.0 km");
+        assert_eq!(value("Bearing"), "180°");
+        assert_eq!(value("Elevation"), "5.7°");
+        assert_eq!(value("Position source"), "MLAT");
+        assert_eq!(value("Sensors"), "1234, 5678 (2)");
+        assert_eq!(value("Squawk"), "-");
+        // Heading straight for home at 100 m/s, reported 4 s ago
+        assert_eq!(value("Closest approach"), "0.0 km in 1:36");
+        assert!(value("Overhead ETA").ends_with("(in 1:36)"));
+
+        assert_eq!(details.field("Latitude").unwrap().age_secs, Some(4.0));
+        assert_eq!(details.field("Speed").unwrap().age_secs, Some(65.0));
+        assert_eq!(details.field("Squawk").unwrap().age_secs, None);
+
+        let text = details.to_text();
+        assert!(text.starts_with("BAW123\n\n[Identity]"));
+        assert!(text.contains("\nSpeed: 360 km/h (1:05 ago)"));
+    }
+}
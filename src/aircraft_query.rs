@@ -0,0 +1,355 @@
+use crate::aircraft::Aircraft;
+use crate::config::CallsignDisplay;
+use crate::filter_expr::{Filter, FilterContext};
+use crate::geo::Location;
+use chrono::{DateTime, Utc};
+use std::cmp::Ordering;
+
+const EMERGENCY_SQUAWKS: [&str; 3] = ["7500", "7600", "7700"];
+const CLIMB_THRESHOLD_FPM: f64 = 300.0;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ListColumn {
+    Callsign,
+    Icao24,
+    Altitude,
+    Speed,
+    Track,
+    Distance,
+    Bearing,
+    VerticalRate,
+    Squawk,
+    Age,
+}
+
+impl ListColumn {
+    pub const ALL: [ListColumn; 10] = [
+        ListColumn::Callsign,
+        ListColumn::Icao24,
+        ListColumn::Altitude,
+        ListColumn::Speed,
+        ListColumn::Track,
+        ListColumn::Distance,
+        ListColumn::Bearing,
+        ListColumn::VerticalRate,
+        ListColumn::Squawk,
+        ListColumn::Age,
+    ];
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            ListColumn::Callsign => "Callsign",
+            ListColumn::Icao24 => "ICAO24",
+            ListColumn::Altitude => "Alt ft",
+            ListColumn::Speed => "Spd km/h",
+            ListColumn::Track => "Trk",
+            ListColumn::Distance => "Dist km",
+            ListColumn::Bearing => "Brg",
+            ListColumn::VerticalRate => "V/S fpm",
+            ListColumn::Squawk => "Sqk",
+            ListColumn::Age => "Age",
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum QuickFilter {
+    Airborne,
+    OnGround,
+    Climbing,
+    Descending,
+    Military,
+    Emergency,
+}
+
+impl QuickFilter {
+    pub const ALL: [QuickFilter; 6] = [
+        QuickFilter::Airborne,
+        QuickFilter::OnGround,
+        QuickFilter::Climbing,
+        QuickFilter::Descending,
+        QuickFilter::Military,
+        QuickFilter::Emergency,
+    ];
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            QuickFilter::Airborne => "Airborne",
+            QuickFilter::OnGround => "On ground",
+            QuickFilter::Climbing => "Climbing",
+            QuickFilter::Descending => "Descending",
+            QuickFilter::Military => "Military",
+            QuickFilter::Emergency => "Emergency",
+        }
+    }
+
+    pub fn matches(&self, aircraft: &Aircraft) -> bool {
+        match self {
+            QuickFilter::Airborne => aircraft.on_ground != Some(true),
+            QuickFilter::OnGround => aircraft.on_ground == Some(true),
+            QuickFilter::Climbing => aircraft.vertical_rate.is_some_and(|r| r >= CLIMB_THRESHOLD_FPM),
+            QuickFilter::Descending => aircraft.vertical_rate.is_some_and(|r| r <= -CLIMB_THRESHOLD_FPM),
+            QuickFilter::Military => aircraft.is_military(),
+            QuickFilter::Emergency => aircraft
+                .squawk
+                .as_deref()
+                .is_some_and(|s| EMERGENCY_SQUAWKS.contains(&s.trim())),
+        }
+    }
+}
+
+// One line of the aircraft table: the aircraft plus values derived from
+// home and the current time
+#[derive(Debug, Clone)]
+pub struct AircraftRow<'a> {
+    pub aircraft: &'a Aircraft,
+    pub label: String,
+    pub distance_km: Option<f64>,
+    pub bearing_deg: Option<f64>,
+    pub age_secs: Option<f64>,
+}
+
+impl<'a> AircraftRow<'a> {
+    pub fn new(aircraft: &'a Aircraft, home: &Location, now: DateTime<Utc>, display: CallsignDisplay) -> Self {
+        let position = aircraft.position().map(Location::from_point);
+        Self {
+            aircraft,
+            label: aircraft.label(display),
+            distance_km: position.as_ref().map(|p| home.distance_to(p)),
+            bearing_deg: position.as_ref().map(|p| home.bearing_to(p)),
+            age_secs: aircraft
+                .time_position
+                .map(|t| ((now - t).num_milliseconds() as f64 / 1000.0).max(0.0)),
+        }
+    }
+
+    pub fn text(&self, column: ListColumn) -> String {
+        let aircraft = self.aircraft;
+        let number = |value: Option<f64>, format: fn(f64) -> String| value.map(format).unwrap_or_else(|| "-".to_string());
+        match column {
+            ListColumn::Callsign => self.label.clone(),
+            ListColumn::Icao24 => aircraft.icao24.clone(),
+            ListColumn::Altitude => number(aircraft.altitude, |v| format!("{:.0}", v)),
+            ListColumn::Speed => number(aircraft.velocity, |v| format!("{:.0}", v)),
+            ListColumn::Track => number(aircraft.true_track, |v| format!("{:03.0}°", v)),
+            ListColumn::Distance => number(self.distance_km, |v| format!("{:.1}", v)),
+            ListColumn::Bearing => number(self.bearing_deg, |v| format!("{:03.0}°", v)),
+            ListColumn::VerticalRate => number(aircraft.vertical_rate, |v| format!("{:+.0}", v)),
+            ListColumn::Squawk => aircraft.squawk.clone().unwrap_or_else(|| "-".to_string()),
+            ListColumn::Age => number(self.age_secs, |v| format!("{:.0}s", v)),
+        }
+    }
+
+    fn number(&self, column: ListColumn) -> Option<f64> {
+        let aircraft = self.aircraft;
+        match column {
+            ListColumn::Altitude => aircraft.altitude,
+            ListColumn::Speed => aircraft.velocity,
+            ListColumn::Track => aircraft.true_track,
+            ListColumn::Distance => self.distance_km,
+            ListColumn::Bearing => self.bearing_deg,
+            ListColumn::VerticalRate => aircraft.vertical_rate,
+            ListColumn::Age => self.age_secs,
+            ListColumn::Callsign | ListColumn::Icao24 | ListColumn::Squawk => None,
+        }
+    }
+
+    fn compare(&self, other: &Self, column: ListColumn) -> Ordering {
+        match column {
+            ListColumn::Callsign => self.label.cmp(&other.label),
+            ListColumn::Icao24 => self.aircraft.icao24.cmp(&other.aircraft.icao24),
+            ListColumn::Squawk => self.aircraft.squawk.cmp(&other.aircraft.squawk),
+            _ => match (self.number(column), other.number(column)) {
+                (Some(a), Some(b)) => a.total_cmp(&b),
+                (a, b) => a.is_some().cmp(&b.is_some()),
+            },
+        }
+    }
+
+    // Missing values sort last whichever way the column is sorted
+    fn is_missing(&self, column: ListColumn) -> bool {
+        match column {
+            ListColumn::Callsign | ListColumn::Icao24 => false,
+            ListColumn::Squawk => self.aircraft.squawk.is_none(),
+            _ => self.number(column).is_none(),
+        }
+    }
+}
+
+// Free-text search, quick filters, an optional filter expression and sort
+// order for the aircraft table
+#[derive(Debug, Clone)]
+pub struct AircraftQuery {
+    pub search: String,
+    // All selected filters must match
+    pub filters: Vec<QuickFilter>,
+    pub expression: Option<Filter>,
+    pub sort: ListColumn,
+    pub descending: bool,
+}
+
+impl Default for AircraftQuery {
+    fn default() -> Self {
+        Self {
+            search: String::new(),
+            filters: Vec::new(),
+            expression: None,
+            sort: ListColumn::Distance,
+            descending: false,
+        }
+    }
+}
+
+impl AircraftQuery {
+    pub fn has_filter(&self, filter: QuickFilter) -> bool {
+        self.filters.contains(&filter)
+    }
+
+    pub fn toggle_filter(&mut self, filter: QuickFilter) {
+        if self.has_filter(filter) {
+            self.filters.retain(|f| *f != filter);
+        } else {
+            self.filters.push(filter);
+        }
+    }
+
+    // Clicking the sorted column flips direction, another column sorts by it
+    pub fn sort_by(&mut self, column: ListColumn) {
+        if self.sort == column {
+            self.descending = !self.descending;
+        } else {
+            self.sort = column;
+            self.descending = false;
+        }
+    }
+
+    // Case-insensitive match on callsign, decoded label, address, squawk,
+    // country and airline name
+    pub fn matches_search(&self, row: &AircraftRow) -> bool {
+        let needle = self.search.trim().to_lowercase();
+        if needle.is_empty() {
+            return true;
+        }
+        let aircraft = row.aircraft;
+        let haystacks = [
+            aircraft.callsign.as_deref(),
+            Some(row.label.as_str()),
+            Some(aircraft.icao24.as_str()),
+            aircraft.squawk.as_deref(),
+            aircraft.country(),
+            aircraft.airline().map(|a| a.name),
+        ];
+        haystacks.into_iter().flatten().any(|h| h.to_lowercase().contains(&needle))
+    }
+
+    pub fn matches(&self, row: &AircraftRow, context: &FilterContext) -> bool {
+        self.filters.iter().all(|f| f.matches(row.aircraft))
+            && self.matches_search(row)
+            && self.expression.as_ref().map_or(true, |e| e.matches(row.aircraft, context))
+    }
+
+    pub fn apply<'a>(
+        &self,
+        aircraft: impl IntoIterator<Item = &'a Aircraft>,
+        context: &FilterContext,
+        display: CallsignDisplay,
+    ) -> Vec<AircraftRow<'a>> {
+        let mut rows: Vec<AircraftRow> = aircraft
+            .into_iter()
+            .map(|a| AircraftRow::new(a, context.home, context.now, display))
+            .filter(|row| self.matches(row, context))
+            .collect();
+        rows.sort_by(|a, b| {
+            let column = self.sort;
+            a.is_missing(column).cmp(&b.is_missing(column)).then_with(|| {
+                let order = a.compare(b, column);
+                if self.descending { order.reverse() } else { order }
+            })
+        });
+        rows
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn context(home: &Location) -> FilterContext {
+        FilterContext {
+            home,
+            now: Utc::now(),
+            phases: None,
+        }
+    }
+
+    fn aircraft(icao24: &str, callsign: &str, altitude: Option<f64>) -> Aircraft {
+        let mut aircraft = Aircraft::new(icao24.to_string());
+        aircraft.callsign = Some(callsign.to_string());
+        aircraft.altitude = altitude;
+        aircraft
+    }
+
+    #[test]
+    fn test_sort_keeps_missing_last() {
+        let traffic = vec![
+            aircraft("aaaaaa", "AAA1", Some(5000.0)),
+            aircraft("bbbbbb", "BBB2", None),
+            aircraft("cccccc", "CCC3", Some(30000.0)),
+        ];
+        let home = Location::london();
+        let mut query = AircraftQuery::default();
+        query.sort_by(ListColumn::Altitude);
+
+        let ids = |query: &AircraftQuery| -> Vec<String> {
+            query
+                .apply(&traffic, &context(&home), CallsignDisplay::Callsign)
+                .iter()
+                .map(|r| r.aircraft.icao24.clone())
+                .collect()
+        };
+        assert_eq!(ids(&query), ["aaaaaa", "cccccc", "bbbbbb"]);
+        query.sort_by(ListColumn::Altitude);
+        assert!(query.descending);
+        assert_eq!(ids(&query), ["cccccc", "aaaaaa", "bbbbbb"]);
+    }
+
+    #[test]
+    fn test_search_and_quick_filters() {
+        let mut emergency = aircraft("aaaaaa", "BAW123", Some(5000.0));
+        emergency.squawk = Some("7700".to_string());
+        emergency.vertical_rate = Some(-1200.0);
+        let traffic = vec![emergency, aircraft("bbbbbb", "EZY45", Some(3000.0))];
+        let home = Location::london();
+        let context = context(&home);
+
+        let mut query = AircraftQuery {
+            search: "baw".to_string(),
+            ..AircraftQuery::default()
+        };
+        assert_eq!(query.apply(&traffic, &context, CallsignDisplay::Callsign).len(), 1);
+
+        query.search.clear();
+        query.toggle_filter(QuickFilter::Emergency);
+        query.toggle_filter(QuickFilter::Descending);
+        let rows = query.apply(&traffic, &context, CallsignDisplay::Callsign);
+        assert_eq!(rows.len(), 1);
+        assert_eq!(rows[0].aircraft.icao24, "aaaaaa");
+
+        query.toggle_filter(QuickFilter::Emergency);
+        query.toggle_filter(QuickFilter::Descending);
+        assert_eq!(query.apply(&traffic, &context, CallsignDisplay::Callsign).len(), 2);
+
+        query.expression = Some(Filter::parse("altitude > 4000").unwrap());
+        assert_eq!(query.apply(&traffic, &context, CallsignDisplay::Callsign).len(), 1);
+    }
+
+    #[test]
+    fn test_row_text() {
+        let mut traffic = aircraft("aaaaaa", "AAA1", Some(5000.0));
+        traffic.vertical_rate = Some(800.0);
+        let row = AircraftRow::new(&traffic, &Location::london(), Utc::now(), CallsignDisplay::Callsign);
+        assert_eq!(row.text(ListColumn::Altitude), "5000");
+        assert_eq!(row.text(ListColumn::VerticalRate), "+800");
+        assert_eq!(row.text(ListColumn::Distance), "-");
+    }
+}
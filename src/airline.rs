@@ -0,0 +1,220 @@
+// Callsign decoding and an offline airline table keyed by ICAO designator.
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Airline {
+    pub icao: &'static str,
+    pub iata: &'static str,
+    pub name: &'static str,
+    pub telephony: &'static str,
+}
+
+const fn airline(icao: &'static str, iata: &'static str, name: &'static str, telephony: &'static str) -> Airline {
+    Airline { icao, iata, name, telephony }
+}
+
+// Sorted by ICAO designator
+static AIRLINES: &[Airline] = &[
+    airline("AAL", "AA", "American Airlines", "AMERICAN"),
+    airline("AAY", "G4", "Allegiant Air", "ALLEGIANT"),
+    airline("ACA", "AC", "Air Canada", "AIR CANADA"),
+    airline("AFR", "AF", "Air France", "AIRFRANS"),
+    airline("AIC", "AI", "Air India", "AIRINDIA"),
+    airline("AMX", "AM", "Aeromexico", "AEROMEXICO"),
+    airline("ANA", "NH", "All Nippon Airways", "ALL NIPPON"),
+    airline("ANZ", "NZ", "Air New Zealand", "NEW ZEALAND"),
+    airline("ASA", "AS", "Alaska Airlines", "ALASKA"),
+    airline("ASH", "YV", "Mesa Airlines", "AIR SHUTTLE"),
+    airline("AUA", "OS", "Austrian Airlines", "AUSTRIAN"),
+    airline("AVA", "AV", "Avianca", "AVIANCA"),
+    airline("BAW", "BA", "British Airways", "SPEEDBIRD"),
+    airline("BEL", "SN", "Brussels Airlines", "BEE-LINE"),
+    airline("CAL", "CI", "China Airlines", "DYNASTY"),
+    airline("CCA", "CA", "Air China", "AIR CHINA"),
+    airline("CES", "MU", "China Eastern Airlines", "CHINA EASTERN"),
+    airline("CFG", "DE", "Condor", "CONDOR"),
+    airline("CLX", "CV", "Cargolux", "CARGOLUX"),
+    airline("CMP", "CM", "Copa Airlines", "COPA"),
+    airline("CPA", "CX", "Cathay Pacific", "CATHAY"),
+    airline("CSN", "CZ", "China Southern Airlines", "CHINA SOUTHERN"),
+    airline("DAL", "DL", "Delta Air Lines", "DELTA"),
+    airline("DLH", "LH", "Lufthansa", "LUFTHANSA"),
+    airline("EDV", "9E", "Endeavor Air", "ENDEAVOR"),
+    airline("EIN", "EI", "Aer Lingus", "SHAMROCK"),
+    airline("EJU", "EC", "easyJet Europe", "ALPINE"),
+    airline("ENY", "MQ", "Envoy Air", "ENVOY"),
+    airline("ETD", "EY", "Etihad Airways", "ETIHAD"),
+    airline("ETH", "ET", "Ethiopian Airlines", "ETHIOPIAN"),
+    airline("EVA", "BR", "EVA Air", "EVA"),
+    airline("EWG", "EW", "Eurowings", "EUROWINGS"),
+    airline("EXS", "LS", "Jet2", "CHANNEX"),
+    airline("EZY", "U2", "easyJet", "EASY"),
+    airline("FDX", "FX", "FedEx Express", "FEDEX"),
+    airline("FFT", "F9", "Frontier Airlines", "FRONTIER FLIGHT"),
+    airline("FIN", "AY", "Finnair", "FINNAIR"),
+    airline("GIA", "GA", "Garuda Indonesia", "INDONESIA"),
+    airline("GJS", "G7", "GoJet Airlines", "LINDBERGH"),
+    airline("GTI", "5Y", "Atlas Air", "GIANT"),
+    airline("HAL", "HA", "Hawaiian Airlines", "HAWAIIAN"),
+    airline("IBE", "IB", "Iberia", "IBERIA"),
+    airline("ICE", "FI", "Icelandair", "ICEAIR"),
+    airline("JAL", "JL", "Japan Airlines", "JAPANAIR"),
+    airline("JBU", "B6", "JetBlue", "JETBLUE"),
+    airline("JIA", "OH", "PSA Airlines", "BLUE STREAK"),
+    airline("JST", "JQ", "Jetstar", "JETSTAR"),
+    airline("JZA", "QK", "Jazz Aviation", "JAZZ"),
+    airline("KAL", "KE", "Korean Air", "KOREANAIR"),
+    airline("KLM", "KL", "KLM Royal Dutch Airlines", "KLM"),
+    airline("LAN", "LA", "LATAM Airlines Chile", "LAN"),
+    airline("LOG", "LM", "Loganair", "LOGAN"),
+    airline("LOT", "LO", "LOT Polish Airlines", "POLLOT"),
+    airline("MAS", "MH", "Malaysia Airlines", "MALAYSIAN"),
+    airline("NAX", "DY", "Norwegian Air Shuttle", "NOR SHUTTLE"),
+    airline("NKS", "NK", "Spirit Airlines", "SPIRIT WINGS"),
+    airline("PAL", "PR", "Philippine Airlines", "PHILIPPINE"),
+    airline("QFA", "QF", "Qantas", "QANTAS"),
+    airline("QTR", "QR", "Qatar Airways", "QATARI"),
+    airline("QXE", "QX", "Horizon Air", "HORIZON"),
+    airline("ROU", "RV", "Air Canada Rouge", "ROUGE"),
+    airline("RPA", "YX", "Republic Airways", "BRICKYARD"),
+    airline("RYR", "FR", "Ryanair", "RYANAIR"),
+    airline("SAS", "SK", "Scandinavian Airlines", "SCANDINAVIAN"),
+    airline("SCX", "SY", "Sun Country Airlines", "SUN COUNTRY"),
+    airline("SIA", "SQ", "Singapore Airlines", "SINGAPORE"),
+    airline("SKW", "OO", "SkyWest Airlines", "SKYWEST"),
+    airline("SVA", "SV", "Saudia", "SAUDIA"),
+    airline("SWA", "WN", "Southwest Airlines", "SOUTHWEST"),
+    airline("SWR", "LX", "Swiss International Air Lines", "SWISS"),
+    airline("TAM", "JJ", "LATAM Airlines Brasil", "TAM"),
+    airline("TAP", "TP", "TAP Air Portugal", "AIR PORTUGAL"),
+    airline("THA", "TG", "Thai Airways", "THAI"),
+    airline("THY", "TK", "Turkish Airlines", "TURKISH"),
+    airline("TOM", "BY", "TUI Airways", "TOMSON"),
+    airline("TSC", "TS", "Air Transat", "AIR TRANSAT"),
+    airline("UAE", "EK", "Emirates", "EMIRATES"),
+    airline("UAL", "UA", "United Airlines", "UNITED"),
+    airline("UPS", "5X", "UPS Airlines", "UPS"),
+    airline("VIR", "VS", "Virgin Atlantic", "VIRGIN"),
+    airline("VLG", "VY", "Vueling", "VUELING"),
+    airline("VOZ", "VA", "Virgin Australia", "VELOCITY"),
+    airline("WJA", "WS", "WestJet", "WESTJET"),
+    airline("WZZ", "W6", "Wizz Air", "WIZZ AIR"),
+];
+
+pub fn lookup_airline(icao: &str) -> Option<&'static Airline> {
+    let icao = icao.trim().to_ascii_uppercase();
+    AIRLINES
+        .binary_search_by(|a| a.icao.cmp(icao.as_str()))
+        .ok()
+        .map(|idx| &AIRLINES[idx])
+}
+
+pub fn airlines() -> &'static [Airline] {
+    AIRLINES
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ParsedCallsign {
+    pub raw: String,
+    pub airline_icao: Option<String>,
+    pub flight_number: Option<String>,
+}
+
+impl ParsedCallsign {
+    // Airline callsigns are a 3-letter ICAO designator followed by a flight
+    // number that starts with a digit (BAW123, DLH4AB). Anything else, such
+    // as a registration (N123AB, GABCD), is kept as-is.
+    pub fn parse(callsign: &str) -> Option<Self> {
+        let raw = callsign.trim().to_ascii_uppercase();
+        if raw.is_empty() {
+            return None;
+        }
+
+        let bytes = raw.as_bytes();
+        let is_airline = raw.len() >= 4
+            && raw.len() <= 8
+            && bytes[..3].iter().all(|b| b.is_ascii_alphabetic())
+            && bytes[3].is_ascii_digit()
+            && bytes[3..].iter().all(|b| b.is_ascii_alphanumeric());
+
+        if is_airline {
+            Some(Self {
+                airline_icao: Some(raw[..3].to_string()),
+                flight_number: Some(raw[3..].to_string()),
+                raw,
+            })
+        } else {
+            Some(Self {
+                raw,
+                airline_icao: None,
+                flight_number: None,
+            })
+        }
+    }
+
+    pub fn airline(&self) -> Option<&'static Airline> {
+        self.airline_icao.as_deref().and_then(lookup_airline)
+    }
+
+    fn display_number(&self) -> Option<&str> {
+        self.flight_number.as_deref().map(|n| {
+            let trimmed = n.trim_start_matches('0');
+            if trimmed.is_empty() { n } else { trimmed }
+        })
+    }
+
+    // e.g. "British Airways 123"
+    pub fn airline_flight(&self) -> Option<String> {
+        let airline = self.airline()?;
+        Some(format!("{} {}", airline.name, self.display_number()?))
+    }
+
+    // e.g. "BA123"
+    pub fn iata_flight(&self) -> Option<String> {
+        let airline = self.airline()?;
+        Some(format!("{}{}", airline.iata, self.display_number()?))
+    }
+
+    // e.g. "SPEEDBIRD 123"
+    pub fn telephony(&self) -> Option<String> {
+        let airline = self.airline()?;
+        Some(format!("{} {}", airline.telephony, self.display_number()?))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_airlines_sorted() {
+        for pair in AIRLINES.windows(2) {
+            assert!(pair[0].icao < pair[1].icao, "{} >= {}", pair[0].icao, pair[1].icao);
+        }
+    }
+
+    #[test]
+    fn test_parse_airline_callsign() {
+        let parsed = ParsedCallsign::parse("baw123  ").unwrap();
+        assert_eq!(parsed.airline_icao.as_deref(), Some("BAW"));
+        assert_eq!(parsed.flight_number.as_deref(), Some("123"));
+        assert_eq!(parsed.airline_flight().as_deref(), Some("British Airways 123"));
+        assert_eq!(parsed.iata_flight().as_deref(), Some("BA123"));
+        assert_eq!(parsed.telephony().as_deref(), Some("SPEEDBIRD 123"));
+
+        let parsed = ParsedCallsign::parse("DLH04AB").unwrap();
+        assert_eq!(parsed.flight_number.as_deref(), Some("04AB"));
+        assert_eq!(parsed.iata_flight().as_deref(), Some("LH4AB"));
+    }
+
+    #[test]
+    fn test_parse_non_airline_callsign() {
+        let parsed = ParsedCallsign::parse("N123AB").unwrap();
+        assert!(parsed.airline_icao.is_none());
+        assert!(parsed.airline().is_none());
+
+        let parsed = ParsedCallsign::parse("GABCD").unwrap();
+        assert!(parsed.flight_number.is_none());
+
+        assert!(ParsedCallsign::parse("   ").is_none());
+    }
+}
@@ -0,0 +1,439 @@
+use crate::geo::Location;
+use anyhow::{anyhow, Context, Result};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::path::Path;
+use tracing::warn;
+
+// Small extract in OurAirports CSV format covering the preset locations.
+// Import the full airports.csv/runways.csv for real coverage.
+const BUNDLED_AIRPORTS: &str = include_str!("../data/airports.csv");
+const BUNDLED_RUNWAYS: &str = include_str!("../data/runways.csv");
+
+const CELL_SIZE_DEG: f64 = 1.0;
+const FEET_PER_KM: f64 = 3280.84;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum AirportKind {
+    Large,
+    Medium,
+    Small,
+    Heliport,
+    Seaplane,
+    Closed,
+    Other,
+}
+
+impl AirportKind {
+    fn from_csv(value: &str) -> Self {
+        match value {
+            "large_airport" => AirportKind::Large,
+            "medium_airport" => AirportKind::Medium,
+            "small_airport" => AirportKind::Small,
+            "heliport" => AirportKind::Heliport,
+            "seaplane_base" => AirportKind::Seaplane,
+            "closed" => AirportKind::Closed,
+            _ => AirportKind::Other,
+        }
+    }
+
+    fn rank(&self) -> u8 {
+        match self {
+            AirportKind::Large => 0,
+            AirportKind::Medium => 1,
+            AirportKind::Small => 2,
+            AirportKind::Seaplane => 3,
+            AirportKind::Heliport => 4,
+            AirportKind::Other => 5,
+            AirportKind::Closed => 6,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Default)]
+pub enum AirportCodeStyle {
+    #[default]
+    Icao,
+    Iata,
+}
+
+#[derive(Debug, Clone)]
+pub struct Runway {
+    pub le_ident: String,
+    pub he_ident: String,
+    pub length_ft: Option<f64>,
+    pub width_ft: Option<f64>,
+    pub surface: String,
+    pub closed: bool,
+    pub le_position: Option<Location>,
+    pub he_position: Option<Location>,
+    pub le_heading: Option<f64>,
+}
+
+impl Runway {
+    pub fn designator(&self) -> String {
+        format!("{}/{}", self.le_ident, self.he_ident)
+    }
+
+    pub fn heading(&self) -> Option<f64> {
+        self.le_heading.or_else(|| match (&self.le_position, &self.he_position) {
+            (Some(le), Some(he)) => Some(le.bearing_to(he)),
+            _ => None,
+        })
+    }
+
+    // Threshold to threshold; derives the far end from heading and length
+    // when the dataset only gives one end.
+    pub fn centerline(&self) -> Option<(Location, Location)> {
+        match (&self.le_position, &self.he_position) {
+            (Some(le), Some(he)) => Some((le.clone(), he.clone())),
+            (Some(le), None) => {
+                let length_km = self.length_ft? / FEET_PER_KM;
+                Some((le.clone(), le.point_at_distance(length_km, self.le_heading?)))
+            }
+            (None, Some(he)) => {
+                let length_km = self.length_ft? / FEET_PER_KM;
+                let heading = (self.le_heading? + 180.0) % 360.0;
+                Some((he.point_at_distance(length_km, heading), he.clone()))
+            }
+            (None, None) => None,
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct Airport {
+    pub ident: String,
+    pub kind: AirportKind,
+    pub name: String,
+    pub lat: f64,
+    pub lon: f64,
+    pub elevation_ft: Option<f64>,
+    pub iso_country: String,
+    pub municipality: Option<String>,
+    pub gps_code: Option<String>,
+    pub iata_code: Option<String>,
+    pub runways: Vec<Runway>,
+}
+
+impl Airport {
+    pub fn location(&self) -> Location {
+        Location::with_name(self.lat, self.lon, format!("{} - {}", self.code(AirportCodeStyle::Icao), self.name))
+    }
+
+    pub fn icao_code(&self) -> &str {
+        self.gps_code.as_deref().unwrap_or(&self.ident)
+    }
+
+    pub fn code(&self, style: AirportCodeStyle) -> &str {
+        match style {
+            AirportCodeStyle::Icao => self.icao_code(),
+            AirportCodeStyle::Iata => self.iata_code.as_deref().unwrap_or_else(|| self.icao_code()),
+        }
+    }
+
+    pub fn is_airfield(&self) -> bool {
+        matches!(self.kind, AirportKind::Large | AirportKind::Medium | AirportKind::Small)
+    }
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct AirportDatabase {
+    airports: Vec<Airport>,
+    grid: HashMap<(i32, i32), Vec<usize>>,
+}
+
+impl AirportDatabase {
+    pub fn bundled() -> Self {
+        Self::from_csv(BUNDLED_AIRPORTS, BUNDLED_RUNWAYS).expect("bundled airport data is valid")
+    }
+
+    // Expects OurAirports `airports.csv` and optionally `runways.csv` in `dir`
+    pub fn load_dir(dir: &Path) -> Result<Self> {
+        let airports_path = dir.join("airports.csv");
+        let airports = std::fs::read_to_string(&airports_path)
+            .with_context(|| format!("Failed to read {}", airports_path.display()))?;
+        let runways = std::fs::read_to_string(dir.join("runways.csv")).unwrap_or_default();
+        Self::from_csv(&airports, &runways)
+    }
+
+    pub fn from_csv(airports_csv: &str, runways_csv: &str) -> Result<Self> {
+        let mut airports = Vec::new();
+        let mut by_ident = HashMap::new();
+        let mut skipped = 0;
+
+        let csv = Csv::parse(airports_csv)?;
+        for row in csv.rows() {
+            let lat = row.number("latitude_deg");
+            let lon = row.number("longitude_deg");
+            let (Some(lat), Some(lon), Some(ident)) = (lat, lon, row.text("ident")) else {
+                skipped += 1;
+                continue;
+            };
+
+            by_ident.insert(ident.clone(), airports.len());
+            airports.push(Airport {
+                kind: AirportKind::from_csv(row.get("type")),
+                name: row.text("name").unwrap_or_else(|| ident.clone()),
+                ident,
+                lat,
+                lon,
+                elevation_ft: row.number("elevation_ft"),
+                iso_country: row.get("iso_country").to_string(),
+                municipality: row.text("municipality"),
+                gps_code: row.text("icao_code").or_else(|| row.text("gps_code")),
+                iata_code: row.text("iata_code"),
+                runways: Vec::new(),
+            });
+        }
+
+        if skipped > 0 {
+            warn!("Skipped {} airport rows without an ident or position", skipped);
+        }
+
+        if !runways_csv.trim().is_empty() {
+            let csv = Csv::parse(runways_csv)?;
+            for row in csv.rows() {
+                let Some(&idx) = by_ident.get(row.get("airport_ident")) else {
+                    continue;
+                };
+                let position = |prefix: &str| {
+                    match (row.number(&format!("{}_latitude_deg", prefix)), row.number(&format!("{}_longitude_deg", prefix))) {
+                        (Some(lat), Some(lon)) => Some(Location::new(lat, lon)),
+                        _ => None,
+                    }
+                };
+
+                airports[idx].runways.push(Runway {
+                    le_ident: row.get("le_ident").to_string(),
+                    he_ident: row.get("he_ident").to_string(),
+                    length_ft: row.number("length_ft"),
+                    width_ft: row.number("width_ft"),
+                    surface: row.get("surface").to_string(),
+                    closed: row.get("closed") == "1",
+                    le_position: position("le"),
+                    he_position: position("he"),
+                    le_heading: row.number("le_heading_degT"),
+                });
+            }
+        }
+
+        Ok(Self::from_airports(airports))
+    }
+
+    pub fn from_airports(airports: Vec<Airport>) -> Self {
+        let mut grid: HashMap<(i32, i32), Vec<usize>> = HashMap::new();
+        for (idx, airport) in airports.iter().enumerate() {
+            grid.entry(cell(airport.lat, airport.lon)).or_default().push(idx);
+        }
+        Self { airports, grid }
+    }
+
+    pub fn len(&self) -> usize {
+        self.airports.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.airports.is_empty()
+    }
+
+    pub fn airports(&self) -> &[Airport] {
+        &self.airports
+    }
+
+    pub fn within_radius(&self, center: &Location, radius_km: f64) -> Vec<(&Airport, f64)> {
+        let lat_span = radius_km / 111.0;
+        let lon_span = radius_km / (111.0 * center.lat.to_radians().cos().abs().max(0.01));
+        let (min_row, min_col) = cell(center.lat - lat_span, center.lon - lon_span);
+        let (max_row, max_col) = cell(center.lat + lat_span, center.lon + lon_span);
+
+        let mut found = Vec::new();
+        for row in min_row..=max_row {
+            for col in min_col..=max_col {
+                let Some(indices) = self.grid.get(&(row, col)) else {
+                    continue;
+                };
+                for &idx in indices {
+                    let airport = &self.airports[idx];
+                    let distance = center.distance_to(&Location::new(airport.lat, airport.lon));
+                    if distance <= radius_km {
+                        found.push((airport, distance));
+                    }
+                }
+            }
+        }
+
+        found.sort_by(|a, b| a.1.total_cmp(&b.1));
+        found
+    }
+
+    pub fn nearest(&self, center: &Location, max_km: f64) -> Option<(&Airport, f64)> {
+        self.within_radius(center, max_km)
+            .into_iter()
+            .find(|(airport, _)| airport.is_airfield())
+    }
+
+    pub fn find(&self, code: &str) -> Option<&Airport> {
+        let code = code.trim();
+        self.airports.iter().find(|a| {
+            a.ident.eq_ignore_ascii_case(code)
+                || a.gps_code.as_deref().is_some_and(|c| c.eq_ignore_ascii_case(code))
+                || a.iata_code.as_deref().is_some_and(|c| c.eq_ignore_ascii_case(code))
+        })
+    }
+
+    // Case-insensitive match on codes, name and municipality, biggest first
+    pub fn search(&self, query: &str, limit: usize) -> Vec<&Airport> {
+        let query = query.trim().to_lowercase();
+        if query.is_empty() {
+            return Vec::new();
+        }
+
+        let mut matches: Vec<&Airport> = self
+            .airports
+            .iter()
+            .filter(|a| a.kind != AirportKind::Closed)
+            .filter(|a| {
+                a.ident.to_lowercase() == query
+                    || a.iata_code.as_deref().is_some_and(|c| c.to_lowercase() == query)
+                    || a.name.to_lowercase().contains(&query)
+                    || a.municipality.as_deref().is_some_and(|m| m.to_lowercase().contains(&query))
+            })
+            .collect();
+
+        matches.sort_by_key(|a| a.kind.rank());
+        matches.truncate(limit);
+        matches
+    }
+}
+
+fn cell(lat: f64, lon: f64) -> (i32, i32) {
+    ((lat / CELL_SIZE_DEG).floor() as i32, (lon / CELL_SIZE_DEG).floor() as i32)
+}
+
+// Minimal reader for the OurAirports CSV dialect: comma separated, optional
+// double quotes with "" escapes, one record per line.
+struct Csv {
+    header: HashMap<String, usize>,
+    rows: Vec<Vec<String>>,
+}
+
+struct CsvRow<'a> {
+    header: &'a HashMap<String, usize>,
+    fields: &'a [String],
+}
+
+impl Csv {
+    fn parse(data: &str) -> Result<Self> {
+        let mut lines = data.lines();
+        let header_line = lines.next().ok_or_else(|| anyhow!("CSV data is empty"))?;
+        let header = parse_csv_line(header_line.trim_start_matches('\u{feff}'))
+            .into_iter()
+            .enumerate()
+            .map(|(idx, name)| (name, idx))
+            .collect();
+        let rows = lines
+            .filter(|line| !line.trim().is_empty())
+            .map(parse_csv_line)
+            .collect();
+        Ok(Self { header, rows })
+    }
+
+    fn rows(&self) -> impl Iterator<Item = CsvRow<'_>> {
+        self.rows.iter().map(|fields| CsvRow {
+            header: &self.header,
+            fields,
+        })
+    }
+}
+
+impl<'a> CsvRow<'a> {
+    fn get(&self, column: &str) -> &'a str {
+        self.header
+            .get(column)
+            .and_then(|&idx| self.fields.get(idx))
+            .map(|s| s.trim())
+            .unwrap_or("")
+    }
+
+    fn text(&self, column: &str) -> Option<String> {
+        let value = self.get(column);
+        if value.is_empty() {
+            None
+        } else {
+            Some(value.to_string())
+        }
+    }
+
+    fn number(&self, column: &str) -> Option<f64> {
+        self.get(column).parse().ok()
+    }
+}
+
+pub(crate) fn parse_csv_line(line: &str) -> Vec<String> {
+    let mut fields = Vec::new();
+    let mut field = String::new();
+    let mut in_quotes = false;
+    let mut chars = line.chars().peekable();
+
+    while let Some(c) = chars.next() {
+        match c {
+            '"' if in_quotes && chars.peek() == Some(&'"') => {
+                field.push('"');
+                chars.next();
+            }
+            '"' => in_quotes = !in_quotes,
+            ',' if !in_quotes => fields.push(std::mem::take(&mut field)),
+            _ => field.push(c),
+        }
+    }
+    fields.push(field);
+    fields
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_csv_line() {
+        let fields = parse_csv_line(r#"1,"KSFO","San Francisco ""SFO"", CA",,37.6"#);
+        assert_eq!(fields, vec!["1", "KSFO", "San Francisco \"SFO\", CA", "", "37.6"]);
+    }
+
+    #[test]
+    fn test_bundled_database() {
+        let db = AirportDatabase::bundled();
+        let sfo = db.find("SFO").unwrap();
+        assert_eq!(sfo.icao_code(), "KSFO");
+        assert_eq!(sfo.runways.len(), 4);
+
+        let runway = sfo.runways.iter().find(|r| r.le_ident == "28R" || r.he_ident == "28R").unwrap();
+        let (le, he) = runway.centerline().unwrap();
+        let length_km = le.distance_to(&he);
+        assert!((length_km - 11870.0 / FEET_PER_KM).abs() < 0.05);
+    }
+
+    #[test]
+    fn test_spatial_queries() {
+        let db = AirportDatabase::bundled();
+        let nearby = db.within_radius(&Location::san_francisco(), 80.0);
+        let codes: Vec<&str> = nearby.iter().map(|(a, _)| a.icao_code()).collect();
+        assert_eq!(codes, vec!["KSFO", "KOAK", "KSJC"]);
+
+        let (nearest, _) = db.nearest(&Location::london(), 50.0).unwrap();
+        assert_eq!(nearest.icao_code(), "EGLC");
+        assert!(db.nearest(&Location::new(0.0, 0.0), 50.0).is_none());
+    }
+
+    #[test]
+    fn test_skips_incomplete_rows() {
+        let airports = "ident,type,name,latitude_deg,longitude_deg\n\
+            EGLL,large_airport,Heathrow,51.47,-0.46\n\
+            ,small_airport,No ident,51.0,-1.0\n\
+            XXXX,heliport,No position,,\n";
+        let db = AirportDatabase::from_csv(airports, "").unwrap();
+        assert_eq!(db.len(), 1);
+        assert_eq!(db.airports()[0].ident, "EGLL");
+    }
+}
@@ -0,0 +1,539 @@
+use crate::aircraft::Aircraft;
+use crate::geo::Location;
+use crate::geojson;
+use crate::spatial::BoxGrid;
+use anyhow::{anyhow, Context, Result};
+use egui::Color32;
+use geo::{BoundingRect, Contains, Coord, Geometry, LineString, Point, Polygon};
+use std::path::Path;
+use tracing::debug;
+
+const KM_PER_NM: f64 = 1.852;
+const ARC_STEP_DEG: f64 = 5.0;
+const GRID_CELL_DEG: f64 = 0.5;
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum AirspaceClass {
+    A,
+    B,
+    C,
+    D,
+    E,
+    F,
+    G,
+    Ctr,
+    Restricted,
+    Danger,
+    Prohibited,
+    Tmz,
+    Rmz,
+    Other(String),
+}
+
+impl AirspaceClass {
+    pub fn from_code(code: &str) -> Self {
+        match code.trim().to_ascii_uppercase().as_str() {
+            "A" => AirspaceClass::A,
+            "B" => AirspaceClass::B,
+            "C" => AirspaceClass::C,
+            "D" => AirspaceClass::D,
+            "E" => AirspaceClass::E,
+            "F" => AirspaceClass::F,
+            "G" => AirspaceClass::G,
+            "CTR" => AirspaceClass::Ctr,
+            "R" | "RESTRICTED" => AirspaceClass::Restricted,
+            "Q" | "DANGER" => AirspaceClass::Danger,
+            "P" | "PROHIBITED" => AirspaceClass::Prohibited,
+            "TMZ" => AirspaceClass::Tmz,
+            "RMZ" => AirspaceClass::Rmz,
+            other => AirspaceClass::Other(other.to_string()),
+        }
+    }
+
+    pub fn code(&self) -> &str {
+        match self {
+            AirspaceClass::A => "A",
+            AirspaceClass::B => "B",
+            AirspaceClass::C => "C",
+            AirspaceClass::D => "D",
+            AirspaceClass::E => "E",
+            AirspaceClass::F => "F",
+            AirspaceClass::G => "G",
+            AirspaceClass::Ctr => "CTR",
+            AirspaceClass::Restricted => "R",
+            AirspaceClass::Danger => "Q",
+            AirspaceClass::Prohibited => "P",
+            AirspaceClass::Tmz => "TMZ",
+            AirspaceClass::Rmz => "RMZ",
+            AirspaceClass::Other(code) => code,
+        }
+    }
+
+    pub fn color(&self) -> Color32 {
+        match self {
+            AirspaceClass::A | AirspaceClass::B => Color32::from_rgb(80, 120, 255),
+            AirspaceClass::C | AirspaceClass::D | AirspaceClass::Ctr => Color32::from_rgb(60, 170, 255),
+            AirspaceClass::E | AirspaceClass::F | AirspaceClass::G => Color32::from_rgb(120, 160, 120),
+            AirspaceClass::Restricted | AirspaceClass::Danger => Color32::from_rgb(255, 90, 90),
+            AirspaceClass::Prohibited => Color32::from_rgb(255, 30, 30),
+            AirspaceClass::Tmz | AirspaceClass::Rmz => Color32::from_rgb(200, 120, 255),
+            AirspaceClass::Other(_) => Color32::from_rgb(150, 150, 150),
+        }
+    }
+
+    // Special-use areas are drawn dashed, controlled airspace solid
+    pub fn is_special_use(&self) -> bool {
+        matches!(
+            self,
+            AirspaceClass::Restricted | AirspaceClass::Danger | AirspaceClass::Prohibited | AirspaceClass::Tmz | AirspaceClass::Rmz
+        )
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum AltitudeLimit {
+    Surface,
+    Msl(f64),
+    Agl(f64),
+    FlightLevel(f64),
+    Unlimited,
+}
+
+impl AltitudeLimit {
+    // Accepts the usual OpenAir spellings: SFC, GND, UNL, FL65, 3500ft MSL,
+    // 1500 ft AGL, 2000 (feet MSL assumed), 900m
+    pub fn parse(text: &str) -> Result<Self> {
+        let upper = text.trim().to_ascii_uppercase();
+        if upper.is_empty() {
+            return Err(anyhow!("Empty altitude limit"));
+        }
+        if ["SFC", "GND", "0"].contains(&upper.as_str()) {
+            return Ok(AltitudeLimit::Surface);
+        }
+        if upper.starts_with("UNL") {
+            return Ok(AltitudeLimit::Unlimited);
+        }
+        if let Some(level) = upper.strip_prefix("FL") {
+            let level: f64 = level.trim().parse().map_err(|_| anyhow!("Invalid flight level {}", text))?;
+            return Ok(AltitudeLimit::FlightLevel(level));
+        }
+
+        let digits: String = upper.chars().take_while(|c| c.is_ascii_digit() || *c == '.').collect();
+        let value: f64 = digits.parse().map_err(|_| anyhow!("Invalid altitude limit {}", text))?;
+        let rest = upper[digits.len()..].trim();
+        let feet = if rest.starts_with('M') && !rest.starts_with("MSL") { value * 3.28084 } else { value };
+
+        if rest.contains("AGL") || rest.contains("GND") || rest.contains("SFC") {
+            Ok(AltitudeLimit::Agl(feet))
+        } else {
+            Ok(AltitudeLimit::Msl(feet))
+        }
+    }
+
+    // Approximate feet MSL; AGL limits assume ground at `ground_ft`
+    pub fn feet(&self, ground_ft: f64) -> f64 {
+        match self {
+            AltitudeLimit::Surface => f64::NEG_INFINITY,
+            AltitudeLimit::Msl(ft) => *ft,
+            AltitudeLimit::Agl(ft) => ground_ft + ft,
+            AltitudeLimit::FlightLevel(level) => level * 100.0,
+            AltitudeLimit::Unlimited => f64::INFINITY,
+        }
+    }
+
+    pub fn label(&self) -> String {
+        match self {
+            AltitudeLimit::Surface => "SFC".to_string(),
+            AltitudeLimit::Msl(ft) => format!("{:.0}ft", ft),
+            AltitudeLimit::Agl(ft) => format!("{:.0}ft AGL", ft),
+            AltitudeLimit::FlightLevel(level) => format!("FL{:03.0}", level),
+            AltitudeLimit::Unlimited => "UNL".to_string(),
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct Airspace {
+    pub name: String,
+    pub class: AirspaceClass,
+    pub floor: AltitudeLimit,
+    pub ceiling: AltitudeLimit,
+    pub polygon: Polygon<f64>,
+}
+
+impl Airspace {
+    // `ground_ft` is the terrain elevation under the aircraft, used for AGL
+    // floors and ceilings
+    pub fn contains(&self, point: Point<f64>, altitude_ft: f64, ground_ft: f64) -> bool {
+        altitude_ft >= self.floor.feet(ground_ft)
+            && altitude_ft <= self.ceiling.feet(ground_ft)
+            && self.polygon.contains(&point)
+    }
+
+    pub fn uses_agl(&self) -> bool {
+        matches!(self.floor, AltitudeLimit::Agl(_)) || matches!(self.ceiling, AltitudeLimit::Agl(_))
+    }
+
+    pub fn vertical_label(&self) -> String {
+        format!("{}-{}", self.floor.label(), self.ceiling.label())
+    }
+}
+
+pub fn parse_openair(text: &str) -> Result<Vec<Airspace>> {
+    let mut parser = OpenAirParser::default();
+    for (line_no, line) in text.lines().enumerate() {
+        parser
+            .line(line)
+            .with_context(|| format!("OpenAir line {}: {}", line_no + 1, line.trim()))?;
+    }
+    parser.finish();
+    Ok(parser.airspaces)
+}
+
+#[derive(Default)]
+struct OpenAirParser {
+    airspaces: Vec<Airspace>,
+    class: Option<AirspaceClass>,
+    name: String,
+    floor: Option<AltitudeLimit>,
+    ceiling: Option<AltitudeLimit>,
+    points: Vec<Coord<f64>>,
+    center: Option<Location>,
+    clockwise: bool,
+}
+
+impl OpenAirParser {
+    fn line(&mut self, line: &str) -> Result<()> {
+        let line = line.split('*').next().unwrap_or("").trim();
+        if line.is_empty() {
+            return Ok(());
+        }
+        let (command, args) = line.split_once(char::is_whitespace).unwrap_or((line, ""));
+        let args = args.trim();
+
+        match command.to_ascii_uppercase().as_str() {
+            "AC" => {
+                self.finish();
+                self.class = Some(AirspaceClass::from_code(args));
+                self.clockwise = true;
+            }
+            "AN" => self.name = args.to_string(),
+            "AL" => self.floor = Some(AltitudeLimit::parse(args)?),
+            "AH" => self.ceiling = Some(AltitudeLimit::parse(args)?),
+            "DP" => {
+                let point = parse_coordinate(args)?;
+                self.points.push(Coord { x: point.lon, y: point.lat });
+            }
+            "V" => {
+                let (key, value) = args.split_once('=').ok_or_else(|| anyhow!("Malformed variable"))?;
+                match key.trim().to_ascii_uppercase().as_str() {
+                    "X" => self.center = Some(parse_coordinate(value)?),
+                    "D" => self.clockwise = value.trim() != "-",
+                    _ => {}
+                }
+            }
+            "DC" => {
+                let radius_nm: f64 = args.parse().map_err(|_| anyhow!("Invalid radius"))?;
+                let center = self.center()?;
+                self.arc(&center, radius_nm * KM_PER_NM, 0.0, 360.0, true);
+            }
+            "DA" => {
+                let parts: Vec<f64> = args
+                    .split(',')
+                    .map(|p| p.trim().parse().map_err(|_| anyhow!("Invalid arc parameter {}", p)))
+                    .collect::<Result<_>>()?;
+                let [radius_nm, start, end] = parts[..] else {
+                    return Err(anyhow!("DA expects radius, start and end angles"));
+                };
+                let center = self.center()?;
+                self.arc(&center, radius_nm * KM_PER_NM, start, end, self.clockwise);
+            }
+            "DB" => {
+                let (from, to) = args.split_once(',').ok_or_else(|| anyhow!("DB expects two coordinates"))?;
+                let (from, to) = (parse_coordinate(from)?, parse_coordinate(to)?);
+                let center = self.center()?;
+                let radius_km = center.distance_to(&from);
+                self.arc(&center, radius_km, center.bearing_to(&from), center.bearing_to(&to), self.clockwise);
+            }
+            // Labels, pens, brushes and airways aren't used here
+            other => debug!("Skipping OpenAir command {}", other),
+        }
+        Ok(())
+    }
+
+    fn center(&self) -> Result<Location> {
+        self.center.clone().ok_or_else(|| anyhow!("Arc without V X= centre"))
+    }
+
+    fn arc(&mut self, center: &Location, radius_km: f64, start: f64, end: f64, clockwise: bool) {
+        let mut sweep = if clockwise { end - start } else { start - end };
+        if sweep <= 0.0 {
+            sweep += 360.0;
+        }
+        let steps = (sweep / ARC_STEP_DEG).ceil().max(1.0) as usize;
+        let direction = if clockwise { 1.0 } else { -1.0 };
+
+        for step in 0..=steps {
+            let bearing = start + direction * sweep * step as f64 / steps as f64;
+            let point = center.point_at_distance(radius_km, bearing.rem_euclid(360.0));
+            self.points.push(Coord { x: point.lon, y: point.lat });
+        }
+    }
+
+    fn finish(&mut self) {
+        let points = std::mem::take(&mut self.points);
+        let class = self.class.take();
+        let name = std::mem::take(&mut self.name);
+        self.center = None;
+
+        let floor = self.floor.take().unwrap_or(AltitudeLimit::Surface);
+        let ceiling = self.ceiling.take().unwrap_or(AltitudeLimit::Unlimited);
+
+        if let Some(class) = class.filter(|_| points.len() >= 3) {
+            self.airspaces.push(Airspace {
+                name,
+                class,
+                floor,
+                ceiling,
+                polygon: Polygon::new(LineString::from(points), Vec::new()),
+            });
+        }
+    }
+}
+
+// 53:24:25 N 006:21:11 E, 53:24.4 N 6:21.2 E or 53.4069N 6.3531E
+fn parse_coordinate(text: &str) -> Result<Location> {
+    let upper = text.trim().to_ascii_uppercase();
+    let split = upper
+        .find(['N', 'S'])
+        .ok_or_else(|| anyhow!("Coordinate without N/S hemisphere: {}", text))?;
+    let (lat_part, lon_part) = upper.split_at(split + 1);
+
+    let lat = parse_dms(lat_part)?;
+    let lon = parse_dms(lon_part)?;
+    if !(-90.0..=90.0).contains(&lat) || !(-180.0..=180.0).contains(&lon) {
+        return Err(anyhow!("Coordinate out of range: {}", text));
+    }
+    Ok(Location::new(lat, lon))
+}
+
+fn parse_dms(text: &str) -> Result<f64> {
+    let text = text.trim();
+    let hemisphere = text
+        .chars()
+        .last()
+        .ok_or_else(|| anyhow!("Empty coordinate"))?;
+    let sign = match hemisphere {
+        'N' | 'E' => 1.0,
+        'S' | 'W' => -1.0,
+        _ => return Err(anyhow!("Missing hemisphere in {}", text)),
+    };
+
+    let mut value = 0.0;
+    let mut divisor = 1.0;
+    for part in text[..text.len() - 1].trim().split(':') {
+        let part: f64 = part.trim().parse().map_err(|_| anyhow!("Invalid coordinate {}", text))?;
+        value += part / divisor;
+        divisor *= 60.0;
+    }
+    Ok(sign * value)
+}
+
+// Polygon features with `name`, `class` and floor/ceiling properties. The
+// property names used by common exports are accepted.
+pub fn parse_geojson(text: &str) -> Result<Vec<Airspace>> {
+    let mut airspaces = Vec::new();
+    for feature in geojson::parse(text)? {
+        let class = ["class", "icaoClass", "type"]
+            .iter()
+            .find_map(|key| feature.property_str(key))
+            .map(AirspaceClass::from_code)
+            .unwrap_or(AirspaceClass::Other("?".to_string()));
+        let name = feature.property_str("name").unwrap_or("Unnamed").to_string();
+        let limit = |keys: &[&str]| -> Result<Option<AltitudeLimit>> {
+            for key in keys {
+                if let Some(text) = feature.property_str(key) {
+                    return AltitudeLimit::parse(text).map(Some);
+                }
+                if let Some(feet) = feature.property_f64(key) {
+                    return Ok(Some(AltitudeLimit::Msl(feet)));
+                }
+            }
+            Ok(None)
+        };
+        let floor = limit(&["floor", "lower", "lowerLimit"])?.unwrap_or(AltitudeLimit::Surface);
+        let ceiling = limit(&["ceiling", "upper", "upperLimit"])?.unwrap_or(AltitudeLimit::Unlimited);
+
+        let polygons = match feature.geometry {
+            Geometry::Polygon(polygon) => vec![polygon],
+            Geometry::MultiPolygon(polygons) => polygons.0,
+            _ => continue,
+        };
+        for polygon in polygons {
+            airspaces.push(Airspace {
+                name: name.clone(),
+                class: class.clone(),
+                floor,
+                ceiling,
+                polygon,
+            });
+        }
+    }
+    Ok(airspaces)
+}
+
+pub struct AirspaceDatabase {
+    airspaces: Vec<Airspace>,
+    grid: BoxGrid,
+}
+
+impl Default for AirspaceDatabase {
+    fn default() -> Self {
+        Self::new(Vec::new())
+    }
+}
+
+impl AirspaceDatabase {
+    pub fn new(airspaces: Vec<Airspace>) -> Self {
+        let mut grid = BoxGrid::new(GRID_CELL_DEG);
+        for (idx, airspace) in airspaces.iter().enumerate() {
+            if let Some(rect) = airspace.polygon.bounding_rect() {
+                grid.insert(idx, (rect.min().x, rect.min().y, rect.max().x, rect.max().y));
+            }
+        }
+        Self { airspaces, grid }
+    }
+
+    // OpenAir unless the extension says GeoJSON
+    pub fn load_files(paths: &[std::path::PathBuf]) -> Result<Self> {
+        let mut airspaces = Vec::new();
+        for path in paths {
+            airspaces.extend(load_file(path)?);
+        }
+        Ok(Self::new(airspaces))
+    }
+
+    pub fn airspaces(&self) -> &[Airspace] {
+        &self.airspaces
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.airspaces.is_empty()
+    }
+
+    pub fn containing(&self, aircraft: &Aircraft, ground_ft: f64) -> Vec<&Airspace> {
+        let (Some(point), Some(altitude)) = (aircraft.position(), aircraft.altitude) else {
+            return Vec::new();
+        };
+        self.grid
+            .query(point)
+            .iter()
+            .map(|&idx| &self.airspaces[idx])
+            .filter(|airspace| airspace.contains(point, altitude, ground_ft))
+            .collect()
+    }
+}
+
+pub fn load_file(path: &Path) -> Result<Vec<Airspace>> {
+    let text = std::fs::read_to_string(path)
+        .with_context(|| format!("Failed to read {}", path.display()))?;
+    let is_geojson = path
+        .extension()
+        .and_then(|e| e.to_str())
+        .is_some_and(|e| e.eq_ignore_ascii_case("geojson") || e.eq_ignore_ascii_case("json"));
+
+    if is_geojson {
+        parse_geojson(&text)
+    } else {
+        parse_openair(&text)
+    }
+    .with_context(|| format!("Failed to parse {}", path.display()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const SAMPLE: &str = "\
+* Sample airspace
+AC D
+AN TEST CTR
+AL SFC
+AH 2500ft MSL
+V X=51:30:00 N 000:07:00 W
+DC 5
+
+AC R
+AN DANGER BOX
+AL FL65
+AH FL195
+DP 51:00:00 N 001:00:00 W
+DP 51:00:00 N 000:00:00 E
+DP 51:20:00 N 000:00:00 E
+DP 51:20:00 N 001:00:00 W
+";
+
+    #[test]
+    fn test_parse_openair() {
+        let airspaces = parse_openair(SAMPLE).unwrap();
+        assert_eq!(airspaces.len(), 2);
+
+        let ctr = &airspaces[0];
+        assert_eq!(ctr.name, "TEST CTR");
+        assert_eq!(ctr.class, AirspaceClass::D);
+        assert_eq!(ctr.ceiling, AltitudeLimit::Msl(2500.0));
+        assert!(ctr.contains(Point::new(-0.1167, 51.5), 1500.0, 0.0));
+        assert!(!ctr.contains(Point::new(-0.1167, 51.5), 3000.0, 0.0));
+        assert!(!ctr.contains(Point::new(0.1, 51.5), 1500.0, 0.0));
+
+        let box_area = &airspaces[1];
+        assert_eq!(box_area.floor, AltitudeLimit::FlightLevel(65.0));
+        assert!(box_area.contains(Point::new(-0.5, 51.1), 10000.0, 0.0));
+    }
+
+    #[test]
+    fn test_agl_limits_follow_ground() {
+        let text = "AC E\nAN HILL TMZ\nAL 500ft AGL\nAH 3000ft AGL\nV X=51:30:00 N 000:07:00 W\nDC 5\n";
+        let tmz = &parse_openair(text).unwrap()[0];
+        let point = Point::new(-0.1167, 51.5);
+        assert!(tmz.uses_agl());
+
+        // 1200 ft MSL is inside over the sea but below the floor over 1000 ft terrain
+        assert!(tmz.contains(point, 1200.0, 0.0));
+        assert!(!tmz.contains(point, 1200.0, 1000.0));
+        assert!(tmz.contains(point, 3800.0, 1000.0));
+        assert!(!tmz.contains(point, 3800.0, 0.0));
+    }
+
+    #[test]
+    fn test_parse_altitude_limits() {
+        assert_eq!(AltitudeLimit::parse("GND").unwrap(), AltitudeLimit::Surface);
+        assert_eq!(AltitudeLimit::parse("FL 95").unwrap(), AltitudeLimit::FlightLevel(95.0));
+        assert_eq!(AltitudeLimit::parse("1500ft AGL").unwrap(), AltitudeLimit::Agl(1500.0));
+        assert_eq!(AltitudeLimit::parse("UNLTD").unwrap(), AltitudeLimit::Unlimited);
+        assert!(AltitudeLimit::parse("high").is_err());
+    }
+
+    #[test]
+    fn test_parse_coordinates() {
+        let location = parse_coordinate("53:24:25 N 006:21:11 E").unwrap();
+        assert!((location.lat - 53.40694).abs() < 1e-4);
+        assert!((location.lon - 6.35306).abs() < 1e-4);
+
+        let location = parse_coordinate("33:56.5 S 151:10.0 E").unwrap();
+        assert!((location.lat + 33.94167).abs() < 1e-4);
+        assert!(parse_coordinate("53:24:25 006:21:11").is_err());
+    }
+
+    #[test]
+    fn test_database_flags_aircraft() {
+        let db = AirspaceDatabase::new(parse_openair(SAMPLE).unwrap());
+        let mut aircraft = Aircraft::new("a12345".to_string());
+        aircraft.latitude = Some(51.5);
+        aircraft.longitude = Some(-0.1167);
+        aircraft.altitude = Some(2000.0);
+
+        let inside: Vec<&str> = db.containing(&aircraft, 0.0).iter().map(|a| a.name.as_str()).collect();
+        assert_eq!(inside, vec!["TEST CTR"]);
+    }
+}
@@ -0,0 +1,417 @@
+use crate::aircraft::Aircraft;
+use crate::config::CallsignDisplay;
+use crate::filter_expr::{Filter, FilterContext, FilterError};
+use anyhow::{anyhow, Context, Result};
+use chrono::{DateTime, Duration, Utc};
+use egui::Color32;
+use serde::{Deserialize, Serialize};
+use std::collections::{HashMap, HashSet};
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use std::process::Command;
+use tracing::warn;
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq, PartialOrd, Ord, Default)]
+pub enum Severity {
+    Info,
+    #[default]
+    Warning,
+    Critical,
+}
+
+impl Severity {
+    pub const ALL: [Severity; 3] = [Severity::Info, Severity::Warning, Severity::Critical];
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            Severity::Info => "Info",
+            Severity::Warning => "Warning",
+            Severity::Critical => "Critical",
+        }
+    }
+
+    pub fn color(&self) -> Color32 {
+        match self {
+            Severity::Info => Color32::from_rgb(100, 180, 255),
+            Severity::Warning => Color32::from_rgb(255, 190, 40),
+            Severity::Critical => Color32::from_rgb(255, 70, 70),
+        }
+    }
+
+    // Urgency hint of the freedesktop notification spec
+    fn urgency(&self) -> u8 {
+        match self {
+            Severity::Info => 0,
+            Severity::Warning => 1,
+            Severity::Critical => 2,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub enum AlertCondition {
+    // Filter expression, e.g. "altitude < 2000 and distance < 5km"
+    Expression(String),
+    // Transponder code changed since the previous update
+    SquawkChange,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct AlertActions {
+    pub banner: bool,
+    pub desktop: bool,
+    // Run through the shell with SKYRADAR_* variables describing the alert
+    #[serde(default)]
+    pub command: Option<String>,
+    #[serde(default)]
+    pub log_file: Option<PathBuf>,
+}
+
+impl Default for AlertActions {
+    fn default() -> Self {
+        Self {
+            banner: true,
+            desktop: false,
+            command: None,
+            log_file: None,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct AlertRule {
+    pub name: String,
+    pub enabled: bool,
+    pub condition: AlertCondition,
+    // Minimum time between alerts for the same aircraft
+    pub cooldown_secs: u64,
+    pub severity: Severity,
+    #[serde(default)]
+    pub actions: AlertActions,
+}
+
+impl AlertRule {
+    pub fn new(name: &str, condition: AlertCondition) -> Self {
+        Self {
+            name: name.to_string(),
+            enabled: true,
+            condition,
+            cooldown_secs: 300,
+            severity: Severity::Warning,
+            actions: AlertActions::default(),
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct Alert {
+    pub rule: String,
+    pub icao24: String,
+    pub severity: Severity,
+    pub message: String,
+    pub time: DateTime<Utc>,
+    pub actions: AlertActions,
+}
+
+impl Alert {
+    // Built-in notifications such as watchlist arrivals, which aren't user
+    // rules and only ever show a banner
+    pub fn notice(source: &str, icao24: &str, severity: Severity, message: String) -> Self {
+        Self {
+            rule: source.to_string(),
+            icao24: icao24.to_string(),
+            severity,
+            message,
+            time: Utc::now(),
+            actions: AlertActions::default(),
+        }
+    }
+
+    pub fn log_line(&self) -> String {
+        format!(
+            "{} [{}] {}: {} {}",
+            self.time.format("%Y-%m-%dT%H:%M:%SZ"),
+            self.severity.name().to_uppercase(),
+            self.rule,
+            self.icao24,
+            self.message
+        )
+    }
+}
+
+// Evaluates rules against every update, with a per-rule, per-aircraft
+// cooldown so a condition that stays true doesn't alert on every refresh
+#[derive(Default)]
+pub struct AlertEngine {
+    rules: Vec<(AlertRule, Option<Filter>)>,
+    last_fired: HashMap<(String, String), DateTime<Utc>>,
+    squawks: HashMap<String, String>,
+}
+
+impl AlertEngine {
+    // Rules with invalid expressions are kept but never fire; their errors
+    // are returned by rule name
+    pub fn set_rules(&mut self, rules: &[AlertRule]) -> Vec<(String, FilterError)> {
+        let mut errors = Vec::new();
+        self.rules = rules
+            .iter()
+            .map(|rule| {
+                let filter = match &rule.condition {
+                    AlertCondition::Expression(source) => match Filter::parse(source) {
+                        Ok(filter) => Some(filter),
+                        Err(e) => {
+                            errors.push((rule.name.clone(), e));
+                            None
+                        }
+                    },
+                    AlertCondition::SquawkChange => None,
+                };
+                (rule.clone(), filter)
+            })
+            .collect();
+        self.last_fired.retain(|(name, _), _| rules.iter().any(|r| &r.name == name));
+        errors
+    }
+
+    pub fn evaluate(&mut self, aircraft: &[Aircraft], context: &FilterContext, display: CallsignDisplay) -> Vec<Alert> {
+        let now = context.now;
+        let mut alerts = Vec::new();
+
+        for aircraft in aircraft {
+            let previous_squawk = match &aircraft.squawk {
+                Some(squawk) => self.squawks.insert(aircraft.icao24.clone(), squawk.clone()),
+                None => self.squawks.get(&aircraft.icao24).cloned(),
+            };
+
+            for (rule, filter) in self.rules.iter().filter(|(rule, _)| rule.enabled) {
+                let detail = match (&rule.condition, filter) {
+                    (AlertCondition::Expression(source), Some(filter)) => {
+                        filter.matches(aircraft, context).then(|| source.clone())
+                    }
+                    (AlertCondition::SquawkChange, _) => match (&previous_squawk, &aircraft.squawk) {
+                        (Some(before), Some(after)) if before != after => Some(format!("squawk {} → {}", before, after)),
+                        _ => None,
+                    },
+                    _ => None,
+                };
+                let Some(detail) = detail else {
+                    continue;
+                };
+
+                let key = (rule.name.clone(), aircraft.icao24.clone());
+                let cooldown = Duration::seconds(rule.cooldown_secs as i64);
+                if self.last_fired.get(&key).is_some_and(|last| now - *last < cooldown) {
+                    continue;
+                }
+                self.last_fired.insert(key, now);
+
+                alerts.push(Alert {
+                    rule: rule.name.clone(),
+                    icao24: aircraft.icao24.clone(),
+                    severity: rule.severity,
+                    message: format!("{} ({})", aircraft.label(display), detail),
+                    time: now,
+                    actions: rule.actions.clone(),
+                });
+            }
+        }
+
+        // Forget departed aircraft, but keep cooldowns still running so one
+        // that briefly drops out of the feed doesn't alert again on return
+        let present: HashSet<&str> = aircraft.iter().map(|a| a.icao24.as_str()).collect();
+        let cooldowns: HashMap<&str, Duration> = self
+            .rules
+            .iter()
+            .map(|(rule, _)| (rule.name.as_str(), Duration::seconds(rule.cooldown_secs as i64)))
+            .collect();
+        self.last_fired.retain(|(rule, icao24), last| {
+            present.contains(icao24.as_str()) || cooldowns.get(rule.as_str()).is_some_and(|c| now - *last < *c)
+        });
+        self.squawks.retain(|icao24, _| present.contains(icao24.as_str()));
+        alerts
+    }
+}
+
+// Carries out the actions that happen outside the app. Logging,
+// notifications and commands run on a background thread so slow disks or
+// handlers can't stall the UI.
+pub fn dispatch(alert: &Alert) {
+    let alert = alert.clone();
+    if alert.actions.log_file.is_some() || alert.actions.desktop || alert.actions.command.is_some() {
+        std::thread::spawn(move || {
+            if let Some(path) = &alert.actions.log_file {
+                if let Err(e) = append_log(path, &alert) {
+                    warn!("Alert log failed: {}", e);
+                }
+            }
+            if alert.actions.desktop {
+                if let Err(e) = send_desktop_notification(&alert) {
+                    warn!("Desktop notification failed: {}", e);
+                }
+            }
+            if let Some(command) = &alert.actions.command {
+                if let Err(e) = run_command(command, &alert) {
+                    warn!("Alert command failed: {}", e);
+                }
+            }
+        });
+    }
+}
+
+pub fn append_log(path: &Path, alert: &Alert) -> Result<()> {
+    let mut file = std::fs::OpenOptions::new()
+        .create(true)
+        .append(true)
+        .open(path)
+        .with_context(|| format!("Failed to open {}", path.display()))?;
+    writeln!(file, "{}", alert.log_line())?;
+    Ok(())
+}
+
+fn run_command(command: &str, alert: &Alert) -> Result<()> {
+    let mut process = if cfg!(windows) {
+        let mut process = Command::new("cmd");
+        process.arg("/C");
+        process
+    } else {
+        let mut process = Command::new("sh");
+        process.arg("-c");
+        process
+    };
+    let status = process
+        .arg(command)
+        .env("SKYRADAR_RULE", &alert.rule)
+        .env("SKYRADAR_ICAO24", &alert.icao24)
+        .env("SKYRADAR_SEVERITY", alert.severity.name())
+        .env("SKYRADAR_MESSAGE", &alert.message)
+        .status()
+        .with_context(|| format!("Failed to run `{}`", command))?;
+    if !status.success() {
+        return Err(anyhow!("`{}` exited with {}", command, status));
+    }
+    Ok(())
+}
+
+// org.freedesktop.Notifications.Notify on the session bus
+fn send_desktop_notification(alert: &Alert) -> Result<()> {
+    let connection = zbus::blocking::Connection::session()?;
+    let mut hints: HashMap<&str, zbus::zvariant::Value> = HashMap::new();
+    hints.insert("urgency", zbus::zvariant::Value::U8(alert.severity.urgency()));
+
+    let summary = format!("SkyRadar: {}", alert.rule);
+    let actions: Vec<&str> = Vec::new();
+    connection.call_method(
+        Some("org.freedesktop.Notifications"),
+        "/org/freedesktop/Notifications",
+        Some("org.freedesktop.Notifications"),
+        "Notify",
+        &("SkyRadar", 0u32, "airplane", summary.as_str(), alert.message.as_str(), actions, hints, -1i32),
+    )?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::geo::Location;
+
+    fn aircraft(altitude: f64, squawk: &str) -> Aircraft {
+        let home = Location::london();
+        let position = home.offset_by_km(1.0, 1.0);
+        let mut aircraft = Aircraft::new("a12345".to_string());
+        aircraft.callsign = Some("TEST1".to_string());
+        aircraft.latitude = Some(position.lat);
+        aircraft.longitude = Some(position.lon);
+        aircraft.altitude = Some(altitude);
+        aircraft.squawk = Some(squawk.to_string());
+        aircraft
+    }
+
+    fn evaluate(engine: &mut AlertEngine, aircraft: &[Aircraft], now: DateTime<Utc>) -> Vec<Alert> {
+        let home = Location::london();
+        let context = FilterContext {
+            home: &home,
+            now,
+            phases: None,
+        };
+        engine.evaluate(aircraft, &context, CallsignDisplay::Callsign)
+    }
+
+    #[test]
+    fn test_expression_rule_with_cooldown() {
+        let mut engine = AlertEngine::default();
+        let rule = AlertRule::new("Low and close", AlertCondition::Expression("altitude < 2000 and distance < 5km".to_string()));
+        assert!(engine.set_rules(&[rule]).is_empty());
+
+        let start = Utc::now();
+        let low = [aircraft(1500.0, "1234")];
+        let alerts = evaluate(&mut engine, &low, start);
+        assert_eq!(alerts.len(), 1);
+        assert_eq!(alerts[0].message, "TEST1 (altitude < 2000 and distance < 5km)");
+
+        assert!(evaluate(&mut engine, &low, start + Duration::seconds(60)).is_empty());
+        assert_eq!(evaluate(&mut engine, &low, start + Duration::seconds(301)).len(), 1);
+        assert!(evaluate(&mut engine, &[aircraft(3000.0, "1234")], start + Duration::seconds(700)).is_empty());
+    }
+
+    #[test]
+    fn test_departed_aircraft_are_forgotten() {
+        let mut engine = AlertEngine::default();
+        engine.set_rules(&[AlertRule::new("Low", AlertCondition::Expression("altitude < 2000".to_string()))]);
+
+        let start = Utc::now();
+        assert_eq!(evaluate(&mut engine, &[aircraft(1500.0, "1234")], start).len(), 1);
+        // Gone but still cooling down
+        evaluate(&mut engine, &[], start + Duration::seconds(60));
+        assert_eq!(engine.last_fired.len(), 1);
+        assert!(evaluate(&mut engine, &[aircraft(1500.0, "1234")], start + Duration::seconds(120)).is_empty());
+
+        evaluate(&mut engine, &[], start + Duration::seconds(400));
+        assert!(engine.last_fired.is_empty());
+        assert!(engine.squawks.is_empty());
+    }
+
+    #[test]
+    fn test_squawk_change() {
+        let mut engine = AlertEngine::default();
+        let mut rule = AlertRule::new("Squawk", AlertCondition::SquawkChange);
+        rule.cooldown_secs = 0;
+        engine.set_rules(&[rule]);
+
+        let now = Utc::now();
+        assert!(evaluate(&mut engine, &[aircraft(5000.0, "1234")], now).is_empty());
+        let alerts = evaluate(&mut engine, &[aircraft(5000.0, "7700")], now);
+        assert_eq!(alerts.len(), 1);
+        assert!(alerts[0].message.contains("1234 → 7700"));
+        assert!(evaluate(&mut engine, &[aircraft(5000.0, "7700")], now).is_empty());
+    }
+
+    #[test]
+    fn test_invalid_rule_reported() {
+        let mut engine = AlertEngine::default();
+        let errors = engine.set_rules(&[AlertRule::new("Bad", AlertCondition::Expression("altitude <".to_string()))]);
+        assert_eq!(errors.len(), 1);
+        assert_eq!(errors[0].0, "Bad");
+        assert!(evaluate(&mut engine, &[aircraft(100.0, "1234")], Utc::now()).is_empty());
+    }
+
+    #[test]
+    fn test_append_log() {
+        let path = std::env::temp_dir().join(format!("skyradar-alerts-{}.log", std::process::id()));
+        let alert = Alert {
+            rule: "Low".to_string(),
+            icao24: "a12345".to_string(),
+            severity: Severity::Critical,
+            message: "TEST1".to_string(),
+            time: Utc::now(),
+            actions: AlertActions::default(),
+        };
+        append_log(&path, &alert).unwrap();
+        append_log(&path, &alert).unwrap();
+        let text = std::fs::read_to_string(&path).unwrap();
+        std::fs::remove_file(&path).ok();
+
+        assert_eq!(text.lines().count(), 2);
+        assert!(text.lines().all(|l| l.ends_with("[CRITICAL] Low: a12345 TEST1")));
+    }
+}
@@ -9,6 +9,11 @@ use tracing::{debug, info};
 
 const OPENSKY_BASE_URL: &str = "https://opensky-network.org/api";
 
+// OpenSky reports SI units; the app works in feet, km/h and ft/min
+const FEET_PER_METRE: f64 = 3.28084;
+const KMH_PER_MS: f64 = 3.6;
+const FPM_PER_MS: f64 = 196.85;
+
 #[derive(Debug, Clone)]
 pub struct OpenSkyApi {
     client: Client,
@@ -122,55 +127,61 @@ struct StatesResponse {
     states: Option<Vec<StateData>>,
 }
 
+// One row of OpenSky's state vector array, in column order. Most columns
+// are null when the receiver hasn't seen that field, and category (17) is
+// only sent when extended data is requested.
 #[derive(Debug, Deserialize)]
 struct StateData(
-    String,    // icao24
-    String,    // callsign
-    String,    // origin_country
-    i64,       // time_position
-    i64,       // time_velocity
-    f64,       // longitude
-    f64,       // latitude
-    f64,       // altitude
-    f64,       // velocity
-    f64,       // true_track
-    f64,       // vertical_rate
-    Vec<String>, // sensors
-    f64,       // geo_altitude
-    String,    // squawk
-    bool,      // spi
-    i32,       // position_source
-    i32,       // category
+    String,                 // 0 icao24
+    Option<String>,         // 1 callsign
+    Option<String>,         // 2 origin_country
+    Option<i64>,            // 3 time_position
+    Option<i64>,            // 4 last_contact
+    Option<f64>,            // 5 longitude
+    Option<f64>,            // 6 latitude
+    Option<f64>,            // 7 baro_altitude
+    Option<bool>,           // 8 on_ground
+    Option<f64>,            // 9 velocity
+    Option<f64>,            // 10 true_track
+    Option<f64>,            // 11 vertical_rate
+    Option<Vec<i64>>,       // 12 sensors
+    Option<f64>,            // 13 geo_altitude
+    Option<String>,         // 14 squawk
+    Option<bool>,           // 15 spi
+    Option<i32>,            // 16 position_source
+    #[serde(default)]
+    Option<i32>,            // 17 category
 );
 
+fn timestamp(secs: Option<i64>) -> Option<DateTime<Utc>> {
+    secs.filter(|s| *s > 0).and_then(|s| DateTime::from_timestamp(s, 0))
+}
+
+fn non_empty(text: Option<String>) -> Option<String> {
+    text.map(|t| t.trim().to_string()).filter(|t| !t.is_empty())
+}
+
 impl From<StateData> for Aircraft {
     fn from(state: StateData) -> Self {
         Self {
             icao24: state.0,
-            callsign: if state.1.is_empty() { None } else { Some(state.1) },
-            origin_country: if state.2.is_empty() { None } else { Some(state.2) },
-            time_position: if state.3 > 0 {
-                Some(DateTime::from_timestamp(state.3, 0).unwrap_or_else(|| Utc::now()))
-            } else {
-                None
-            },
-            time_velocity: if state.4 > 0 {
-                Some(DateTime::from_timestamp(state.4, 0).unwrap_or_else(|| Utc::now()))
-            } else {
-                None
-            },
-            longitude: if state.5 != 0.0 { Some(state.5) } else { None },
-            latitude: if state.6 != 0.0 { Some(state.6) } else { None },
-            altitude: if state.7 != 0.0 { Some(state.7) } else { None },
-            velocity: if state.8 != 0.0 { Some(state.8) } else { None },
-            true_track: if state.9 != 0.0 { Some(state.9) } else { None },
-            vertical_rate: if state.10 != 0.0 { Some(state.10) } else { None },
-            sensors: if state.11.is_empty() { None } else { Some(state.11) },
-            geo_altitude: if state.12 != 0.0 { Some(state.12) } else { None },
-            squawk: if state.13.is_empty() { None } else { Some(state.13) },
-            spi: Some(state.14),
-            position_source: Some(state.15),
-            category: Some(state.16),
+            callsign: non_empty(state.1),
+            origin_country: non_empty(state.2),
+            time_position: timestamp(state.3),
+            time_velocity: timestamp(state.4),
+            longitude: state.5,
+            latitude: state.6,
+            altitude: state.7.map(|m| m * FEET_PER_METRE),
+            on_ground: state.8,
+            velocity: state.9.map(|ms| ms * KMH_PER_MS),
+            true_track: state.10,
+            vertical_rate: state.11.map(|ms| ms * FPM_PER_MS),
+            sensors: state.12.map(|ids| ids.iter().map(i64::to_string).collect()),
+            geo_altitude: state.13.map(|m| m * FEET_PER_METRE),
+            squawk: non_empty(state.14),
+            spi: state.15,
+            position_source: state.16,
+            category: state.17,
         }
     }
 }
@@ -200,6 +211,7 @@ impl MockApi {
                 longitude: Some(-122.4194),
                 latitude: Some(37.7749),
                 altitude: Some(35000.0),
+                on_ground: Some(false),
                 velocity: Some(450.0),
                 true_track: Some(90.0),
                 vertical_rate: Some(0.0),
@@ -219,6 +231,7 @@ impl MockApi {
                 longitude: Some(-122.4000),
                 latitude: Some(37.7800),
                 altitude: Some(28000.0),
+                on_ground: Some(false),
                 velocity: Some(380.0),
                 true_track: Some(180.0),
                 vertical_rate: Some(-500.0),
@@ -234,4 +247,44 @@ impl MockApi {
         info!("Generated {} mock aircraft", mock_aircraft.len());
         Ok(mock_aircraft)
     }
-} 
\ No newline at end of file
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn close(value: Option<f64>, expected: f64) -> bool {
+        value.is_some_and(|v| (v - expected).abs() < 0.5)
+    }
+
+    #[test]
+    fn test_parses_state_vectors() {
+        let json = r#"{"time":1700000000,"states":[
+            ["3c6444","DLH9LF  ","Germany",1699999995,1699999998,8.5622,50.0379,10972.8,false,231.5,92.1,-0.33,null,11277.6,"1000",false,0],
+            ["4ca7b4","","Ireland",null,1699999990,null,null,null,true,0.0,null,null,[1234],null,null,false,0,3]
+        ]}"#;
+        let response: StatesResponse = serde_json::from_str(json).unwrap();
+        let aircraft: Vec<Aircraft> = response.states.unwrap().into_iter().map(Aircraft::from).collect();
+
+        let cruising = &aircraft[0];
+        assert_eq!(cruising.callsign.as_deref(), Some("DLH9LF"));
+        // Converted from metres and m/s
+        assert!(close(cruising.altitude, 36000.0));
+        assert_eq!(cruising.on_ground, Some(false));
+        assert!(close(cruising.velocity, 833.4));
+        assert_eq!(cruising.true_track, Some(92.1));
+        assert!(close(cruising.vertical_rate, -65.0));
+        assert!(close(cruising.geo_altitude, 37000.0));
+        assert_eq!(cruising.squawk.as_deref(), Some("1000"));
+        assert_eq!(cruising.category, None);
+
+        let parked = &aircraft[1];
+        assert_eq!(parked.callsign, None);
+        assert_eq!(parked.time_position, None);
+        assert_eq!(parked.latitude, None);
+        assert_eq!(parked.on_ground, Some(true));
+        assert_eq!(parked.velocity, Some(0.0));
+        assert_eq!(parked.sensors, Some(vec!["1234".to_string()]));
+        assert_eq!(parked.category, Some(3));
+    }
+}
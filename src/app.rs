@@ -1,16 +1,44 @@
 use crate::aircraft::Aircraft;
+use crate::aircraft_details::{self, AircraftDetails};
+use crate::aircraft_query::{AircraftQuery, ListColumn, QuickFilter};
+use crate::airports::{AirportCodeStyle, AirportDatabase};
+use crate::airspace::AirspaceDatabase;
+use crate::alerts::{self, Alert, AlertCondition, AlertEngine, AlertRule, Severity};
 use crate::api::{MockApi, OpenSkyApi};
-use crate::config::{AppConfig, Theme};
+use crate::basemap::Basemap;
+use crate::config::{AppConfig, CallsignDisplay, SymbolColoring, Theme};
+use crate::conflict::{self, Conflict};
+use crate::cpa::{self, ClosestApproach, PassingSort};
+use crate::filter_expr::{Filter, FilterContext};
+use crate::flight_phase::{FlightPhase, PhaseTracker};
+use crate::format::format_duration;
 use crate::geo::Location;
-use crate::radar_view::RadarView;
+use crate::geofence::{self, Geofence, GeofenceEvent, GeofenceMonitor};
+use crate::heatmap::DensityGrid;
+use crate::history_chart::{self, ChartSeries, HistoryChart};
+use crate::icao::{self, AddressKind};
+use crate::labels::DataBlockField;
+use crate::map_layers::{self, MapLayer, MapLayerConfig};
+use crate::prediction;
+use crate::profile_view::{ProfileAxis, ProfileView};
+use crate::radar_view::{RadarOrientation, RadarView};
 use crate::theme::apply_theme;
+use crate::track_store::TrackStore;
+use crate::watchlist::{WatchEntry, WatchKind, WatchMatch, WatchMonitor, Watchlist};
+use crate::webhook::{self, EventKind, LifecycleTracker, Outbox, WebhookEvent, WebhookSettings};
 use anyhow::Result;
 use chrono::{DateTime, Utc};
 use eframe::egui;
+use std::borrow::Cow;
+use std::collections::{HashMap, HashSet, VecDeque};
+use std::sync::{Arc, Mutex};
 use std::time::{Duration, Instant};
 use tokio::runtime::Runtime;
 use tracing::{error, info, warn};
 
+// Farthest airfield whose elevation stands in for the ground under an aircraft
+const GROUND_REFERENCE_KM: f64 = 30.0;
+
 pub struct SkyRadarApp {
     config: AppConfig,
     aircraft: Vec<Aircraft>,
@@ -20,11 +48,76 @@ pub struct SkyRadarApp {
     selected_aircraft: Option<String>,
     show_settings: bool,
     show_aircraft_list: bool,
+    show_details: bool,
     runtime: Runtime,
     api: Box<dyn AircraftApi>,
     refresh_timer: f32,
     status_message: String,
     is_loading: bool,
+    airports: Arc<AirportDatabase>,
+    airport_search: String,
+    airport_import_path: String,
+    phase_tracker: PhaseTracker,
+    phase_filter: Option<FlightPhase>,
+    passing_soon: Vec<ClosestApproach>,
+    passing_sort: PassingSort,
+    show_passing_soon: bool,
+    overhead_alerted: HashSet<String>,
+    conflicts: Vec<Conflict>,
+    geofence_monitor: GeofenceMonitor,
+    geofence_events: VecDeque<GeofenceEvent>,
+    show_geofences: bool,
+    fence_sketch: Option<Vec<Location>>,
+    new_fence_name: String,
+    new_fence_radius_km: f64,
+    geofence_import_path: String,
+    airspaces: Arc<AirspaceDatabase>,
+    airspace_hits: HashMap<String, Vec<String>>,
+    airspace_import_path: String,
+    map_layer_path: String,
+    basemap_path: String,
+    profile_view: ProfileView,
+    show_profile: bool,
+    following: Option<String>,
+    orientation_before_follow: Option<RadarOrientation>,
+    track_store: TrackStore,
+    // Recorded session shown in the heatmap instead of live history
+    heatmap_session: Option<TrackStore>,
+    session_path: String,
+    list_query: AircraftQuery,
+    list_filter_text: String,
+    list_filter_error: Option<String>,
+    radar_filter: Option<Filter>,
+    radar_filter_error: Option<String>,
+    // Aircraft passing the radar filter, shown on the radar and profile
+    radar_aircraft: Vec<Aircraft>,
+    watchlist: Watchlist,
+    // Set when the watchlist file exists but couldn't be read; saving is
+    // then refused so the user's file isn't replaced by an empty list
+    watchlist_load_error: Option<String>,
+    watch_monitor: WatchMonitor,
+    // Watched aircraft in the feed and the note of the entry matching them
+    watched: HashMap<String, String>,
+    show_watchlist: bool,
+    new_watch_kind: WatchKind,
+    new_watch_value: String,
+    new_watch_note: String,
+    alert_engine: AlertEngine,
+    // Rule name and why its expression failed to parse
+    alert_rule_errors: HashMap<String, String>,
+    alert_banners: Vec<Alert>,
+    alert_history: VecDeque<Alert>,
+    show_alerts: bool,
+    new_rule_name: String,
+    new_rule_expression: String,
+    // Shared with the delivery task running on `runtime`
+    outbox: Arc<Mutex<Outbox>>,
+    lifecycle: LifecycleTracker,
+    new_webhook_name: String,
+    new_webhook_url: String,
+    history_chart: HistoryChart,
+    show_history: bool,
+    history_export_path: String,
 }
 
 trait AircraftApi: Send + Sync {
@@ -80,10 +173,53 @@ impl SkyRadarApp {
             Box::new(MockApi::new())
         };
 
-        let radar_view = RadarView::new(egui::Rect::NOTHING); // Will be updated in update()
+        let airports = Arc::new(Self::load_airports(&config));
+        let mut radar_view = RadarView::new(egui::Rect::NOTHING); // Will be updated in draw()
+        radar_view.set_airports(airports.clone());
+        radar_view.set_geofences(&config.geofences);
+        let airspaces = Arc::new(Self::load_airspaces(&config));
+        radar_view.set_airspaces(airspaces.clone());
+        radar_view.set_map_layers(map_layers::load_layers(&config.map_layers));
+        radar_view.set_basemap(config.basemap_path.as_deref().and_then(|path| {
+            Basemap::open(path)
+                .map_err(|e| warn!("Failed to open basemap: {}", e))
+                .ok()
+        }));
+
+        let (radar_filter, radar_filter_error) = match Filter::parse_optional(&config.radar_filter) {
+            Ok(filter) => (filter, None),
+            Err(e) => {
+                warn!("Ignoring invalid radar filter: {}", e);
+                (None, Some(e.describe(&config.radar_filter)))
+            }
+        };
+
+        let (watchlist, watchlist_load_error) = match Watchlist::load(&Watchlist::default_path()) {
+            Ok(watchlist) => (watchlist, None),
+            Err(e) => {
+                warn!("Failed to load watchlist: {:#}", e);
+                (Watchlist::default(), Some(format!("{:#}", e)))
+            }
+        };
+
+        let mut alert_engine = AlertEngine::default();
+        let alert_rule_errors = alert_engine
+            .set_rules(&config.alert_rules)
+            .into_iter()
+            .map(|(name, e)| {
+                warn!("Alert rule {} will not fire: {}", name, e);
+                (name, e.to_string())
+            })
+            .collect();
+
+        let outbox = Outbox::open(&Outbox::default_path()).unwrap_or_else(|e| {
+            warn!("Failed to open webhook outbox: {}", e);
+            Outbox::in_memory()
+        });
+        let outbox = Arc::new(Mutex::new(outbox));
+        webhook::spawn_worker(&runtime, outbox.clone());
 
         Self {
-            config,
             aircraft: Vec::new(),
             radar_view,
             last_update: None,
@@ -91,19 +227,275 @@ impl SkyRadarApp {
             selected_aircraft: None,
             show_settings: false,
             show_aircraft_list: true,
+            show_details: true,
             runtime,
             api,
             refresh_timer: 0.0,
             status_message: "Initializing...".to_string(),
             is_loading: false,
+            airport_import_path: config
+                .airports_dir
+                .as_ref()
+                .map(|p| p.display().to_string())
+                .unwrap_or_default(),
+            airports,
+            airport_search: String::new(),
+            phase_tracker: PhaseTracker::default(),
+            phase_filter: None,
+            passing_soon: Vec::new(),
+            passing_sort: PassingSort::Eta,
+            show_passing_soon: false,
+            overhead_alerted: HashSet::new(),
+            conflicts: Vec::new(),
+            geofence_monitor: GeofenceMonitor::new(&config.geofences),
+            geofence_events: VecDeque::new(),
+            show_geofences: false,
+            fence_sketch: None,
+            new_fence_name: String::new(),
+            new_fence_radius_km: 2.0,
+            geofence_import_path: String::new(),
+            airspaces,
+            airspace_hits: HashMap::new(),
+            airspace_import_path: String::new(),
+            map_layer_path: String::new(),
+            basemap_path: config
+                .basemap_path
+                .as_ref()
+                .map(|p| p.display().to_string())
+                .unwrap_or_default(),
+            profile_view: ProfileView::default(),
+            show_profile: false,
+            following: None,
+            orientation_before_follow: None,
+            track_store: TrackStore::new(config.track_retention_minutes),
+            heatmap_session: None,
+            session_path: String::new(),
+            list_query: AircraftQuery::default(),
+            list_filter_text: String::new(),
+            list_filter_error: None,
+            radar_filter,
+            radar_filter_error,
+            radar_aircraft: Vec::new(),
+            watchlist,
+            watchlist_load_error,
+            watch_monitor: WatchMonitor::default(),
+            watched: HashMap::new(),
+            show_watchlist: false,
+            new_watch_kind: WatchKind::Icao24,
+            new_watch_value: String::new(),
+            new_watch_note: String::new(),
+            alert_engine,
+            alert_rule_errors,
+            alert_banners: Vec::new(),
+            alert_history: VecDeque::new(),
+            show_alerts: false,
+            new_rule_name: String::new(),
+            new_rule_expression: String::new(),
+            outbox,
+            lifecycle: LifecycleTracker::default(),
+            new_webhook_name: String::new(),
+            new_webhook_url: String::new(),
+            history_chart: HistoryChart::default(),
+            show_history: false,
+            history_export_path: String::new(),
+            config,
         }
     }
 
-    fn update(&mut self, ctx: &egui::Context) {
-        // Update radar view with current window size
-        let available_rect = ctx.available_rect();
-        self.radar_view = RadarView::new(available_rect);
+    fn load_airports(config: &AppConfig) -> AirportDatabase {
+        if let Some(dir) = &config.airports_dir {
+            match AirportDatabase::load_dir(dir) {
+                Ok(db) => {
+                    info!("Loaded {} airports from {}", db.len(), dir.display());
+                    return db;
+                }
+                Err(e) => warn!("Failed to load airports: {}, using bundled data", e),
+            }
+        }
+        AirportDatabase::bundled()
+    }
+
+    fn import_airports(&mut self) {
+        let dir = std::path::PathBuf::from(self.airport_import_path.trim());
+        match AirportDatabase::load_dir(&dir) {
+            Ok(db) => {
+                self.status_message = format!("Imported {} airports", db.len());
+                self.airports = Arc::new(db);
+                self.radar_view.set_airports(self.airports.clone());
+                self.config.airports_dir = Some(dir);
+            }
+            Err(e) => {
+                error!("Failed to import airports: {}", e);
+                self.status_message = format!("Airport import failed: {}", e);
+            }
+        }
+    }
+
+    fn load_airspaces(config: &AppConfig) -> AirspaceDatabase {
+        match AirspaceDatabase::load_files(&config.airspace_files) {
+            Ok(db) => {
+                if !db.is_empty() {
+                    info!("Loaded {} airspaces", db.airspaces().len());
+                }
+                db
+            }
+            Err(e) => {
+                warn!("Failed to load airspace: {}", e);
+                AirspaceDatabase::default()
+            }
+        }
+    }
+
+    fn import_airspace(&mut self) {
+        let path = std::path::PathBuf::from(self.airspace_import_path.trim());
+        let mut files = self.config.airspace_files.clone();
+        if !files.contains(&path) {
+            files.push(path);
+        }
+        match AirspaceDatabase::load_files(&files) {
+            Ok(db) => {
+                self.status_message = format!("Loaded {} airspaces", db.airspaces().len());
+                self.config.airspace_files = files;
+                self.set_airspaces(db);
+            }
+            Err(e) => {
+                error!("Failed to import airspace: {}", e);
+                self.status_message = format!("Airspace import failed: {}", e);
+            }
+        }
+    }
+
+    fn add_map_layer(&mut self) {
+        let path = std::path::PathBuf::from(self.map_layer_path.trim());
+        match MapLayer::load(&path) {
+            Ok(layer) => {
+                self.status_message = format!("Loaded {} lines from {}", layer.line_count(), path.display());
+                self.radar_view.add_map_layer(path.clone(), layer);
+                self.config.map_layers.retain(|l| l.path != path);
+                self.config.map_layers.push(MapLayerConfig::new(path));
+                self.map_layer_path.clear();
+            }
+            Err(e) => {
+                error!("Failed to load map layer: {}", e);
+                self.status_message = format!("Map layer failed: {}", e);
+            }
+        }
+    }
+
+    fn update_heatmap(&mut self) {
+        if !self.config.heatmap.enabled {
+            self.radar_view.set_heatmap(None);
+            return;
+        }
+        // Sessions are shown up to their last fix rather than up to now
+        let (store, until) = match &self.heatmap_session {
+            Some(session) => (session, session.latest().unwrap_or_else(Utc::now)),
+            None => (&self.track_store, Utc::now()),
+        };
+        let grid = DensityGrid::build(store, &self.config.location, &self.config.heatmap, until);
+        self.radar_view.set_heatmap(Some(grid));
+    }
+
+    fn save_session(&mut self) {
+        let path = std::path::PathBuf::from(self.session_path.trim());
+        match self.track_store.save_session(&path) {
+            Ok(()) => {
+                self.status_message = format!("Saved {} positions to {}", self.track_store.point_count(), path.display());
+            }
+            Err(e) => {
+                error!("Failed to save session: {}", e);
+                self.status_message = format!("Session save failed: {}", e);
+            }
+        }
+    }
+
+    fn export_history(&mut self) {
+        let path = std::path::PathBuf::from(self.history_export_path.trim());
+        let aircraft = self.history_chart.aircraft(self.selected_aircraft.as_deref());
+        let Some(extent) = history_chart::data_extent(&self.track_store, &aircraft) else {
+            self.status_message = "No history to export".to_string();
+            return;
+        };
+        // Exactly what is plotted: the visible time span and series
+        let lines = self
+            .history_chart
+            .lines(&self.track_store, &aircraft, &self.config.location, extent);
+        match history_chart::export_csv(&lines, &path) {
+            Ok(()) => {
+                let values: usize = lines.iter().map(|l| l.points.len()).sum();
+                self.status_message = format!("Exported {} values to {}", values, path.display());
+            }
+            Err(e) => {
+                error!("Failed to export history: {}", e);
+                self.status_message = format!("History export failed: {}", e);
+            }
+        }
+    }
+
+    fn load_session(&mut self) {
+        let path = std::path::PathBuf::from(self.session_path.trim());
+        match TrackStore::load_session(&path) {
+            Ok(session) => {
+                self.status_message = format!("Loaded {} positions from {}", session.point_count(), path.display());
+                self.heatmap_session = Some(session);
+                self.update_heatmap();
+            }
+            Err(e) => {
+                error!("Failed to load session: {}", e);
+                self.status_message = format!("Session load failed: {}", e);
+            }
+        }
+    }
+
+    fn open_basemap(&mut self) {
+        let path = std::path::PathBuf::from(self.basemap_path.trim());
+        match Basemap::open(&path) {
+            Ok(basemap) => {
+                self.status_message = format!("Opened basemap {}", basemap.name());
+                self.radar_view.set_basemap(Some(basemap));
+                self.config.basemap_path = Some(path);
+            }
+            Err(e) => {
+                error!("Failed to open basemap: {}", e);
+                self.status_message = format!("Basemap failed: {}", e);
+            }
+        }
+    }
+
+    fn set_airspaces(&mut self, db: AirspaceDatabase) {
+        self.airspaces = Arc::new(db);
+        self.radar_view.set_airspaces(self.airspaces.clone());
+        self.update_airspace_flags();
+    }
 
+    fn update_airspace_flags(&mut self) {
+        self.airspace_hits = self
+            .aircraft
+            .iter()
+            .filter_map(|aircraft| {
+                // AGL limits are measured from the nearest airfield's elevation;
+                // without one they assume sea level and are marked approximate
+                let ground_ft = aircraft.position().and_then(|point| {
+                    self.airports
+                        .nearest(&Location::from_point(point), GROUND_REFERENCE_KM)
+                        .and_then(|(airport, _)| airport.elevation_ft)
+                });
+                let inside: Vec<String> = self
+                    .airspaces
+                    .containing(aircraft, ground_ft.unwrap_or(0.0))
+                    .iter()
+                    .map(|a| {
+                        let approximate = if a.uses_agl() && ground_ft.is_none() { " (approx.)" } else { "" };
+                        format!("{} {}{}", a.class.code(), a.name, approximate)
+                    })
+                    .collect();
+                (!inside.is_empty()).then(|| (aircraft.icao24.clone(), inside))
+            })
+            .collect();
+        self.radar_view.update_airspace_flags(self.airspace_hits.keys().cloned());
+    }
+
+    fn update(&mut self, ctx: &egui::Context) {
         // Apply theme
         ctx.style_mut(|style| {
             apply_theme(&mut style.visuals, self.config.theme.is_dark());
@@ -121,6 +513,11 @@ impl SkyRadarApp {
         // Update refresh timer for UI
         let elapsed = self.last_refresh.elapsed();
         self.refresh_timer = elapsed.as_secs_f32();
+
+        // Keep the extrapolated position moving between updates
+        if self.following.is_some() {
+            ctx.request_repaint_after(Duration::from_millis(200));
+        }
     }
 
     fn refresh_aircraft_data(&mut self) {
@@ -142,6 +539,7 @@ impl SkyRadarApp {
                 longitude: Some(-122.4194),
                 latitude: Some(37.7749),
                 altitude: Some(35000.0),
+                on_ground: Some(false),
                 velocity: Some(450.0),
                 true_track: Some(90.0),
                 vertical_rate: Some(0.0),
@@ -154,166 +552,1548 @@ impl SkyRadarApp {
             },
         ];
 
+        // Set before the watchers run so anything they report replaces it
         self.last_update = Some(Utc::now());
         self.status_message = format!("Last updated: {}", self.last_update.unwrap().format("%H:%M:%S"));
+
+        self.radar_view.update_trails(&self.aircraft, &self.config);
+        self.track_store.record(&self.aircraft, Utc::now());
+        self.update_heatmap();
+        self.phase_tracker.update(&self.aircraft, &self.airports);
+        self.radar_view.update_phases(&self.phase_tracker);
+        self.update_passing_soon();
+        self.update_conflicts();
+        self.update_geofences();
+        self.update_airspace_flags();
+        self.update_radar_aircraft();
+        self.update_watchlist();
+        self.update_alerts();
+        self.update_lifecycle();
+
+        if let Some(icao24) = &self.following {
+            if !self.aircraft.iter().any(|a| &a.icao24 == icao24 && a.has_position()) {
+                self.status_message = format!("Lost {}, returning home", self.aircraft_label(icao24));
+                self.stop_following();
+            }
+        }
+
         self.is_loading = false;
     }
 
-    fn draw_main_window(&mut self, ctx: &egui::Context) {
-        egui::CentralPanel::default().show(ctx, |ui| {
-            ui.horizontal(|ui| {
-                ui.heading("SkyRadar - Live Aircraft Tracker");
-                ui.with_layout(egui::Layout::right_to_left(egui::Align::Center), |ui| {
-                    if ui.button("⚙️ Settings").clicked() {
-                        self.show_settings = !self.show_settings;
-                    }
-                    if ui.button("📋 Aircraft").clicked() {
-                        self.show_aircraft_list = !self.show_aircraft_list;
-                    }
-                    if ui.button("🔄 Refresh").clicked() {
-                        self.refresh_aircraft_data();
-                    }
-                });
-            });
+    fn update_passing_soon(&mut self) {
+        self.passing_soon = cpa::passing_soon(
+            &self.aircraft,
+            &self.config.location,
+            self.config.radar_radius_km,
+            self.config.passing_horizon_minutes * 60.0,
+        );
+        self.passing_sort.sort(&mut self.passing_soon);
 
-            ui.separator();
+        // Alert once per aircraft while it stays on course for overhead
+        let alert_km = self.config.overhead_alert_km;
+        let overhead: Vec<&ClosestApproach> = self
+            .passing_soon
+            .iter()
+            .filter(|cpa| cpa.is_overhead(alert_km))
+            .collect();
+        self.overhead_alerted
+            .retain(|icao24| overhead.iter().any(|cpa| &cpa.icao24 == icao24));
 
-            // Status bar
-            ui.horizontal(|ui| {
-                ui.label(&self.status_message);
-                ui.with_layout(egui::Layout::right_to_left(egui::Align::Center), |ui| {
-                    if self.config.auto_refresh {
-                        let remaining = self.config.refresh_interval_seconds as f32 - self.refresh_timer;
-                        ui.label(format!("Auto-refresh in {:.0}s", remaining.max(0.0)));
-                    }
-                });
-            });
+        let mut notices = Vec::new();
+        for approach in overhead {
+            if self.overhead_alerted.insert(approach.icao24.clone()) {
+                let name = self.aircraft_label(&approach.icao24);
+                let message = format!(
+                    "{} passes {:.1} km away in {:.0}s",
+                    name, approach.min_distance_km, approach.time_to_cpa_secs
+                );
+                notices.push(Alert::notice("Overhead", &approach.icao24, Severity::Info, message));
+            }
+        }
+        for notice in notices {
+            self.raise_alert(notice);
+        }
+    }
 
-            ui.separator();
+    fn update_conflicts(&mut self) {
+        let settings = &self.config.conflict_detection;
+        let conflicts = if settings.enabled {
+            conflict::detect_conflicts(&self.aircraft, settings)
+        } else {
+            Vec::new()
+        };
 
-            // Main content area
-            ui.horizontal(|ui| {
-                // Radar view (takes most space)
-                ui.vertical(|ui| {
-                    ui.label("Radar View");
-                    self.radar_view.draw(ui, &self.aircraft, &self.config, &self.config.location);
-                });
+        let new_conflicts = conflicts
+            .iter()
+            .filter(|c| !self.conflicts.iter().any(|old| old.icao24 == c.icao24))
+            .count();
+        if new_conflicts > 0 {
+            warn!("{} new traffic conflict(s) predicted", new_conflicts);
+        }
 
-                // Side panels
-                if self.show_aircraft_list {
-                    ui.vertical(|ui| {
-                        ui.label("Aircraft List");
-                        self.draw_aircraft_list(ui);
-                    });
-                }
-            });
-        });
+        self.radar_view.update_conflicts(&conflicts);
+        self.conflicts = conflicts;
     }
 
-    fn draw_aircraft_list(&mut self, ui: &mut egui::Ui) {
-        ui.set_enabled(!self.is_loading);
+    fn update_geofences(&mut self) {
+        for event in self.geofence_monitor.update(&self.aircraft, Utc::now()) {
+            let message = format!(
+                "{} {} {}",
+                self.aircraft_label(&event.icao24),
+                event.kind.name(),
+                event.fence
+            );
+            self.raise_alert(Alert::notice("Geofence", &event.icao24, Severity::Info, message));
+
+            self.geofence_events.push_front(event);
+            self.geofence_events.truncate(50);
+        }
+    }
 
-        egui::ScrollArea::vertical().show(ui, |ui| {
-            if self.aircraft.is_empty() {
-                ui.label("No aircraft detected");
-                return;
+    fn update_watchlist(&mut self) {
+        self.watched = self
+            .aircraft
+            .iter()
+            .filter_map(|a| Some((a.icao24.clone(), self.watchlist.matching(a)?.note.clone())))
+            .collect();
+        self.radar_view.set_watched(self.watched.keys().cloned());
+
+        let alerts = self.watch_monitor.update(
+            &self.aircraft,
+            &self.watchlist,
+            &self.config.location,
+            self.config.radar_radius_km,
+        );
+        for alert in alerts {
+            let mut message = format!(
+                "{} in range, {:.1} km away",
+                self.aircraft_label(&alert.icao24),
+                alert.distance_km
+            );
+            if !alert.entry.note.is_empty() {
+                message.push_str(&format!(" ({})", alert.entry.note));
             }
+            self.raise_alert(Alert::notice("Watchlist", &alert.icao24, Severity::Info, message));
+        }
+    }
 
-            for aircraft in &self.aircraft {
-                ui.horizontal(|ui| {
-                    let is_selected = self.selected_aircraft.as_ref() == Some(&aircraft.icao24);
-                    
-                    if ui.selectable_label(is_selected, &aircraft.display_name()).clicked() {
-                        self.selected_aircraft = Some(aircraft.icao24.clone());
-                    }
+    fn update_alerts(&mut self) {
+        let context = FilterContext {
+            home: &self.config.location,
+            now: Utc::now(),
+            phases: Some(&self.phase_tracker),
+        };
+        let fired = self
+            .alert_engine
+            .evaluate(&self.aircraft, &context, self.config.callsign_display);
+
+        for alert in fired {
+            self.raise_alert(alert);
+        }
+    }
+
+    // Everything that should reach the user goes through here, so it shows
+    // in the banner strip and history instead of a status line the next
+    // refresh overwrites, and is forwarded to webhooks
+    fn raise_alert(&mut self, alert: Alert) {
+        warn!("Alert {}: {}", alert.rule, alert.message);
+        alerts::dispatch(&alert);
+        let aircraft = self.aircraft.iter().find(|a| a.icao24 == alert.icao24);
+        self.queue_webhooks(&WebhookEvent::from_alert(&alert, aircraft));
+        if alert.actions.banner {
+            self.alert_banners.push(alert.clone());
+        }
+        self.alert_history.push_front(alert);
+        self.alert_history.truncate(100);
+
+        // Oldest banners give way so the strip never pushes the radar off screen
+        let excess = self.alert_banners.len().saturating_sub(5);
+        self.alert_banners.drain(..excess);
+    }
+
+    fn update_lifecycle(&mut self) {
+        let now = Utc::now();
+        for (kind, aircraft) in self.lifecycle.update(&self.aircraft) {
+            let event = WebhookEvent::lifecycle(kind, &aircraft, self.config.callsign_display, now);
+            self.queue_webhooks(&event);
+        }
+    }
+
+    fn queue_webhooks(&self, event: &WebhookEvent) {
+        if self.config.webhooks.iter().any(|w| w.accepts(event.kind)) {
+            self.outbox.lock().unwrap().enqueue_event(&self.config.webhooks, event);
+        }
+    }
+
+    fn alert_rules_changed(&mut self) {
+        self.alert_rule_errors = self
+            .alert_engine
+            .set_rules(&self.config.alert_rules)
+            .into_iter()
+            .map(|(name, e)| (name, e.to_string()))
+            .collect();
+    }
+
+    fn add_watch(&mut self, matcher: WatchMatch, note: String) {
+        if matcher.value().is_empty() || self.watchlist.contains(&matcher) {
+            return;
+        }
+        let entry = WatchEntry {
+            matcher,
+            note,
+            added: Utc::now(),
+        };
+        match self.watchlist.add(entry) {
+            Ok(()) => self.watchlist_changed(),
+            Err(e) => self.status_message = format!("Watch not added: {}", e),
+        }
+    }
+
+    fn watchlist_changed(&mut self) {
+        if self.watchlist_load_error.is_some() {
+            self.status_message = format!(
+                "Watchlist changes not saved: fix or remove {} first",
+                Watchlist::default_path().display()
+            );
+        } else if let Err(e) = self.watchlist.save(&Watchlist::default_path()) {
+            error!("Failed to save watchlist: {}", e);
+            self.status_message = format!("Watchlist save failed: {}", e);
+        }
+        self.update_watchlist();
+    }
+
+    fn draw_watchlist_window(&mut self, ctx: &egui::Context) {
+        if !self.show_watchlist {
+            return;
+        }
 
-                    if let Some(altitude) = aircraft.altitude {
-                        ui.label(format!("{:.0}ft", altitude));
+        let mut remove = None;
+        let mut note_edit = None;
+        let mut save_notes = false;
+        let mut add_requested = false;
+        egui::Window::new("Watchlist")
+            .open(&mut self.show_watchlist)
+            .show(ctx, |ui| {
+                if let Some(error) = &self.watchlist_load_error {
+                    ui.colored_label(
+                        egui::Color32::from_rgb(255, 100, 100),
+                        format!("Could not read the saved watchlist, changes won't be saved: {}", error),
+                    );
+                }
+                if self.watchlist.is_empty() {
+                    ui.label("Nothing watched yet");
+                }
+                egui::Grid::new("watchlist_grid").striped(true).show(ui, |ui| {
+                    for (idx, entry) in self.watchlist.entries().iter().enumerate() {
+                        ui.label(entry.matcher.kind().name());
+                        let in_feed = self
+                            .aircraft
+                            .iter()
+                            .any(|a| self.watchlist.matching(a).is_some_and(|m| std::ptr::eq(m, entry)));
+                        if let Some(error) = self.watchlist.error(idx) {
+                            ui.colored_label(egui::Color32::from_rgb(255, 100, 100), entry.matcher.value())
+                                .on_hover_text(error);
+                        } else if in_feed {
+                            ui.colored_label(egui::Color32::from_rgb(255, 200, 40), entry.matcher.value());
+                        } else {
+                            ui.label(entry.matcher.value());
+                        }
+                        let mut note = entry.note.clone();
+                        let response = ui.text_edit_singleline(&mut note);
+                        if response.changed() {
+                            note_edit = Some((idx, note));
+                        }
+                        save_notes |= response.lost_focus();
+                        if ui.small_button("🗑").clicked() {
+                            remove = Some(idx);
+                        }
+                        ui.end_row();
                     }
                 });
 
-                if let Some(selected) = &self.selected_aircraft {
-                    if selected == &aircraft.icao24 {
-                        ui.indent("details", |ui| {
-                            if let Some(callsign) = &aircraft.callsign {
-                                ui.label(format!("Callsign: {}", callsign));
-                            }
-                            if let Some(country) = &aircraft.origin_country {
-                                ui.label(format!("Country: {}", country));
-                            }
-                            if let Some(speed) = aircraft.velocity {
-                                ui.label(format!("Speed: {:.0} km/h", speed));
-                            }
-                            if let Some(heading) = aircraft.true_track {
-                                ui.label(format!("Heading: {:.0}°", heading));
-                            }
-                            if let Some(squawk) = &aircraft.squawk {
-                                ui.label(format!("Squawk: {}", squawk));
+                ui.separator();
+
+                ui.horizontal(|ui| {
+                    egui::ComboBox::from_id_source("watch_kind")
+                        .selected_text(self.new_watch_kind.name())
+                        .show_ui(ui, |ui| {
+                            for kind in WatchKind::ALL {
+                                ui.selectable_value(&mut self.new_watch_kind, kind, kind.name());
                             }
                         });
+                    ui.text_edit_singleline(&mut self.new_watch_value);
+                });
+                ui.horizontal(|ui| {
+                    ui.label("Note:");
+                    ui.text_edit_singleline(&mut self.new_watch_note);
+                    if ui.button("Watch").clicked() {
+                        add_requested = true;
                     }
-                }
-            }
-        });
+                });
+            });
+
+        if let Some((idx, note)) = note_edit {
+            self.watchlist.set_note(idx, note);
+        }
+        if save_notes {
+            self.watchlist_changed();
+        }
+        if let Some(idx) = remove {
+            self.watchlist.remove(idx);
+            self.watchlist_changed();
+        }
+        if add_requested {
+            let matcher = WatchMatch::new(self.new_watch_kind, &self.new_watch_value);
+            let note = std::mem::take(&mut self.new_watch_note);
+            self.add_watch(matcher, note);
+            self.new_watch_value.clear();
+        }
     }
 
-    fn draw_settings_window(&mut self, ctx: &egui::Context) {
-        if !self.show_settings {
+    fn draw_alerts_window(&mut self, ctx: &egui::Context) {
+        if !self.show_alerts {
             return;
         }
 
-        egui::Window::new("Settings")
-            .open(&mut self.show_settings)
+        let mut changed = false;
+        let mut remove = None;
+        let mut new_rule = None;
+        let mut remove_webhook = None;
+        egui::Window::new("Alerts")
+            .open(&mut self.show_alerts)
             .show(ctx, |ui| {
-                ui.label("Location");
+                for (idx, rule) in self.config.alert_rules.iter_mut().enumerate() {
+                    ui.push_id(idx, |ui| {
+                        ui.horizontal(|ui| {
+                            changed |= ui.checkbox(&mut rule.enabled, &rule.name).changed();
+                            egui::ComboBox::from_id_source("severity")
+                                .selected_text(rule.severity.name())
+                                .show_ui(ui, |ui| {
+                                    for severity in Severity::ALL {
+                                        changed |= ui.selectable_value(&mut rule.severity, severity, severity.name()).changed();
+                                    }
+                                });
+                            ui.label("Cooldown (s):");
+                            changed |= ui
+                                .add(egui::DragValue::new(&mut rule.cooldown_secs).speed(10.0).clamp_range(0..=86400))
+                                .changed();
+                            if ui.small_button("🗑").clicked() {
+                                remove = Some(idx);
+                            }
+                        });
+
+                        ui.horizontal(|ui| {
+                            ui.label("When:");
+                            match &mut rule.condition {
+                                AlertCondition::Expression(source) => {
+                                    changed |= ui
+                                        .add(egui::TextEdit::singleline(source).font(egui::TextStyle::Monospace))
+                                        .changed();
+                                }
+                                AlertCondition::SquawkChange => {
+                                    ui.label("squawk changes");
+                                }
+                            }
+                        });
+                        if let Some(error) = self.alert_rule_errors.get(&rule.name) {
+                            ui.colored_label(egui::Color32::from_rgb(255, 100, 100), error);
+                        }
+
+                        ui.horizontal(|ui| {
+                            changed |= ui.checkbox(&mut rule.actions.banner, "Banner").changed();
+                            changed |= ui.checkbox(&mut rule.actions.desktop, "Desktop notification").changed();
+                        });
+                        ui.horizontal(|ui| {
+                            ui.label("Run command:");
+                            let mut command = rule.actions.command.clone().unwrap_or_default();
+                            if ui.text_edit_singleline(&mut command).changed() {
+                                rule.actions.command = Some(command).filter(|c| !c.trim().is_empty());
+                                changed = true;
+                            }
+                        });
+                        ui.horizontal(|ui| {
+                            ui.label("Append to log:");
+                            let mut log_file = rule
+                                .actions
+                                .log_file
+                                .as_ref()
+                                .map(|p| p.display().to_string())
+                                .unwrap_or_default();
+                            if ui.text_edit_singleline(&mut log_file).changed() {
+                                let log_file = log_file.trim();
+                                rule.actions.log_file = (!log_file.is_empty()).then(|| log_file.into());
+                                changed = true;
+                            }
+                        });
+                    });
+                    ui.separator();
+                }
+
                 ui.horizontal(|ui| {
-                    ui.label("Latitude:");
-                    let mut lat = self.config.location.lat;
-                    if ui.add(egui::DragValue::new(&mut lat).speed(0.1)).changed() {
-                        self.config.location.lat = lat;
-                    }
+                    ui.label("Name:");
+                    ui.text_edit_singleline(&mut self.new_rule_name);
                 });
                 ui.horizontal(|ui| {
-                    ui.label("Longitude:");
-                    let mut lon = self.config.location.lon;
-                    if ui.add(egui::DragValue::new(&mut lon).speed(0.1)).changed() {
-                        self.config.location.lon = lon;
+                    ui.add(
+                        egui::TextEdit::singleline(&mut self.new_rule_expression)
+                            .hint_text("altitude < 2000 and distance < 5km"),
+                    );
+                    if ui.button("Add rule").clicked() {
+                        let expression = std::mem::take(&mut self.new_rule_expression);
+                        new_rule = Some(AlertCondition::Expression(expression));
+                    }
+                    if ui.button("Add squawk change").clicked() {
+                        new_rule = Some(AlertCondition::SquawkChange);
                     }
                 });
 
                 ui.separator();
 
-                ui.label("Radar Settings");
+                ui.label("Webhooks");
+                let sample = WebhookEvent::lifecycle(
+                    EventKind::Appeared,
+                    &Aircraft::new("abc123".to_string()),
+                    self.config.callsign_display,
+                    Utc::now(),
+                );
+                for (idx, hook) in self.config.webhooks.iter_mut().enumerate() {
+                    ui.push_id(("webhook", idx), |ui| {
+                        ui.horizontal(|ui| {
+                            ui.checkbox(&mut hook.enabled, &hook.name);
+                            ui.text_edit_singleline(&mut hook.url);
+                            if ui.small_button("🗑").clicked() {
+                                remove_webhook = Some(idx);
+                            }
+                        });
+                        ui.horizontal(|ui| {
+                            ui.label("Send:");
+                            for kind in EventKind::ALL {
+                                let mut subscribed = hook.events.contains(&kind);
+                                if ui.checkbox(&mut subscribed, kind.name()).changed() {
+                                    hook.events.retain(|k| *k != kind);
+                                    if subscribed {
+                                        hook.events.push(kind);
+                                    }
+                                }
+                            }
+                        });
+                        ui.add(
+                            egui::TextEdit::multiline(&mut hook.template)
+                                .code_editor()
+                                .desired_rows(2)
+                                .desired_width(f32::INFINITY),
+                        );
+                        if let Err(e) = webhook::render(&hook.template, &sample) {
+                            ui.colored_label(egui::Color32::from_rgb(255, 100, 100), format!("{:#}", e));
+                        }
+                    });
+                }
                 ui.horizontal(|ui| {
-                    ui.label("Radius (km):");
-                    let mut radius = self.config.radar_radius_km;
-                    if ui.add(egui::DragValue::new(&mut radius).speed(0.5).clamp_range(1.0..=100.0)).changed() {
-                        self.config.set_radar_radius(radius);
+                    ui.label("Name:");
+                    ui.text_edit_singleline(&mut self.new_webhook_name);
+                    ui.label("URL:");
+                    ui.text_edit_singleline(&mut self.new_webhook_url);
+                    if ui.button("Add webhook").clicked() && !self.new_webhook_url.trim().is_empty() {
+                        let name = match self.new_webhook_name.trim() {
+                            "" => format!("Webhook {}", self.config.webhooks.len() + 1),
+                            name => name.to_string(),
+                        };
+                        self.config.webhooks.push(WebhookSettings::new(&name, &self.new_webhook_url));
+                        self.new_webhook_name.clear();
+                        self.new_webhook_url.clear();
                     }
                 });
-
                 ui.horizontal(|ui| {
-                    ui.label("Refresh interval (seconds):");
-                    let mut interval = self.config.refresh_interval_seconds;
-                    if ui.add(egui::DragValue::new(&mut interval).speed(1.0).clamp_range(10..=300)).changed() {
-                        self.config.set_refresh_interval(interval);
+                    let mut outbox = self.outbox.lock().unwrap();
+                    ui.label(format!("{} deliveries queued", outbox.len()));
+                    if let Some(error) = outbox.entries().iter().find_map(|e| e.last_error.as_ref()) {
+                        ui.colored_label(egui::Color32::from_rgb(255, 190, 40), error);
+                    }
+                    if !outbox.is_empty() && ui.button("Discard").clicked() {
+                        outbox.clear();
                     }
                 });
 
-                ui.checkbox(&mut self.config.auto_refresh, "Auto-refresh");
-                ui.checkbox(&mut self.config.show_trails, "Show aircraft trails");
-
-                ui.separator();
-
-                ui.label("Theme");
-                egui::ComboBox::from_label("Theme")
-                    .selected_text(self.config.theme.name())
-                    .show_ui(ui, |ui| {
-                        ui.selectable_value(&mut self.config.theme, Theme::Dark, "Dark");
-                        ui.selectable_value(&mut self.config.theme, Theme::Light, "Light");
-                        ui.selectable_value(&mut self.config.theme, Theme::Auto, "Auto");
-                    });
-
+                ui.separator();
+
+                ui.label("Recent alerts");
+                egui::ScrollArea::vertical().max_height(150.0).show(ui, |ui| {
+                    for alert in &self.alert_history {
+                        ui.horizontal(|ui| {
+                            ui.label(alert.time.format("%H:%M:%S").to_string());
+                            ui.colored_label(alert.severity.color(), alert.severity.name());
+                            ui.label(format!("{}: {}", alert.rule, alert.message));
+                        });
+                    }
+                });
+            });
+
+        if let Some(idx) = remove {
+            self.config.alert_rules.remove(idx);
+            changed = true;
+        }
+        if let Some(idx) = remove_webhook {
+            self.config.webhooks.remove(idx);
+        }
+        if let Some(condition) = new_rule {
+            let name = rule_name(&self.new_rule_name, &self.config.alert_rules);
+            self.config.alert_rules.push(AlertRule::new(&name, condition));
+            self.new_rule_name.clear();
+            changed = true;
+        }
+        if changed {
+            self.alert_rules_changed();
+        }
+    }
+
+    fn draw_alert_banners(&mut self, ui: &mut egui::Ui) {
+        let mut dismissed = None;
+        for (idx, alert) in self.alert_banners.iter().enumerate().rev() {
+            let color = alert.severity.color();
+            egui::Frame::none()
+                .fill(color.gamma_multiply(0.2))
+                .stroke(egui::Stroke::new(1.0, color))
+                .inner_margin(4.0)
+                .show(ui, |ui| {
+                    ui.horizontal(|ui| {
+                        ui.colored_label(color, format!("{} {}", alert.time.format("%H:%M:%S"), alert.rule));
+                        if ui.link(&alert.message).clicked() {
+                            self.selected_aircraft = Some(alert.icao24.clone());
+                        }
+                        ui.with_layout(egui::Layout::right_to_left(egui::Align::Center), |ui| {
+                            if ui.small_button("✖").clicked() {
+                                dismissed = Some(idx);
+                            }
+                        });
+                    });
+                });
+        }
+        if let Some(idx) = dismissed {
+            self.alert_banners.remove(idx);
+        }
+    }
+
+    fn geofences_changed(&mut self) {
+        self.geofence_monitor.set_fences(&self.config.geofences);
+        self.radar_view.set_geofences(&self.config.geofences);
+    }
+
+    fn filter_context(&self) -> FilterContext<'_> {
+        FilterContext {
+            home: &self.config.location,
+            now: Utc::now(),
+            phases: Some(&self.phase_tracker),
+        }
+    }
+
+    fn set_radar_filter(&mut self) {
+        match Filter::parse_optional(&self.config.radar_filter) {
+            Ok(filter) => {
+                self.radar_filter = filter;
+                self.radar_filter_error = None;
+            }
+            // Keep showing the last valid filter while the new one is fixed
+            Err(e) => self.radar_filter_error = Some(e.describe(&self.config.radar_filter)),
+        }
+        self.update_radar_aircraft();
+    }
+
+    fn set_list_filter(&mut self) {
+        match Filter::parse_optional(&self.list_filter_text) {
+            Ok(filter) => {
+                self.list_query.expression = filter;
+                self.list_filter_error = None;
+            }
+            Err(e) => self.list_filter_error = Some(e.describe(&self.list_filter_text)),
+        }
+    }
+
+    fn update_radar_aircraft(&mut self) {
+        let context = self.filter_context();
+        let (visible, hidden): (Vec<Aircraft>, Vec<Aircraft>) = match &self.radar_filter {
+            Some(filter) => self.aircraft.iter().cloned().partition(|a| filter.matches(a, &context)),
+            None => (self.aircraft.clone(), Vec::new()),
+        };
+        self.radar_view.set_hidden(hidden.into_iter().map(|a| a.icao24));
+        self.radar_aircraft = visible;
+    }
+
+    // Centre of the radar: the followed aircraft, otherwise home
+    fn view_center(&self) -> Location {
+        self.following
+            .as_ref()
+            .and_then(|icao24| self.aircraft.iter().find(|a| &a.icao24 == icao24))
+            .and_then(|aircraft| prediction::extrapolate(aircraft, Utc::now()))
+            .unwrap_or_else(|| self.config.location.clone())
+    }
+
+    fn toggle_follow(&mut self, icao24: String) {
+        if self.following.as_ref() == Some(&icao24) {
+            self.stop_following();
+            return;
+        }
+        if self.config.follow_track_up && self.orientation_before_follow.is_none() {
+            self.orientation_before_follow = Some(self.config.orientation);
+            self.config.orientation = RadarOrientation::TrackUp;
+        }
+        self.selected_aircraft = Some(icao24.clone());
+        self.radar_view.set_followed(Some(icao24.clone()));
+        self.following = Some(icao24);
+    }
+
+    fn stop_following(&mut self) {
+        self.following = None;
+        self.radar_view.set_followed(None);
+        if let Some(orientation) = self.orientation_before_follow.take() {
+            self.config.orientation = orientation;
+        }
+    }
+
+    fn handle_radar_input(&mut self, response: &egui::Response, center: &Location) {
+        let Some(pos) = response.interact_pointer_pos().filter(|_| response.clicked()) else {
+            return;
+        };
+        if let Some(sketch) = &mut self.fence_sketch {
+            let point = self.radar_view.screen_to_geo(pos, center);
+            sketch.push(Location::from_point(point));
+            self.radar_view.set_sketch(sketch);
+        } else if let Some(icao24) = {
+            let displayed = followed_at_center(&self.radar_aircraft, self.following.as_deref(), center);
+            self.radar_view.aircraft_at(pos, &displayed, center)
+        } {
+            self.selected_aircraft = Some(icao24);
+            self.show_details = true;
+        }
+    }
+
+    fn draw_profile_controls(&mut self, ui: &mut egui::Ui) {
+        ui.horizontal(|ui| {
+            ui.label("Vertical Profile");
+            let mut along_bearing = matches!(self.config.profile_axis, ProfileAxis::Bearing(_));
+            if ui.checkbox(&mut along_bearing, "Along bearing").changed() {
+                self.config.profile_axis = if along_bearing { ProfileAxis::Bearing(0.0) } else { ProfileAxis::Distance };
+            }
+            if let ProfileAxis::Bearing(bearing) = &mut self.config.profile_axis {
+                ui.add(egui::DragValue::new(bearing).speed(1.0).clamp_range(0.0..=359.0).suffix("°"));
+            }
+        });
+    }
+
+    fn draw_geofence_window(&mut self, ctx: &egui::Context) {
+        if !self.show_geofences {
+            return;
+        }
+
+        let mut changed = false;
+        let mut remove = None;
+        let mut finish_sketch = false;
+        egui::Window::new("Geofences")
+            .open(&mut self.show_geofences)
+            .show(ctx, |ui| {
+                for (idx, fence) in self.config.geofences.iter_mut().enumerate() {
+                    ui.horizontal(|ui| {
+                        changed |= ui.checkbox(&mut fence.enabled, &fence.name).changed();
+
+                        let mut floor = fence.min_altitude_ft.unwrap_or(0.0);
+                        let mut ceiling = fence.max_altitude_ft.unwrap_or(0.0);
+                        ui.label("Floor/ceiling ft (0 = none):");
+                        if ui.add(egui::DragValue::new(&mut floor).speed(100.0).clamp_range(0.0..=60000.0)).changed() {
+                            fence.min_altitude_ft = Some(floor).filter(|f| *f > 0.0);
+                            changed = true;
+                        }
+                        if ui.add(egui::DragValue::new(&mut ceiling).speed(100.0).clamp_range(0.0..=60000.0)).changed() {
+                            fence.max_altitude_ft = Some(ceiling).filter(|c| *c > 0.0);
+                            changed = true;
+                        }
+                        if ui.small_button("🗑").clicked() {
+                            remove = Some(idx);
+                        }
+                    });
+                }
+
+                ui.separator();
+
+                ui.horizontal(|ui| {
+                    ui.label("Name:");
+                    ui.text_edit_singleline(&mut self.new_fence_name);
+                });
+                ui.horizontal(|ui| {
+                    ui.label("Radius (km):");
+                    ui.add(egui::DragValue::new(&mut self.new_fence_radius_km).speed(0.1).clamp_range(0.1..=500.0));
+                    if ui.button("Add circle at home").clicked() {
+                        let name = fence_name(&self.new_fence_name, self.config.geofences.len());
+                        self.config.geofences.push(Geofence::circle(name, self.config.location.clone(), self.new_fence_radius_km));
+                        changed = true;
+                    }
+                });
+
+                ui.horizontal(|ui| {
+                    let Some(points) = self.fence_sketch.as_ref().map(Vec::len) else {
+                        if ui.button("Draw polygon").clicked() {
+                            self.fence_sketch = Some(Vec::new());
+                        }
+                        return;
+                    };
+
+                    ui.label(format!("Click the radar to add points ({})", points));
+                    if ui.button("Finish").clicked() {
+                        finish_sketch = true;
+                    }
+                    if ui.button("Cancel").clicked() {
+                        self.fence_sketch = None;
+                        self.radar_view.set_sketch(&[]);
+                    }
+                });
+
+                ui.horizontal(|ui| {
+                    ui.label("GeoJSON file:");
+                    ui.text_edit_singleline(&mut self.geofence_import_path);
+                    if ui.button("Import").clicked() {
+                        match geofence::import_geojson(std::path::Path::new(self.geofence_import_path.trim())) {
+                            Ok(fences) => {
+                                self.status_message = format!("Imported {} geofences", fences.len());
+                                self.config.geofences.extend(fences);
+                                changed = true;
+                            }
+                            Err(e) => {
+                                error!("Failed to import geofences: {}", e);
+                                self.status_message = format!("Geofence import failed: {}", e);
+                            }
+                        }
+                    }
+                });
+
+                ui.separator();
+
+                ui.label("Recent events");
+                egui::ScrollArea::vertical().max_height(150.0).show(ui, |ui| {
+                    for event in &self.geofence_events {
+                        ui.label(format!(
+                            "{} {} {} {}",
+                            event.time.format("%H:%M:%S"),
+                            event.icao24,
+                            event.kind.name(),
+                            event.fence
+                        ));
+                    }
+                });
+            });
+
+        if let Some(idx) = remove {
+            self.config.geofences.remove(idx);
+            changed = true;
+        }
+
+        if finish_sketch {
+            if let Some(vertices) = self.fence_sketch.take().filter(|v| v.len() >= 3) {
+                let name = fence_name(&self.new_fence_name, self.config.geofences.len());
+                self.config.geofences.push(Geofence::polygon(name, vertices));
+                changed = true;
+            }
+            self.fence_sketch = None;
+            self.radar_view.set_sketch(&[]);
+        }
+
+        if changed {
+            self.geofences_changed();
+        }
+    }
+
+    fn aircraft_label(&self, icao24: &str) -> String {
+        self.aircraft
+            .iter()
+            .find(|a| a.icao24 == icao24)
+            .map(|a| a.label(self.config.callsign_display))
+            .unwrap_or_else(|| icao24.to_string())
+    }
+
+    fn draw_passing_soon_window(&mut self, ctx: &egui::Context) {
+        if !self.show_passing_soon {
+            return;
+        }
+
+        let mut sort = self.passing_sort;
+        let labels: Vec<String> = self.passing_soon.iter().map(|a| self.aircraft_label(&a.icao24)).collect();
+        egui::Window::new("Passing Soon")
+            .open(&mut self.show_passing_soon)
+            .show(ctx, |ui| {
+                ui.horizontal(|ui| {
+                    ui.label("Sort by:");
+                    for option in [PassingSort::Eta, PassingSort::Distance, PassingSort::Elevation] {
+                        ui.selectable_value(&mut sort, option, option.name());
+                    }
+                });
+                ui.horizontal(|ui| {
+                    ui.label("Look ahead (min):");
+                    ui.add(egui::DragValue::new(&mut self.config.passing_horizon_minutes).speed(1.0).clamp_range(1.0..=120.0));
+                    ui.label("Alert within (km):");
+                    ui.add(egui::DragValue::new(&mut self.config.overhead_alert_km).speed(0.1).clamp_range(0.1..=50.0));
+                });
+
+                ui.separator();
+
+                if self.passing_soon.is_empty() {
+                    ui.label("Nothing expected overhead");
+                    return;
+                }
+
+                egui::Grid::new("passing_soon_grid").striped(true).show(ui, |ui| {
+                    ui.strong("Aircraft");
+                    ui.strong("ETA");
+                    ui.strong("Min dist");
+                    ui.strong("Bearing");
+                    ui.strong("Elevation");
+                    ui.end_row();
+
+                    for (approach, label) in self.passing_soon.iter().zip(&labels) {
+                        let text = egui::RichText::new(label);
+                        let text = if approach.is_overhead(self.config.overhead_alert_km) {
+                            text.color(egui::Color32::from_rgb(255, 140, 0))
+                        } else {
+                            text
+                        };
+                        if ui.selectable_label(self.selected_aircraft.as_ref() == Some(&approach.icao24), text).clicked() {
+                            self.selected_aircraft = Some(approach.icao24.clone());
+                        }
+
+                        ui.label(format!("{} ({})", approach.eta.format("%H:%M:%S"), format_duration(approach.time_to_cpa_secs)));
+                        ui.label(format!("{:.1} km", approach.min_distance_km));
+                        ui.label(format!("{:.0}°", approach.bearing_deg));
+                        ui.label(
+                            approach
+                                .elevation_deg
+                                .map(|e| format!("{:.0}°", e))
+                                .unwrap_or_else(|| "-".to_string()),
+                        );
+                        ui.end_row();
+                    }
+                });
+            });
+
+        if sort != self.passing_sort {
+            self.passing_sort = sort;
+            self.passing_sort.sort(&mut self.passing_soon);
+        }
+    }
+
+    fn draw_main_window(&mut self, ctx: &egui::Context) {
+        egui::CentralPanel::default().show(ctx, |ui| {
+            ui.horizontal(|ui| {
+                ui.heading("SkyRadar - Live Aircraft Tracker");
+                ui.with_layout(egui::Layout::right_to_left(egui::Align::Center), |ui| {
+                    if ui.button("⚙️ Settings").clicked() {
+                        self.show_settings = !self.show_settings;
+                    }
+                    if ui.button("🔔 Alerts").clicked() {
+                        self.show_alerts = !self.show_alerts;
+                    }
+                    if ui.button("★ Watchlist").clicked() {
+                        self.show_watchlist = !self.show_watchlist;
+                    }
+                    if ui.button("⬡ Geofences").clicked() {
+                        self.show_geofences = !self.show_geofences;
+                    }
+                    if self.following.is_some() && ui.button("🏠 Home").clicked() {
+                        self.stop_following();
+                    }
+                    let measuring = self.radar_view.is_measuring();
+                    if ui.selectable_label(measuring, "📏 Measure").clicked() {
+                        self.radar_view.set_measuring(!measuring);
+                    }
+                    if ui.button("📉 History").clicked() {
+                        self.show_history = !self.show_history;
+                    }
+                    if ui.button("📈 Profile").clicked() {
+                        self.show_profile = !self.show_profile;
+                    }
+                    if ui.button("⏱ Passing").clicked() {
+                        self.show_passing_soon = !self.show_passing_soon;
+                    }
+                    if ui.button("📋 Aircraft").clicked() {
+                        self.show_aircraft_list = !self.show_aircraft_list;
+                    }
+                    if ui.button("🔄 Refresh").clicked() {
+                        self.refresh_aircraft_data();
+                    }
+                });
+            });
+
+            ui.separator();
+
+            // Status bar
+            ui.horizontal(|ui| {
+                ui.label(&self.status_message);
+                ui.with_layout(egui::Layout::right_to_left(egui::Align::Center), |ui| {
+                    if self.config.auto_refresh {
+                        let remaining = self.config.refresh_interval_seconds as f32 - self.refresh_timer;
+                        ui.label(format!("Auto-refresh in {:.0}s", remaining.max(0.0)));
+                    }
+                });
+            });
+
+            if !self.alert_banners.is_empty() {
+                self.draw_alert_banners(ui);
+            }
+
+            ui.separator();
+
+            // Main content area
+            ui.horizontal(|ui| {
+                // Radar view (takes most space)
+                ui.vertical(|ui| {
+                    ui.label("Radar View");
+                    self.radar_view.set_selected(self.selected_aircraft.clone());
+
+                    let available = ui.available_size();
+                    let radar_height = if self.show_profile { available.y * 0.65 } else { available.y };
+                    let center = self.view_center();
+                    let displayed = followed_at_center(&self.radar_aircraft, self.following.as_deref(), &center);
+                    let response = ui
+                        .allocate_ui(egui::vec2(available.x, radar_height), |ui| {
+                            self.radar_view.draw(ui, &displayed, &self.config, &center)
+                        })
+                        .inner;
+                    self.handle_radar_input(&response, &center);
+
+                    if self.show_profile {
+                        self.draw_profile_controls(ui);
+                        let response = self.profile_view.draw(
+                            ui,
+                            &self.radar_aircraft,
+                            &self.radar_view,
+                            &self.config,
+                            self.selected_aircraft.as_deref(),
+                        );
+                        if let Some(pos) = response.interact_pointer_pos().filter(|_| response.clicked()) {
+                            if let Some(icao24) = self.profile_view.aircraft_at(pos, &self.radar_aircraft, &self.config) {
+                                self.selected_aircraft = Some(icao24);
+                            }
+                        }
+                    }
+                });
+
+                // Side panels
+                if self.show_aircraft_list {
+                    ui.vertical(|ui| {
+                        ui.label("Aircraft List");
+                        self.draw_aircraft_list(ui);
+                    });
+                }
+            });
+        });
+    }
+
+    fn draw_aircraft_list(&mut self, ui: &mut egui::Ui) {
+        ui.set_enabled(!self.is_loading);
+
+        ui.horizontal(|ui| {
+            egui::ComboBox::from_label("Phase")
+                .selected_text(self.phase_filter.map(|p| p.name()).unwrap_or("All"))
+                .show_ui(ui, |ui| {
+                    ui.selectable_value(&mut self.phase_filter, None, "All");
+                    for phase in FlightPhase::ALL {
+                        ui.selectable_value(&mut self.phase_filter, Some(phase), phase.name());
+                    }
+                });
+            ui.add(egui::TextEdit::singleline(&mut self.list_query.search).hint_text("🔍 Search").desired_width(140.0));
+        });
+        let mut list_filter_changed = false;
+        ui.horizontal(|ui| {
+            ui.label("Filter:");
+            list_filter_changed = ui
+                .add(egui::TextEdit::singleline(&mut self.list_filter_text).hint_text("altitude < 10000 and distance < 20km"))
+                .changed();
+        });
+        if let Some(error) = &self.list_filter_error {
+            ui.colored_label(egui::Color32::from_rgb(255, 90, 90), egui::RichText::new(error).monospace());
+        }
+        if list_filter_changed {
+            self.set_list_filter();
+        }
+        ui.horizontal_wrapped(|ui| {
+            for filter in QuickFilter::ALL {
+                if ui.selectable_label(self.list_query.has_filter(filter), filter.name()).clicked() {
+                    self.list_query.toggle_filter(filter);
+                }
+            }
+        });
+
+        if self.aircraft.is_empty() {
+            ui.label("No aircraft detected");
+            return;
+        }
+
+        let phase_filter = self.phase_filter;
+        let phase_tracker = &self.phase_tracker;
+        let rows = self.list_query.apply(
+            self.aircraft
+                .iter()
+                .filter(|a| !phase_filter.is_some_and(|filter| filter != phase_tracker.phase(&a.icao24))),
+            &self.filter_context(),
+            self.config.callsign_display,
+        );
+
+        let mut sort_clicked = None;
+        let mut select_clicked = None;
+        egui::ScrollArea::both().max_height(ui.available_height() * 0.6).show(ui, |ui| {
+            egui::Grid::new("aircraft_table").striped(true).show(ui, |ui| {
+                for column in ListColumn::ALL {
+                    let sorted = self.list_query.sort == column;
+                    let arrow = match (sorted, self.list_query.descending) {
+                        (false, _) => "",
+                        (true, false) => " ▲",
+                        (true, true) => " ▼",
+                    };
+                    if ui.selectable_label(sorted, format!("{}{}", column.name(), arrow)).clicked() {
+                        sort_clicked = Some(column);
+                    }
+                }
+                ui.label("");
+                ui.end_row();
+
+                for row in &rows {
+                    let aircraft = row.aircraft;
+                    let is_selected = self.selected_aircraft.as_ref() == Some(&aircraft.icao24);
+                    for column in ListColumn::ALL {
+                        let text = row.text(column);
+                        if column == ListColumn::Callsign {
+                            if ui.selectable_label(is_selected, text).clicked() {
+                                select_clicked = Some(aircraft.icao24.clone());
+                            }
+                        } else {
+                            ui.label(text);
+                        }
+                    }
+
+                    ui.horizontal(|ui| {
+                        let phase = self.phase_tracker.phase(&aircraft.icao24);
+                        ui.colored_label(phase.color(), phase.short_name());
+
+                        if let Some(code) = aircraft.country_code() {
+                            let flag = icao::flag_emoji(code).unwrap_or_default();
+                            ui.label(format!("{} {}", flag, code).trim());
+                        }
+
+                        match aircraft.address_kind() {
+                            AddressKind::Military => {
+                                ui.colored_label(egui::Color32::from_rgb(255, 140, 0), "MIL");
+                            }
+                            AddressKind::NonIcao => {
+                                ui.colored_label(egui::Color32::GRAY, "ANON");
+                            }
+                            _ => {}
+                        }
+
+                        if let Some(inside) = self.airspace_hits.get(&aircraft.icao24) {
+                            ui.colored_label(egui::Color32::from_rgb(200, 120, 255), "ASP")
+                                .on_hover_text(inside.join("\n"));
+                        }
+
+                        if let Some(note) = self.watched.get(&aircraft.icao24) {
+                            let star = ui.colored_label(egui::Color32::from_rgb(255, 200, 40), "★");
+                            if !note.is_empty() {
+                                star.on_hover_text(note);
+                            }
+                        }
+                    });
+                    ui.end_row();
+                }
+            });
+        });
+        ui.label(format!("{} of {} aircraft", rows.len(), self.aircraft.len()));
+
+        if let Some(column) = sort_clicked {
+            self.list_query.sort_by(column);
+        }
+        if let Some(icao24) = select_clicked {
+            self.selected_aircraft = Some(icao24);
+            self.show_details = true;
+        }
+    }
+
+    fn draw_history_window(&mut self, ctx: &egui::Context) {
+        if !self.show_history {
+            return;
+        }
+
+        let aircraft = self.history_chart.aircraft(self.selected_aircraft.as_deref());
+        let extent = history_chart::data_extent(&self.track_store, &aircraft);
+        let mut pin_toggle = None;
+        let mut export_requested = false;
+        egui::Window::new("History")
+            .open(&mut self.show_history)
+            .default_size([560.0, 380.0])
+            .show(ctx, |ui| {
+                ui.horizontal(|ui| {
+                    for series in ChartSeries::ALL {
+                        if ui.selectable_label(self.history_chart.shows(series), series.name()).clicked() {
+                            self.history_chart.toggle_series(series);
+                        }
+                    }
+                });
+
+                ui.horizontal_wrapped(|ui| {
+                    if let Some(selected) = &self.selected_aircraft {
+                        let text = if self.history_chart.is_pinned(selected) { "Unpin selected" } else { "📌 Pin selected" };
+                        if ui.button(text).clicked() {
+                            pin_toggle = Some(selected.clone());
+                        }
+                    }
+                    for icao24 in self.history_chart.pinned() {
+                        if ui.small_button(format!("✖ {}", icao24)).on_hover_text("Unpin").clicked() {
+                            pin_toggle = Some(icao24.clone());
+                        }
+                    }
+                });
+
+                if let Some(extent) = extent {
+                    ui.horizontal(|ui| {
+                        if ui.small_button("◀").clicked() {
+                            self.history_chart.pan(-0.25, extent);
+                        }
+                        if ui.small_button("➖").clicked() {
+                            self.history_chart.zoom(2.0, 0.5, extent);
+                        }
+                        if ui.small_button("➕").clicked() {
+                            self.history_chart.zoom(0.5, 0.5, extent);
+                        }
+                        if ui.small_button("▶").clicked() {
+                            self.history_chart.pan(0.25, extent);
+                        }
+                        if self.history_chart.is_zoomed() && ui.small_button("Show all").clicked() {
+                            self.history_chart.reset();
+                        }
+                        ui.weak("Drag to pan, ctrl+scroll to zoom");
+                    });
+                }
+
+                ui.horizontal(|ui| {
+                    ui.label("Export CSV:");
+                    ui.text_edit_singleline(&mut self.history_export_path);
+                    if ui.button("Export").clicked() {
+                        export_requested = true;
+                    }
+                });
+
+                ui.separator();
+
+                let feed = &self.aircraft;
+                let display = self.config.callsign_display;
+                self.history_chart.draw(ui, &self.track_store, &self.config.location, &aircraft, |icao24| {
+                    feed.iter()
+                        .find(|a| a.icao24 == icao24)
+                        .map(|a| a.label(display))
+                        .unwrap_or_else(|| icao24.to_string())
+                });
+            });
+
+        if let Some(icao24) = pin_toggle {
+            if !self.history_chart.toggle_pin(&icao24) {
+                self.status_message = format!("At most {} aircraft can be pinned", history_chart::MAX_PINNED);
+            }
+        }
+        if export_requested {
+            self.export_history();
+        }
+    }
+
+    fn draw_details_window(&mut self, ctx: &egui::Context) {
+        if !self.show_details {
+            return;
+        }
+        let Some(aircraft) = self
+            .selected_aircraft
+            .as_ref()
+            .and_then(|icao24| self.aircraft.iter().find(|a| &a.icao24 == icao24))
+        else {
+            return;
+        };
+
+        let details = AircraftDetails::new(
+            aircraft,
+            &self.config.location,
+            self.phase_tracker.phase(&aircraft.icao24),
+            Utc::now(),
+            self.config.callsign_display,
+            self.config.overhead_alert_km,
+        );
+        let icao24 = aircraft.icao24.clone();
+
+        let mut follow_toggle = false;
+        let mut watch_requested = false;
+        let mut copied = None;
+        egui::Window::new("Aircraft Details")
+            .open(&mut self.show_details)
+            .default_width(320.0)
+            .show(ctx, |ui| {
+                ui.horizontal(|ui| {
+                    ui.heading(&details.title);
+                    ui.with_layout(egui::Layout::right_to_left(egui::Align::Center), |ui| {
+                        if ui.button("📋 Copy").on_hover_text("Copy all fields").clicked() {
+                            copied = Some(details.to_text());
+                        }
+                        if !self.watched.contains_key(&icao24) && ui.button("☆ Watch").clicked() {
+                            watch_requested = true;
+                        }
+                        let following = self.following.as_ref() == Some(&icao24);
+                        if ui.selectable_label(following, "🎯 Follow").clicked() {
+                            follow_toggle = true;
+                        }
+                    });
+                });
+                if let Some(note) = self.watched.get(&icao24).filter(|n| !n.is_empty()) {
+                    ui.colored_label(egui::Color32::from_rgb(255, 200, 40), format!("★ {}", note));
+                }
+                if let Some(inside) = self.airspace_hits.get(&icao24) {
+                    ui.colored_label(egui::Color32::from_rgb(200, 120, 255), format!("Inside {}", inside.join(", ")));
+                }
+
+                if let Some(trail) = self.radar_view.trail(&icao24).filter(|t| t.len() > 1) {
+                    aircraft_details::draw_history(ui, trail);
+                }
+
+                egui::ScrollArea::vertical().show(ui, |ui| {
+                    for section in &details.sections {
+                        egui::CollapsingHeader::new(section.title)
+                            .default_open(true)
+                            .show(ui, |ui| {
+                                egui::Grid::new(section.title).striped(true).show(ui, |ui| {
+                                    for field in &section.fields {
+                                        ui.label(field.label);
+                                        let value = ui.add(egui::Label::new(&field.value).sense(egui::Sense::click()));
+                                        if value.on_hover_text("Click to copy").clicked() {
+                                            copied = Some(field.value.clone());
+                                        }
+                                        match field.age_secs {
+                                            // Stale values are worth a second look
+                                            Some(age) if age > 60.0 => {
+                                                ui.colored_label(egui::Color32::from_rgb(255, 150, 0), format_duration(age));
+                                            }
+                                            Some(age) => {
+                                                ui.weak(format_duration(age));
+                                            }
+                                            None => {
+                                                ui.label("");
+                                            }
+                                        }
+                                        ui.end_row();
+                                    }
+                                });
+                            });
+                    }
+                });
+            });
+
+        if let Some(text) = copied {
+            ctx.output_mut(|o| o.copied_text = text);
+            self.status_message = "Copied to clipboard".to_string();
+        }
+        if follow_toggle {
+            self.toggle_follow(icao24.clone());
+        }
+        if watch_requested {
+            self.add_watch(WatchMatch::Icao24(icao24), String::new());
+        }
+    }
+
+    fn draw_settings_window(&mut self, ctx: &egui::Context) {
+        if !self.show_settings {
+            return;
+        }
+
+        let mut import_requested = false;
+        let mut airspace_import_requested = false;
+        let mut airspace_removed = None;
+        let mut add_layer_requested = false;
+        let mut basemap_requested = false;
+        let mut basemap_closed = false;
+        let mut heatmap_changed = false;
+        let mut radar_filter_changed = false;
+        let mut session_save_requested = false;
+        let mut session_load_requested = false;
+        let mut layer_removed = None;
+        egui::Window::new("Settings")
+            .open(&mut self.show_settings)
+            .show(ctx, |ui| {
+                ui.label("Location");
+                ui.horizontal(|ui| {
+                    ui.label("Latitude:");
+                    let mut lat = self.config.location.lat;
+                    if ui.add(egui::DragValue::new(&mut lat).speed(0.1)).changed() {
+                        self.config.location.lat = lat;
+                    }
+                });
+                ui.horizontal(|ui| {
+                    ui.label("Longitude:");
+                    let mut lon = self.config.location.lon;
+                    if ui.add(egui::DragValue::new(&mut lon).speed(0.1)).changed() {
+                        self.config.location.lon = lon;
+                    }
+                });
+
+                ui.separator();
+
+                ui.label("Airports");
+                ui.horizontal(|ui| {
+                    ui.label("Find:");
+                    ui.text_edit_singleline(&mut self.airport_search);
+                });
+                for airport in self.airports.search(&self.airport_search, 8) {
+                    ui.horizontal(|ui| {
+                        ui.label(format!("{} {}", airport.code(AirportCodeStyle::Icao), airport.name));
+                        if ui.small_button("Set as home").clicked() {
+                            self.config.set_location(airport.location());
+                        }
+                    });
+                }
+                ui.checkbox(&mut self.config.show_airports, "Show airports on radar");
+                egui::ComboBox::from_label("Airport codes")
+                    .selected_text(match self.config.airport_code_style {
+                        AirportCodeStyle::Icao => "ICAO",
+                        AirportCodeStyle::Iata => "IATA",
+                    })
+                    .show_ui(ui, |ui| {
+                        ui.selectable_value(&mut self.config.airport_code_style, AirportCodeStyle::Icao, "ICAO");
+                        ui.selectable_value(&mut self.config.airport_code_style, AirportCodeStyle::Iata, "IATA");
+                    });
+                ui.horizontal(|ui| {
+                    ui.label("OurAirports folder:");
+                    ui.text_edit_singleline(&mut self.airport_import_path);
+                    if ui.button("Import").clicked() {
+                        import_requested = true;
+                    }
+                });
+
+                ui.separator();
+
+                ui.label("Basemap");
+                ui.horizontal(|ui| {
+                    ui.label("MBTiles file:");
+                    ui.text_edit_singleline(&mut self.basemap_path);
+                    if ui.button("Open").clicked() {
+                        basemap_requested = true;
+                    }
+                    if self.radar_view.basemap_name().is_some() && ui.button("Close").clicked() {
+                        basemap_closed = true;
+                    }
+                });
+                ui.horizontal(|ui| {
+                    ui.checkbox(&mut self.config.show_basemap, "Show basemap");
+                    ui.add(egui::Slider::new(&mut self.config.basemap_opacity, 0.0..=1.0).text("Opacity"));
+                });
+
+                ui.separator();
+
+                ui.label("Traffic Heatmap");
+                ui.horizontal(|ui| {
+                    let heatmap = &mut self.config.heatmap;
+                    heatmap_changed |= ui.checkbox(&mut heatmap.enabled, "Show heatmap").changed();
+                    ui.label("Window:");
+                    heatmap_changed |= ui
+                        .add(egui::DragValue::new(&mut heatmap.window_minutes).speed(5.0).clamp_range(5..=1440).suffix(" min"))
+                        .changed();
+                    ui.label("Cell:");
+                    heatmap_changed |= ui
+                        .add(egui::DragValue::new(&mut heatmap.cell_km).speed(0.05).clamp_range(0.1..=10.0).suffix(" km"))
+                        .changed();
+                    ui.add(egui::Slider::new(&mut heatmap.opacity, 0.0..=1.0).text("Opacity"));
+                });
+                ui.horizontal(|ui| {
+                    ui.label("Keep history (min):");
+                    if ui
+                        .add(egui::DragValue::new(&mut self.config.track_retention_minutes).speed(5.0).clamp_range(10..=2880))
+                        .changed()
+                    {
+                        self.track_store.set_retention(self.config.track_retention_minutes);
+                    }
+                });
+                ui.horizontal(|ui| {
+                    ui.label("Session file:");
+                    ui.text_edit_singleline(&mut self.session_path);
+                    if ui.button("Save").clicked() {
+                        session_save_requested = true;
+                    }
+                    if ui.button("Load").clicked() {
+                        session_load_requested = true;
+                    }
+                    if self.heatmap_session.is_some() && ui.button("Live").clicked() {
+                        self.heatmap_session = None;
+                        heatmap_changed = true;
+                    }
+                });
+
+                ui.separator();
+
+                ui.label("Map Layers");
+                for (idx, layer) in self.config.map_layers.iter_mut().enumerate() {
+                    ui.horizontal(|ui| {
+                        ui.checkbox(&mut layer.visible, &layer.name);
+                        ui.color_edit_button_srgb(&mut layer.color);
+                        ui.add(egui::DragValue::new(&mut layer.width).speed(0.1).clamp_range(0.5..=4.0));
+                        if ui.small_button("Remove").clicked() {
+                            layer_removed = Some(idx);
+                        }
+                    });
+                }
+                ui.horizontal(|ui| {
+                    ui.label("GeoJSON / Shapefile:");
+                    ui.text_edit_singleline(&mut self.map_layer_path);
+                    if ui.button("Add").clicked() {
+                        add_layer_requested = true;
+                    }
+                });
+
+                ui.separator();
+
+                ui.label("Airspace");
+                ui.checkbox(&mut self.config.show_airspace, "Show airspace on radar");
+                for (idx, path) in self.config.airspace_files.iter().enumerate() {
+                    ui.horizontal(|ui| {
+                        ui.label(path.display().to_string());
+                        if ui.small_button("Remove").clicked() {
+                            airspace_removed = Some(idx);
+                        }
+                    });
+                }
+                ui.horizontal(|ui| {
+                    ui.label("OpenAir / GeoJSON file:");
+                    ui.text_edit_singleline(&mut self.airspace_import_path);
+                    if ui.button("Load").clicked() {
+                        airspace_import_requested = true;
+                    }
+                });
+
+                ui.separator();
+
+                ui.label("Radar Settings");
+                ui.horizontal(|ui| {
+                    ui.label("Radius (km):");
+                    let mut radius = self.config.radar_radius_km;
+                    if ui.add(egui::DragValue::new(&mut radius).speed(0.5).clamp_range(1.0..=100.0)).changed() {
+                        self.config.set_radar_radius(radius);
+                    }
+                });
+
+                ui.horizontal(|ui| {
+                    ui.label("Refresh interval (seconds):");
+                    let mut interval = self.config.refresh_interval_seconds;
+                    if ui.add(egui::DragValue::new(&mut interval).speed(1.0).clamp_range(10..=300)).changed() {
+                        self.config.set_refresh_interval(interval);
+                    }
+                });
+
+                ui.horizontal(|ui| {
+                    egui::ComboBox::from_label("Orientation")
+                        .selected_text(self.config.orientation.name())
+                        .show_ui(ui, |ui| {
+                            let rotation = match self.config.orientation {
+                                RadarOrientation::Rotated(bearing) => bearing,
+                                _ => self.radar_view.rotation_deg(),
+                            };
+                            for orientation in [
+                                RadarOrientation::NorthUp,
+                                RadarOrientation::TrackUp,
+                                RadarOrientation::Rotated(rotation),
+                            ] {
+                                ui.selectable_value(&mut self.config.orientation, orientation, orientation.name());
+                            }
+                        });
+                    if let RadarOrientation::Rotated(bearing) = &mut self.config.orientation {
+                        ui.add(egui::DragValue::new(bearing).speed(1.0).clamp_range(0.0..=359.0).suffix("°"));
+                    }
+                });
+                if self.config.orientation == RadarOrientation::TrackUp && self.selected_aircraft.is_none() {
+                    ui.label("Select an aircraft to orient the radar along its track");
+                }
+                ui.checkbox(&mut self.config.follow_track_up, "Track up while following");
+
+                ui.checkbox(&mut self.config.conflict_detection.enabled, "Conflict detection");
+                ui.horizontal(|ui| {
+                    ui.label("Separation (nm / ft):");
+                    ui.add(egui::DragValue::new(&mut self.config.conflict_detection.lateral_nm).speed(0.1).clamp_range(0.5..=20.0));
+                    ui.add(egui::DragValue::new(&mut self.config.conflict_detection.vertical_ft).speed(100.0).clamp_range(100.0..=5000.0));
+                });
+                ui.horizontal(|ui| {
+                    ui.label("Look ahead (seconds):");
+                    ui.add(egui::DragValue::new(&mut self.config.conflict_detection.look_ahead_secs).speed(5.0).clamp_range(0.0..=1200.0));
+                });
+
+                ui.checkbox(&mut self.config.auto_refresh, "Auto-refresh");
+                ui.checkbox(&mut self.config.show_trails, "Show aircraft trails");
+                ui.horizontal(|ui| {
+                    let leaders = &mut self.config.speed_leaders;
+                    ui.checkbox(&mut leaders.enabled, "Speed leaders");
+                    ui.add(egui::DragValue::new(&mut leaders.minutes).speed(0.1).clamp_range(0.5..=10.0).suffix(" min"));
+                    ui.checkbox(&mut leaders.show_ticks, "1/2/5 min ticks");
+                    ui.checkbox(&mut leaders.use_turn_rate, "Follow turns");
+                });
+
+                ui.horizontal(|ui| {
+                    let sweep = &mut self.config.sweep;
+                    ui.checkbox(&mut sweep.enabled, "Radar sweep");
+                    ui.label("Period:");
+                    ui.add(egui::DragValue::new(&mut sweep.period_secs).speed(0.1).clamp_range(1.0..=30.0).suffix(" s"));
+                    ui.label("Persistence:");
+                    ui.add(egui::DragValue::new(&mut sweep.persistence_secs).speed(0.1).clamp_range(0.2..=30.0).suffix(" s"));
+                });
+
+                ui.horizontal(|ui| {
+                    ui.label("Radar filter:");
+                    radar_filter_changed = ui
+                        .add(egui::TextEdit::singleline(&mut self.config.radar_filter).hint_text("e.g. not on_ground"))
+                        .changed();
+                });
+                if let Some(error) = &self.radar_filter_error {
+                    ui.colored_label(egui::Color32::from_rgb(255, 90, 90), egui::RichText::new(error).monospace());
+                }
+
+                egui::ComboBox::from_label("Symbol colours")
+                    .selected_text(self.config.symbol_coloring.name())
+                    .show_ui(ui, |ui| {
+                        for coloring in [SymbolColoring::Altitude, SymbolColoring::FlightPhase] {
+                            ui.selectable_value(&mut self.config.symbol_coloring, coloring, coloring.name());
+                        }
+                    });
+
+                egui::ComboBox::from_label("Aircraft labels")
+                    .selected_text(self.config.callsign_display.name())
+                    .show_ui(ui, |ui| {
+                        for display in [
+                            CallsignDisplay::Callsign,
+                            CallsignDisplay::Airline,
+                            CallsignDisplay::Iata,
+                            CallsignDisplay::Telephony,
+                        ] {
+                            ui.selectable_value(&mut self.config.callsign_display, display, display.name());
+                        }
+                    });
+
+                ui.label("Data blocks");
+                ui.horizontal_wrapped(|ui| {
+                    for field in DataBlockField::ALL {
+                        let mut enabled = self.config.data_blocks.has(field);
+                        if ui.checkbox(&mut enabled, field.name()).changed() {
+                            self.config.data_blocks.toggle(field, enabled);
+                        }
+                    }
+                });
+                ui.horizontal(|ui| {
+                    ui.checkbox(&mut self.config.data_blocks.declutter, "Avoid overlaps");
+                    ui.label("Leader (px):");
+                    ui.add(egui::DragValue::new(&mut self.config.data_blocks.leader_px).speed(1.0).clamp_range(0.0..=80.0));
+                });
+
+                ui.separator();
+
+                ui.label("Theme");
+                egui::ComboBox::from_label("Theme")
+                    .selected_text(self.config.theme.name())
+                    .show_ui(ui, |ui| {
+                        ui.selectable_value(&mut self.config.theme, Theme::Dark, "Dark");
+                        ui.selectable_value(&mut self.config.theme, Theme::Light, "Light");
+                        ui.selectable_value(&mut self.config.theme, Theme::Auto, "Auto");
+                    });
+
                 ui.separator();
 
                 ui.horizontal(|ui| {
@@ -329,6 +2109,43 @@ impl SkyRadarApp {
                     }
                 });
             });
+
+        if import_requested {
+            self.import_airports();
+        }
+        if basemap_requested {
+            self.open_basemap();
+        }
+        if basemap_closed {
+            self.radar_view.set_basemap(None);
+            self.config.basemap_path = None;
+        }
+        if session_save_requested {
+            self.save_session();
+        }
+        if session_load_requested {
+            self.load_session();
+        }
+        if heatmap_changed {
+            self.update_heatmap();
+        }
+        if radar_filter_changed {
+            self.set_radar_filter();
+        }
+        if add_layer_requested {
+            self.add_map_layer();
+        }
+        if let Some(idx) = layer_removed {
+            self.config.map_layers.remove(idx);
+        }
+        if airspace_import_requested {
+            self.import_airspace();
+        }
+        if let Some(idx) = airspace_removed {
+            self.config.airspace_files.remove(idx);
+            let db = Self::load_airspaces(&self.config);
+            self.set_airspaces(db);
+        }
     }
 }
 
@@ -337,5 +2154,51 @@ impl eframe::App for SkyRadarApp {
         self.update(ctx);
         self.draw_main_window(ctx);
         self.draw_settings_window(ctx);
+        self.draw_passing_soon_window(ctx);
+        self.draw_geofence_window(ctx);
+        self.draw_watchlist_window(ctx);
+        self.draw_alerts_window(ctx);
+        self.draw_details_window(ctx);
+        self.draw_history_window(ctx);
+    }
+
+    // The worker only writes the outbox every few seconds; don't lose
+    // deliveries queued since its last pass
+    fn on_exit(&mut self, _gl: Option<&eframe::glow::Context>) {
+        webhook::persist(&self.outbox);
+    }
+}
+
+// The view is centred on the followed aircraft's extrapolated position, so
+// it's drawn there too rather than drifting off centre between updates
+fn followed_at_center<'a>(aircraft: &'a [Aircraft], following: Option<&str>, center: &Location) -> Cow<'a, [Aircraft]> {
+    let Some(idx) = following.and_then(|icao24| aircraft.iter().position(|a| a.icao24 == icao24 && a.has_position())) else {
+        return Cow::Borrowed(aircraft);
+    };
+    let mut moved = aircraft.to_vec();
+    moved[idx].latitude = Some(center.lat);
+    moved[idx].longitude = Some(center.lon);
+    Cow::Owned(moved)
+}
+
+fn rule_name(requested: &str, rules: &[AlertRule]) -> String {
+    let requested = requested.trim();
+    if !requested.is_empty() && !rules.iter().any(|r| r.name == requested) {
+        return requested.to_string();
     }
-} 
\ No newline at end of file
+    // Cooldowns are tracked by name, so every rule needs its own
+    let base = if requested.is_empty() { "Rule" } else { requested };
+    (1..)
+        .map(|n| format!("{} {}", base, n + rules.len()))
+        .find(|name| !rules.iter().any(|r| &r.name == name))
+        .unwrap()
+}
+
+fn fence_name(requested: &str, existing: usize) -> String {
+    let requested = requested.trim();
+    if requested.is_empty() {
+        format!("Fence {}", existing + 1)
+    } else {
+        requested.to_string()
+    }
+}
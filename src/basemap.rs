@@ -0,0 +1,354 @@
+// Offline raster basemap from an MBTiles file. Tiles are Web Mercator
+// slippy-map tiles; they are reprojected onto the radar's local projection by
+// drawing each tile as a subdivided mesh.
+
+use anyhow::{anyhow, Context, Result};
+use egui::{Color32, ColorImage, Mesh, Painter, Pos2, TextureHandle, TextureOptions};
+use rusqlite::{Connection, OpenFlags, OptionalExtension};
+use std::collections::HashMap;
+use std::f64::consts::PI;
+use std::path::Path;
+use tracing::warn;
+
+// Metres per pixel of a 256 px tile at zoom 0 on the equator
+const EQUATOR_M_PER_PX: f64 = 156_543.034;
+const MAX_MERCATOR_LAT: f64 = 85.051_128_78;
+const MESH_STEPS: usize = 4;
+const MAX_TILES_PER_VIEW: usize = 64;
+const MAX_LOADS_PER_FRAME: usize = 8;
+const CACHE_CAPACITY: usize = 256;
+// Deepest zoom accepted from a file; tile indices must fit in a u32
+pub const MAX_ZOOM: u8 = 30;
+
+pub struct MbTiles {
+    connection: Connection,
+    pub name: String,
+    pub min_zoom: u8,
+    pub max_zoom: u8,
+}
+
+impl MbTiles {
+    pub fn open(path: &Path) -> Result<Self> {
+        let connection = Connection::open_with_flags(path, OpenFlags::SQLITE_OPEN_READ_ONLY)
+            .with_context(|| format!("Failed to open {}", path.display()))?;
+        Self::from_connection(connection)
+    }
+
+    pub fn from_connection(connection: Connection) -> Result<Self> {
+        let metadata = |key: &str| -> Result<Option<String>> {
+            Ok(connection
+                .query_row("SELECT value FROM metadata WHERE name = ?1", [key], |row| row.get(0))
+                .optional()?)
+        };
+
+        let format = metadata("format")?.unwrap_or_else(|| "png".to_string());
+        if !matches!(format.as_str(), "png" | "jpg" | "jpeg" | "webp") {
+            return Err(anyhow!("Unsupported tile format {} (vector tiles are not supported)", format));
+        }
+        let name = metadata("name")?.unwrap_or_else(|| "Basemap".to_string());
+
+        // Zoom range from metadata, falling back to the tiles actually present
+        let zoom = |key: &str, aggregate: &str| -> Result<u8> {
+            if let Some(value) = metadata(key)?.and_then(|v| v.trim().parse().ok()) {
+                return Ok(value);
+            }
+            let sql = format!("SELECT {}(zoom_level) FROM tiles", aggregate);
+            let value: Option<u8> = connection.query_row(&sql, [], |row| row.get(0))?;
+            value.ok_or_else(|| anyhow!("MBTiles file has no tiles"))
+        };
+        let min_zoom = zoom("minzoom", "MIN")?;
+        let mut max_zoom = zoom("maxzoom", "MAX")?;
+        if min_zoom > MAX_ZOOM {
+            return Err(anyhow!("Unsupported zoom range {}-{}", min_zoom, max_zoom));
+        }
+        if max_zoom > MAX_ZOOM {
+            warn!("Basemap {} claims zoom {}, using levels up to {}", name, max_zoom, MAX_ZOOM);
+            max_zoom = MAX_ZOOM;
+        }
+        let min_zoom = min_zoom.min(max_zoom);
+
+        Ok(Self {
+            name,
+            min_zoom,
+            max_zoom,
+            connection,
+        })
+    }
+
+    // XYZ addressing; MBTiles stores rows in TMS order (origin bottom-left)
+    pub fn tile(&self, zoom: u8, x: u32, y: u32) -> Result<Option<Vec<u8>>> {
+        let tms_row = 1u32
+            .checked_shl(zoom.into())
+            .and_then(|n| n.checked_sub(1))
+            .and_then(|last| last.checked_sub(y))
+            .ok_or_else(|| anyhow!("Tile {}/{}/{} is out of range", zoom, x, y))?;
+        Ok(self
+            .connection
+            .query_row(
+                "SELECT tile_data FROM tiles WHERE zoom_level = ?1 AND tile_column = ?2 AND tile_row = ?3",
+                [zoom as u32, x, tms_row],
+                |row| row.get(0),
+            )
+            .optional()?)
+    }
+}
+
+// Tiles along each axis; zooms past MAX_ZOOM are rejected on open, so the
+// fallback is never reached by tiles from a file
+fn tiles_across(zoom: u8) -> u32 {
+    1u32.checked_shl(zoom.into()).unwrap_or(1 << 31)
+}
+
+pub fn lon_to_tile_x(lon: f64, zoom: u8) -> f64 {
+    (lon + 180.0) / 360.0 * tiles_across(zoom) as f64
+}
+
+pub fn lat_to_tile_y(lat: f64, zoom: u8) -> f64 {
+    let lat_rad = lat.clamp(-MAX_MERCATOR_LAT, MAX_MERCATOR_LAT).to_radians();
+    (1.0 - (lat_rad.tan() + 1.0 / lat_rad.cos()).ln() / PI) / 2.0 * tiles_across(zoom) as f64
+}
+
+pub fn tile_x_to_lon(x: f64, zoom: u8) -> f64 {
+    x / tiles_across(zoom) as f64 * 360.0 - 180.0
+}
+
+pub fn tile_y_to_lat(y: f64, zoom: u8) -> f64 {
+    let n = PI * (1.0 - 2.0 * y / tiles_across(zoom) as f64);
+    n.sinh().atan().to_degrees()
+}
+
+// Lowest zoom whose tile pixels are at least as fine as the screen's
+pub fn zoom_for_scale(pixels_per_km: f64, lat: f64, min_zoom: u8, max_zoom: u8) -> u8 {
+    let m_per_screen_px = 1000.0 / pixels_per_km.max(1e-6);
+    let m_per_tile_px_z0 = EQUATOR_M_PER_PX * lat.to_radians().cos();
+    let zoom = (m_per_tile_px_z0 / m_per_screen_px).log2().ceil();
+    (zoom.max(0.0) as u8).clamp(min_zoom, max_zoom)
+}
+
+type TileKey = (u8, u32, u32);
+
+struct CachedTile {
+    // None when the tile is missing or failed to decode, so it isn't retried
+    texture: Option<TextureHandle>,
+    last_used: u64,
+}
+
+pub struct Basemap {
+    tiles: MbTiles,
+    cache: HashMap<TileKey, CachedTile>,
+    frame: u64,
+}
+
+impl Basemap {
+    pub fn open(path: &Path) -> Result<Self> {
+        Ok(Self::new(MbTiles::open(path)?))
+    }
+
+    pub fn new(tiles: MbTiles) -> Self {
+        Self {
+            tiles,
+            cache: HashMap::new(),
+            frame: 0,
+        }
+    }
+
+    pub fn name(&self) -> &str {
+        &self.tiles.name
+    }
+
+    // `to_screen` maps (lon, lat) to the radar projection. Returns true when
+    // tiles are still loading and another frame should be requested.
+    pub fn draw(
+        &mut self,
+        painter: &Painter,
+        bounds: [(f64, f64); 2],
+        pixels_per_km: f64,
+        opacity: f32,
+        to_screen: impl Fn(f64, f64) -> Pos2,
+    ) -> bool {
+        self.frame += 1;
+        let [(min_lon, min_lat), (max_lon, max_lat)] = bounds;
+        let mid_lat = (min_lat + max_lat) / 2.0;
+
+        let mut zoom = zoom_for_scale(pixels_per_km, mid_lat, self.tiles.min_zoom, self.tiles.max_zoom);
+        let range = loop {
+            let x0 = lon_to_tile_x(min_lon, zoom).floor().max(0.0) as u32;
+            let x1 = lon_to_tile_x(max_lon, zoom).floor() as u32;
+            let y0 = lat_to_tile_y(max_lat, zoom).floor().max(0.0) as u32;
+            let y1 = lat_to_tile_y(min_lat, zoom).floor() as u32;
+            let last = tiles_across(zoom) - 1;
+            let (x1, y1) = (x1.min(last), y1.min(last));
+            let count = (x1 + 1).saturating_sub(x0) as usize * (y1 + 1).saturating_sub(y0) as usize;
+            if count <= MAX_TILES_PER_VIEW || zoom <= self.tiles.min_zoom {
+                break (x0, x1, y0, y1);
+            }
+            zoom -= 1;
+        };
+
+        let tint = Color32::WHITE.gamma_multiply(opacity.clamp(0.0, 1.0));
+        let mut loads = 0;
+        let mut pending = false;
+        for y in range.2..=range.3 {
+            for x in range.0..=range.1 {
+                let key = (zoom, x, y);
+                if !self.cache.contains_key(&key) {
+                    if loads == MAX_LOADS_PER_FRAME {
+                        pending = true;
+                        continue;
+                    }
+                    loads += 1;
+                    let texture = self.load_texture(painter.ctx(), key);
+                    self.cache.insert(key, CachedTile { texture, last_used: 0 });
+                }
+
+                let Some(cached) = self.cache.get_mut(&key) else {
+                    continue;
+                };
+                cached.last_used = self.frame;
+                if let Some(texture) = &cached.texture {
+                    painter.add(tile_mesh(texture, key, tint, &to_screen));
+                }
+            }
+        }
+
+        self.evict();
+        pending
+    }
+
+    fn load_texture(&self, ctx: &egui::Context, (zoom, x, y): TileKey) -> Option<TextureHandle> {
+        let bytes = match self.tiles.tile(zoom, x, y) {
+            Ok(bytes) => bytes?,
+            Err(e) => {
+                warn!("Failed to read tile {}/{}/{}: {}", zoom, x, y, e);
+                return None;
+            }
+        };
+        let image = match image::load_from_memory(&bytes) {
+            Ok(image) => image.to_rgba8(),
+            Err(e) => {
+                warn!("Failed to decode tile {}/{}/{}: {}", zoom, x, y, e);
+                return None;
+            }
+        };
+        let size = [image.width() as usize, image.height() as usize];
+        let color_image = ColorImage::from_rgba_unmultiplied(size, image.as_raw());
+        Some(ctx.load_texture(format!("tile-{}-{}-{}", zoom, x, y), color_image, TextureOptions::LINEAR))
+    }
+
+    // Least recently drawn tiles go first
+    fn evict(&mut self) {
+        if self.cache.len() <= CACHE_CAPACITY {
+            return;
+        }
+        let mut ages: Vec<u64> = self.cache.values().map(|t| t.last_used).collect();
+        ages.sort_unstable();
+        let cutoff = ages[self.cache.len() - CACHE_CAPACITY];
+        self.cache.retain(|_, tile| tile.last_used >= cutoff);
+    }
+}
+
+// Mercator is not linear in latitude, so each tile is split into a grid and
+// every vertex is projected individually
+fn tile_mesh(texture: &TextureHandle, (zoom, x, y): TileKey, tint: Color32, to_screen: &impl Fn(f64, f64) -> Pos2) -> Mesh {
+    let mut mesh = Mesh::with_texture(texture.id());
+    for row in 0..=MESH_STEPS {
+        for col in 0..=MESH_STEPS {
+            let u = col as f64 / MESH_STEPS as f64;
+            let v = row as f64 / MESH_STEPS as f64;
+            let lon = tile_x_to_lon(x as f64 + u, zoom);
+            let lat = tile_y_to_lat(y as f64 + v, zoom);
+            mesh.vertices.push(egui::epaint::Vertex {
+                pos: to_screen(lon, lat),
+                uv: Pos2::new(u as f32, v as f32),
+                color: tint,
+            });
+        }
+    }
+
+    let stride = (MESH_STEPS + 1) as u32;
+    for row in 0..MESH_STEPS as u32 {
+        for col in 0..MESH_STEPS as u32 {
+            let top_left = row * stride + col;
+            let bottom_left = top_left + stride;
+            mesh.add_triangle(top_left, top_left + 1, bottom_left);
+            mesh.add_triangle(top_left + 1, bottom_left + 1, bottom_left);
+        }
+    }
+    mesh
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_tile_math_round_trip() {
+        let (lat, lon) = (51.4775, -0.4614);
+        let x = lon_to_tile_x(lon, 12);
+        let y = lat_to_tile_y(lat, 12);
+        assert_eq!((x.floor(), y.floor()), (2042.0, 1362.0));
+        assert!((tile_x_to_lon(x, 12) - lon).abs() < 1e-9);
+        assert!((tile_y_to_lat(y, 12) - lat).abs() < 1e-9);
+    }
+
+    #[test]
+    fn test_zoom_for_scale() {
+        // ~100 m per screen pixel at the equator needs zoom 11 (76 m/px)
+        assert_eq!(zoom_for_scale(10.0, 0.0, 0, 18), 11);
+        assert_eq!(zoom_for_scale(10.0, 0.0, 0, 8), 8);
+        assert_eq!(zoom_for_scale(0.001, 0.0, 3, 18), 3);
+    }
+
+    #[test]
+    fn test_reads_tiles_in_tms_order() {
+        let connection = Connection::open_in_memory().unwrap();
+        connection
+            .execute_batch(
+                "CREATE TABLE metadata (name TEXT, value TEXT);
+                 CREATE TABLE tiles (zoom_level INTEGER, tile_column INTEGER, tile_row INTEGER, tile_data BLOB);
+                 INSERT INTO metadata VALUES ('name', 'Test'), ('format', 'png');
+                 INSERT INTO tiles VALUES (2, 1, 3, x'0102'), (4, 0, 0, x'03');",
+            )
+            .unwrap();
+        let tiles = MbTiles::from_connection(connection).unwrap();
+
+        assert_eq!((tiles.min_zoom, tiles.max_zoom), (2, 4));
+        // TMS row 3 at zoom 2 is XYZ row 0
+        assert_eq!(tiles.tile(2, 1, 0).unwrap(), Some(vec![1, 2]));
+        assert_eq!(tiles.tile(2, 1, 3).unwrap(), None);
+    }
+
+    #[test]
+    fn test_rejects_vector_tiles() {
+        let connection = Connection::open_in_memory().unwrap();
+        connection
+            .execute_batch(
+                "CREATE TABLE metadata (name TEXT, value TEXT);
+                 CREATE TABLE tiles (zoom_level INTEGER, tile_column INTEGER, tile_row INTEGER, tile_data BLOB);
+                 INSERT INTO metadata VALUES ('format', 'pbf');",
+            )
+            .unwrap();
+        assert!(MbTiles::from_connection(connection).is_err());
+    }
+
+    #[test]
+    fn test_limits_zoom_range() {
+        let open = |minzoom: &str, maxzoom: &str| {
+            let connection = Connection::open_in_memory().unwrap();
+            connection
+                .execute_batch(&format!(
+                    "CREATE TABLE metadata (name TEXT, value TEXT);
+                     CREATE TABLE tiles (zoom_level INTEGER, tile_column INTEGER, tile_row INTEGER, tile_data BLOB);
+                     INSERT INTO metadata VALUES ('minzoom', '{}'), ('maxzoom', '{}');",
+                    minzoom, maxzoom
+                ))
+                .unwrap();
+            MbTiles::from_connection(connection)
+        };
+
+        let tiles = open("0", "40").unwrap();
+        assert_eq!((tiles.min_zoom, tiles.max_zoom), (0, MAX_ZOOM));
+        assert!(tiles.tile(40, 0, 0).is_err());
+        assert!(open("32", "40").is_err());
+    }
+}
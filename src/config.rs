@@ -1,4 +1,16 @@
+use crate::airports::AirportCodeStyle;
+use crate::alerts::AlertRule;
+use crate::conflict::ConflictSettings;
 use crate::geo::Location;
+use crate::geofence::Geofence;
+use crate::heatmap::HeatmapSettings;
+use crate::labels::DataBlockSettings;
+use crate::map_layers::MapLayerConfig;
+use crate::prediction::SpeedLeaderSettings;
+use crate::profile_view::ProfileAxis;
+use crate::radar_view::RadarOrientation;
+use crate::sweep::SweepSettings;
+use crate::webhook::WebhookSettings;
 use anyhow::Result;
 use config::{Config, Environment, File};
 use dirs::config_dir;
@@ -16,6 +28,79 @@ pub struct AppConfig {
     pub api_credentials: Option<ApiCredentials>,
     pub window_size: Option<WindowSize>,
     pub auto_refresh: bool,
+    #[serde(default)]
+    pub callsign_display: CallsignDisplay,
+    #[serde(default = "default_true")]
+    pub show_airports: bool,
+    #[serde(default)]
+    pub airport_code_style: AirportCodeStyle,
+    #[serde(default)]
+    pub airports_dir: Option<PathBuf>,
+    #[serde(default)]
+    pub symbol_coloring: SymbolColoring,
+    #[serde(default = "default_passing_horizon")]
+    pub passing_horizon_minutes: f64,
+    #[serde(default = "default_overhead_alert")]
+    pub overhead_alert_km: f64,
+    #[serde(default)]
+    pub conflict_detection: ConflictSettings,
+    #[serde(default)]
+    pub geofences: Vec<Geofence>,
+    #[serde(default)]
+    pub airspace_files: Vec<PathBuf>,
+    #[serde(default = "default_true")]
+    pub show_airspace: bool,
+    #[serde(default)]
+    pub map_layers: Vec<MapLayerConfig>,
+    #[serde(default)]
+    pub basemap_path: Option<PathBuf>,
+    #[serde(default = "default_basemap_opacity")]
+    pub basemap_opacity: f32,
+    #[serde(default = "default_true")]
+    pub show_basemap: bool,
+    #[serde(default)]
+    pub profile_axis: ProfileAxis,
+    #[serde(default)]
+    pub data_blocks: DataBlockSettings,
+    #[serde(default)]
+    pub speed_leaders: SpeedLeaderSettings,
+    #[serde(default)]
+    pub orientation: RadarOrientation,
+    #[serde(default = "default_true")]
+    pub follow_track_up: bool,
+    #[serde(default)]
+    pub sweep: SweepSettings,
+    #[serde(default = "default_track_retention")]
+    pub track_retention_minutes: u64,
+    #[serde(default)]
+    pub heatmap: HeatmapSettings,
+    // Filter expression limiting what the radar shows; empty shows all
+    #[serde(default)]
+    pub radar_filter: String,
+    #[serde(default)]
+    pub alert_rules: Vec<AlertRule>,
+    #[serde(default)]
+    pub webhooks: Vec<WebhookSettings>,
+}
+
+fn default_passing_horizon() -> f64 {
+    15.0
+}
+
+fn default_overhead_alert() -> f64 {
+    1.5
+}
+
+fn default_basemap_opacity() -> f32 {
+    0.6
+}
+
+fn default_track_retention() -> u64 {
+    240
+}
+
+fn default_true() -> bool {
+    true
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -37,6 +122,22 @@ pub enum Theme {
     Auto,
 }
 
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Default)]
+pub enum CallsignDisplay {
+    #[default]
+    Callsign,
+    Airline,
+    Iata,
+    Telephony,
+}
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Default)]
+pub enum SymbolColoring {
+    #[default]
+    Altitude,
+    FlightPhase,
+}
+
 impl Default for AppConfig {
     fn default() -> Self {
         Self {
@@ -49,6 +150,32 @@ impl Default for AppConfig {
             api_credentials: None,
             window_size: None,
             auto_refresh: true,
+            callsign_display: CallsignDisplay::Callsign,
+            show_airports: true,
+            airport_code_style: AirportCodeStyle::Icao,
+            airports_dir: None,
+            symbol_coloring: SymbolColoring::Altitude,
+            passing_horizon_minutes: default_passing_horizon(),
+            overhead_alert_km: default_overhead_alert(),
+            conflict_detection: ConflictSettings::default(),
+            geofences: Vec::new(),
+            airspace_files: Vec::new(),
+            show_airspace: true,
+            map_layers: Vec::new(),
+            basemap_path: None,
+            basemap_opacity: default_basemap_opacity(),
+            show_basemap: true,
+            profile_axis: ProfileAxis::Distance,
+            data_blocks: DataBlockSettings::default(),
+            speed_leaders: SpeedLeaderSettings::default(),
+            orientation: RadarOrientation::NorthUp,
+            follow_track_up: true,
+            sweep: SweepSettings::default(),
+            track_retention_minutes: default_track_retention(),
+            heatmap: HeatmapSettings::default(),
+            radar_filter: String::new(),
+            alert_rules: Vec::new(),
+            webhooks: Vec::new(),
         }
     }
 }
@@ -157,6 +284,26 @@ impl Theme {
     }
 }
 
+impl CallsignDisplay {
+    pub fn name(&self) -> &'static str {
+        match self {
+            CallsignDisplay::Callsign => "Callsign",
+            CallsignDisplay::Airline => "Callsign / Airline",
+            CallsignDisplay::Iata => "IATA flight",
+            CallsignDisplay::Telephony => "Radio callsign",
+        }
+    }
+}
+
+impl SymbolColoring {
+    pub fn name(&self) -> &'static str {
+        match self {
+            SymbolColoring::Altitude => "Altitude",
+            SymbolColoring::FlightPhase => "Flight phase",
+        }
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -0,0 +1,249 @@
+use crate::aircraft::Aircraft;
+use crate::cpa::velocity_km_s;
+use crate::geo::Location;
+use geo::Point;
+use serde::{Deserialize, Serialize};
+use std::collections::{HashMap, HashSet};
+
+const KM_PER_NM: f64 = 1.852;
+const GRID_CELL_KM: f64 = 10.0;
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct ConflictSettings {
+    pub enabled: bool,
+    pub lateral_nm: f64,
+    pub vertical_ft: f64,
+    pub look_ahead_secs: f64,
+}
+
+impl Default for ConflictSettings {
+    fn default() -> Self {
+        Self {
+            enabled: true,
+            lateral_nm: 3.0,
+            vertical_ft: 1000.0,
+            look_ahead_secs: 120.0,
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct Conflict {
+    pub icao24: [String; 2],
+    pub positions: [Point<f64>; 2],
+    // Seconds until both lateral and vertical separation are lost (0 = now)
+    pub time_to_loss_secs: f64,
+    pub min_lateral_nm: f64,
+    pub vertical_ft_at_min: f64,
+}
+
+impl Conflict {
+    pub fn involves(&self, icao24: &str) -> bool {
+        self.icao24.iter().any(|id| id == icao24)
+    }
+
+    pub fn is_active(&self) -> bool {
+        self.time_to_loss_secs <= 0.0
+    }
+}
+
+struct Track<'a> {
+    aircraft: &'a Aircraft,
+    // East/north in km, velocity in km/s, altitude in ft, vertical rate in ft/s
+    x: f64,
+    y: f64,
+    vx: f64,
+    vy: f64,
+    z: f64,
+    vz: f64,
+}
+
+impl Track<'_> {
+    fn swept_bounds(&self, t: f64, margin: f64) -> (f64, f64, f64, f64) {
+        let (ex, ey) = (self.x + self.vx * t, self.y + self.vy * t);
+        (
+            self.x.min(ex) - margin,
+            self.y.min(ey) - margin,
+            self.x.max(ex) + margin,
+            self.y.max(ey) + margin,
+        )
+    }
+}
+
+// Linear-motion conflict probe. Each track's swept path over the look-ahead
+// window is bucketed into a uniform grid so only tracks sharing a cell are
+// tested pairwise.
+pub fn detect_conflicts(aircraft: &[Aircraft], settings: &ConflictSettings) -> Vec<Conflict> {
+    let Some(reference) = aircraft.iter().find_map(|a| a.position()).map(Location::from_point) else {
+        return Vec::new();
+    };
+
+    let tracks: Vec<Track> = aircraft
+        .iter()
+        .filter(|a| a.on_ground != Some(true))
+        .filter_map(|a| {
+            let (x, y) = reference.offset_km_to(&Location::from_point(a.position()?));
+            let (vx, vy) = velocity_km_s(a).unwrap_or((0.0, 0.0));
+            Some(Track {
+                aircraft: a,
+                x,
+                y,
+                vx,
+                vy,
+                z: a.altitude?,
+                vz: a.vertical_rate.unwrap_or(0.0) / 60.0,
+            })
+        })
+        .collect();
+
+    let lateral_km = settings.lateral_nm * KM_PER_NM;
+    let horizon = settings.look_ahead_secs.max(0.0);
+
+    let mut grid: HashMap<(i64, i64), Vec<usize>> = HashMap::new();
+    for (idx, track) in tracks.iter().enumerate() {
+        let (min_x, min_y, max_x, max_y) = track.swept_bounds(horizon, lateral_km / 2.0);
+        for cx in cell_index(min_x)..=cell_index(max_x) {
+            for cy in cell_index(min_y)..=cell_index(max_y) {
+                grid.entry((cx, cy)).or_default().push(idx);
+            }
+        }
+    }
+
+    let mut tested = HashSet::new();
+    let mut conflicts = Vec::new();
+    for members in grid.values() {
+        for (i, &a) in members.iter().enumerate() {
+            for &b in &members[i + 1..] {
+                let pair = (a.min(b), a.max(b));
+                if !tested.insert(pair) {
+                    continue;
+                }
+                if let Some(conflict) = probe_pair(&tracks[pair.0], &tracks[pair.1], lateral_km, settings.vertical_ft, horizon) {
+                    conflicts.push(conflict);
+                }
+            }
+        }
+    }
+
+    conflicts.sort_by(|a, b| a.time_to_loss_secs.total_cmp(&b.time_to_loss_secs));
+    conflicts
+}
+
+fn cell_index(km: f64) -> i64 {
+    (km / GRID_CELL_KM).floor() as i64
+}
+
+fn probe_pair(a: &Track, b: &Track, lateral_km: f64, vertical_ft: f64, horizon: f64) -> Option<Conflict> {
+    let (dx, dy) = (b.x - a.x, b.y - a.y);
+    let (dvx, dvy) = (b.vx - a.vx, b.vy - a.vy);
+    let (dz, dvz) = (b.z - a.z, b.vz - a.vz);
+
+    let lateral = quadratic_window(dvx * dvx + dvy * dvy, 2.0 * (dx * dvx + dy * dvy), dx * dx + dy * dy - lateral_km * lateral_km, horizon)?;
+    let vertical = linear_window(dz, dvz, vertical_ft, horizon)?;
+
+    let start = lateral.0.max(vertical.0);
+    let end = lateral.1.min(vertical.1);
+    if start > end {
+        return None;
+    }
+
+    // Closest lateral point inside the loss-of-separation window
+    let speed_sq = dvx * dvx + dvy * dvy;
+    let t_min = if speed_sq > f64::EPSILON {
+        (-(dx * dvx + dy * dvy) / speed_sq).clamp(start, end)
+    } else {
+        start
+    };
+    let (mx, my) = (dx + dvx * t_min, dy + dvy * t_min);
+
+    Some(Conflict {
+        icao24: [a.aircraft.icao24.clone(), b.aircraft.icao24.clone()],
+        positions: [a.aircraft.position()?, b.aircraft.position()?],
+        time_to_loss_secs: start,
+        min_lateral_nm: (mx * mx + my * my).sqrt() / KM_PER_NM,
+        vertical_ft_at_min: (dz + dvz * t_min).abs(),
+    })
+}
+
+// Interval within [0, horizon] where a*t^2 + b*t + c <= 0
+fn quadratic_window(a: f64, b: f64, c: f64, horizon: f64) -> Option<(f64, f64)> {
+    if a <= f64::EPSILON {
+        return if c <= 0.0 { Some((0.0, horizon)) } else { None };
+    }
+
+    let discriminant = b * b - 4.0 * a * c;
+    if discriminant < 0.0 {
+        return None;
+    }
+
+    let root = discriminant.sqrt();
+    let t0 = ((-b - root) / (2.0 * a)).max(0.0);
+    let t1 = ((-b + root) / (2.0 * a)).min(horizon);
+    if t0 <= t1 { Some((t0, t1)) } else { None }
+}
+
+// Interval within [0, horizon] where |d + v*t| <= limit
+fn linear_window(d: f64, v: f64, limit: f64, horizon: f64) -> Option<(f64, f64)> {
+    if v.abs() <= f64::EPSILON {
+        return if d.abs() <= limit { Some((0.0, horizon)) } else { None };
+    }
+
+    let (ta, tb) = ((-limit - d) / v, (limit - d) / v);
+    let t0 = ta.min(tb).max(0.0);
+    let t1 = ta.max(tb).min(horizon);
+    if t0 <= t1 { Some((t0, t1)) } else { None }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn aircraft(icao24: &str, home: &Location, east_km: f64, altitude: f64, track: f64) -> Aircraft {
+        let position = home.offset_by_km(east_km, 0.0);
+        let mut aircraft = Aircraft::new(icao24.to_string());
+        aircraft.latitude = Some(position.lat);
+        aircraft.longitude = Some(position.lon);
+        aircraft.altitude = Some(altitude);
+        aircraft.velocity = Some(720.0); // 0.2 km/s
+        aircraft.true_track = Some(track);
+        aircraft
+    }
+
+    #[test]
+    fn test_head_on_conflict() {
+        let home = Location::london();
+        let traffic = vec![
+            aircraft("aaaaaa", &home, -20.0, 10000.0, 90.0),
+            aircraft("bbbbbb", &home, 20.0, 10500.0, 270.0),
+        ];
+        let conflicts = detect_conflicts(&traffic, &ConflictSettings::default());
+
+        assert_eq!(conflicts.len(), 1);
+        let conflict = &conflicts[0];
+        // 40 km apart closing at 0.4 km/s, separation lost at 3 nm
+        let expected = (40.0 - 3.0 * KM_PER_NM) / 0.4;
+        assert!((conflict.time_to_loss_secs - expected).abs() < 0.5);
+        assert!(conflict.min_lateral_nm < 0.01);
+        assert!(conflict.involves("aaaaaa") && conflict.involves("bbbbbb"));
+    }
+
+    #[test]
+    fn test_vertical_separation_prevents_conflict() {
+        let home = Location::london();
+        let traffic = vec![
+            aircraft("aaaaaa", &home, -20.0, 10000.0, 90.0),
+            aircraft("bbbbbb", &home, 20.0, 12000.0, 270.0),
+        ];
+        assert!(detect_conflicts(&traffic, &ConflictSettings::default()).is_empty());
+    }
+
+    #[test]
+    fn test_outside_look_ahead() {
+        let home = Location::london();
+        let traffic = vec![
+            aircraft("aaaaaa", &home, -100.0, 10000.0, 90.0),
+            aircraft("bbbbbb", &home, 100.0, 10000.0, 270.0),
+        ];
+        assert!(detect_conflicts(&traffic, &ConflictSettings::default()).is_empty());
+    }
+}
@@ -0,0 +1,175 @@
+use crate::aircraft::Aircraft;
+use crate::geo::Location;
+use chrono::{DateTime, Duration, Utc};
+
+const FEET_TO_KM: f64 = 0.0003048;
+
+#[derive(Debug, Clone)]
+pub struct ClosestApproach {
+    pub icao24: String,
+    pub min_distance_km: f64,
+    pub time_to_cpa_secs: f64,
+    pub eta: DateTime<Utc>,
+    pub cpa_location: Location,
+    pub bearing_deg: f64,
+    pub elevation_deg: Option<f64>,
+}
+
+impl ClosestApproach {
+    pub fn is_overhead(&self, max_distance_km: f64) -> bool {
+        self.min_distance_km <= max_distance_km
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum PassingSort {
+    #[default]
+    Eta,
+    Distance,
+    Elevation,
+}
+
+impl PassingSort {
+    pub fn name(&self) -> &'static str {
+        match self {
+            PassingSort::Eta => "ETA",
+            PassingSort::Distance => "Distance",
+            PassingSort::Elevation => "Elevation",
+        }
+    }
+
+    pub fn sort(&self, approaches: &mut [ClosestApproach]) {
+        match self {
+            PassingSort::Eta => approaches.sort_by(|a, b| a.time_to_cpa_secs.total_cmp(&b.time_to_cpa_secs)),
+            PassingSort::Distance => approaches.sort_by(|a, b| a.min_distance_km.total_cmp(&b.min_distance_km)),
+            PassingSort::Elevation => approaches.sort_by(|a, b| {
+                let a = a.elevation_deg.unwrap_or(f64::NEG_INFINITY);
+                let b = b.elevation_deg.unwrap_or(f64::NEG_INFINITY);
+                b.total_cmp(&a)
+            }),
+        }
+    }
+}
+
+// Ground velocity as (east, north) in km/s; velocity is km/h
+pub fn velocity_km_s(aircraft: &Aircraft) -> Option<(f64, f64)> {
+    let speed = aircraft.velocity? / 3600.0;
+    let track = aircraft.true_track?.to_radians();
+    Some((speed * track.sin(), speed * track.cos()))
+}
+
+// Straight-line extrapolation along the current track, measured from `now`
+// rather than from the report, which may be some seconds old. Aircraft
+// moving away have their closest approach now (time zero).
+pub fn closest_approach(aircraft: &Aircraft, home: &Location, now: DateTime<Utc>) -> Option<ClosestApproach> {
+    let position = Location::from_point(aircraft.position()?);
+    let (vx, vy) = velocity_km_s(aircraft)?;
+    let age = aircraft
+        .time_position
+        .map_or(0.0, |reported| ((now - reported).num_milliseconds() as f64 / 1000.0).max(0.0));
+    let (px, py) = home.offset_km_to(&position);
+    let (px, py) = (px + vx * age, py + vy * age);
+
+    let speed_sq = vx * vx + vy * vy;
+    let t = if speed_sq > f64::EPSILON {
+        (-(px * vx + py * vy) / speed_sq).max(0.0)
+    } else {
+        0.0
+    };
+
+    let cx = px + vx * t;
+    let cy = py + vy * t;
+    let min_distance_km = (cx * cx + cy * cy).sqrt();
+    let cpa_location = home.offset_by_km(cx, cy);
+
+    let elevation_deg = aircraft
+        .altitude
+        .map(|alt| (alt * FEET_TO_KM).atan2(min_distance_km).to_degrees());
+
+    Some(ClosestApproach {
+        icao24: aircraft.icao24.clone(),
+        min_distance_km,
+        time_to_cpa_secs: t,
+        eta: now + Duration::milliseconds((t * 1000.0) as i64),
+        bearing_deg: home.bearing_to(&cpa_location),
+        cpa_location,
+        elevation_deg,
+    })
+}
+
+// Aircraft whose closest approach is within `max_distance_km` and happens
+// no later than `horizon_secs` from now
+pub fn passing_soon(aircraft: &[Aircraft], home: &Location, max_distance_km: f64, horizon_secs: f64) -> Vec<ClosestApproach> {
+    let now = Utc::now();
+    let mut approaches: Vec<ClosestApproach> = aircraft
+        .iter()
+        .filter_map(|a| closest_approach(a, home, now))
+        .filter(|cpa| cpa.is_overhead(max_distance_km))
+        .filter(|cpa| (cpa.eta - now).num_milliseconds() as f64 / 1000.0 <= horizon_secs)
+        .collect();
+
+    PassingSort::Eta.sort(&mut approaches);
+    approaches
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn aircraft_at(home: &Location, east_km: f64, north_km: f64, track: f64) -> Aircraft {
+        let position = home.offset_by_km(east_km, north_km);
+        let mut aircraft = Aircraft::new("a12345".to_string());
+        aircraft.latitude = Some(position.lat);
+        aircraft.longitude = Some(position.lon);
+        aircraft.altitude = Some(3280.84); // 1 km
+        aircraft.velocity = Some(360.0); // 0.1 km/s
+        aircraft.true_track = Some(track);
+        aircraft.time_position = Some(Utc::now());
+        aircraft
+    }
+
+    #[test]
+    fn test_head_on_passes_overhead() {
+        let home = Location::london();
+        let cpa = closest_approach(&aircraft_at(&home, -10.0, 0.0, 90.0), &home, Utc::now()).unwrap();
+
+        assert!(cpa.min_distance_km < 0.01);
+        assert!((cpa.time_to_cpa_secs - 100.0).abs() < 0.5);
+        assert!(cpa.elevation_deg.unwrap() > 89.0);
+    }
+
+    #[test]
+    fn test_offset_track() {
+        let home = Location::london();
+        // Flying north, 3 km east of home
+        let cpa = closest_approach(&aircraft_at(&home, 3.0, -4.0, 0.0), &home, Utc::now()).unwrap();
+
+        assert!((cpa.min_distance_km - 3.0).abs() < 0.01);
+        assert!((cpa.time_to_cpa_secs - 40.0).abs() < 0.5);
+        assert!((cpa.bearing_deg - 90.0).abs() < 1.0);
+        assert!((cpa.elevation_deg.unwrap() - (1.0f64 / 3.0).atan().to_degrees()).abs() < 0.1);
+    }
+
+    #[test]
+    fn test_receding_aircraft() {
+        let home = Location::london();
+        let cpa = closest_approach(&aircraft_at(&home, 5.0, 0.0, 90.0), &home, Utc::now()).unwrap();
+
+        assert_eq!(cpa.time_to_cpa_secs, 0.0);
+        assert!((cpa.min_distance_km - 5.0).abs() < 0.01);
+        assert!(passing_soon(&[aircraft_at(&home, 5.0, 0.0, 90.0)], &home, 2.0, 600.0).is_empty());
+    }
+
+    #[test]
+    fn test_stale_report_counts_from_now() {
+        let home = Location::london();
+        let mut aircraft = aircraft_at(&home, -10.0, 0.0, 90.0);
+        let now = Utc::now();
+        aircraft.time_position = Some(now - Duration::seconds(30));
+        let cpa = closest_approach(&aircraft, &home, now).unwrap();
+
+        // 3 km of the 10 were flown since the report
+        assert!((cpa.time_to_cpa_secs - 70.0).abs() < 0.5);
+        assert!((cpa.eta - now - Duration::seconds(70)).num_milliseconds().abs() < 500);
+    }
+}
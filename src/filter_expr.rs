@@ -0,0 +1,715 @@
+use crate::aircraft::Aircraft;
+use crate::flight_phase::PhaseTracker;
+use crate::geo::Location;
+use chrono::{DateTime, Utc};
+use regex::{Regex, RegexBuilder};
+use std::fmt;
+
+// Small boolean language over aircraft fields, e.g.
+//   altitude < 10000 and callsign ~ "^UAL" and distance < 20km
+// Comparisons against a missing value (no altitude, no squawk...) are false,
+// so `not` is the way to ask for aircraft lacking a field.
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Dimension {
+    Distance,
+    Height,
+    Speed,
+    VerticalSpeed,
+    Duration,
+    Angle,
+}
+
+impl Dimension {
+    // Accepted unit suffixes and their factor to the field's own unit
+    fn units(&self) -> &'static [(&'static str, f64)] {
+        match self {
+            Dimension::Distance => &[("km", 1.0), ("nm", 1.852), ("mi", 1.609_344), ("m", 0.001)],
+            Dimension::Height => &[("ft", 1.0), ("m", 3.280_84)],
+            Dimension::Speed => &[("kmh", 1.0), ("kt", 1.852), ("mph", 1.609_344)],
+            Dimension::VerticalSpeed => &[("fpm", 1.0)],
+            Dimension::Duration => &[("s", 1.0), ("min", 60.0), ("h", 3600.0)],
+            Dimension::Angle => &[("deg", 1.0)],
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum FieldType {
+    Number(Dimension),
+    Text,
+    Bool,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Field {
+    Altitude,
+    GeoAltitude,
+    Speed,
+    Track,
+    VerticalRate,
+    Distance,
+    Bearing,
+    Age,
+    Callsign,
+    Icao24,
+    Squawk,
+    Country,
+    Airline,
+    Category,
+    Phase,
+    OnGround,
+    Military,
+    Emergency,
+}
+
+// Names and aliases accepted in expressions
+const FIELDS: [(&str, Field); 25] = [
+    ("altitude", Field::Altitude),
+    ("alt", Field::Altitude),
+    ("geo_altitude", Field::GeoAltitude),
+    ("speed", Field::Speed),
+    ("gs", Field::Speed),
+    ("track", Field::Track),
+    ("heading", Field::Track),
+    ("vertical_rate", Field::VerticalRate),
+    ("vs", Field::VerticalRate),
+    ("distance", Field::Distance),
+    ("dist", Field::Distance),
+    ("bearing", Field::Bearing),
+    ("age", Field::Age),
+    ("callsign", Field::Callsign),
+    ("icao24", Field::Icao24),
+    ("hex", Field::Icao24),
+    ("squawk", Field::Squawk),
+    ("country", Field::Country),
+    ("airline", Field::Airline),
+    ("category", Field::Category),
+    ("type", Field::Category),
+    ("phase", Field::Phase),
+    ("on_ground", Field::OnGround),
+    ("military", Field::Military),
+    ("emergency", Field::Emergency),
+];
+
+impl Field {
+    fn lookup(name: &str) -> Option<Field> {
+        let name = name.to_ascii_lowercase();
+        FIELDS.iter().find(|(n, _)| *n == name).map(|(_, f)| *f)
+    }
+
+    pub fn name(&self) -> &'static str {
+        FIELDS.iter().find(|(_, f)| f == self).map(|(n, _)| *n).unwrap_or("?")
+    }
+
+    fn field_type(&self) -> FieldType {
+        match self {
+            Field::Altitude | Field::GeoAltitude => FieldType::Number(Dimension::Height),
+            Field::Speed => FieldType::Number(Dimension::Speed),
+            Field::Track | Field::Bearing => FieldType::Number(Dimension::Angle),
+            Field::VerticalRate => FieldType::Number(Dimension::VerticalSpeed),
+            Field::Distance => FieldType::Number(Dimension::Distance),
+            Field::Age => FieldType::Number(Dimension::Duration),
+            Field::Callsign
+            | Field::Icao24
+            | Field::Squawk
+            | Field::Country
+            | Field::Airline
+            | Field::Category
+            | Field::Phase => FieldType::Text,
+            Field::OnGround | Field::Military | Field::Emergency => FieldType::Bool,
+        }
+    }
+
+    fn number(&self, aircraft: &Aircraft, context: &FilterContext) -> Option<f64> {
+        let position = || aircraft.position().map(Location::from_point);
+        match self {
+            Field::Altitude => aircraft.altitude,
+            Field::GeoAltitude => aircraft.geo_altitude,
+            Field::Speed => aircraft.velocity,
+            Field::Track => aircraft.true_track,
+            Field::VerticalRate => aircraft.vertical_rate,
+            Field::Distance => position().map(|p| context.home.distance_to(&p)),
+            Field::Bearing => position().map(|p| context.home.bearing_to(&p)),
+            Field::Age => aircraft
+                .time_position
+                .map(|t| (context.now - t).num_milliseconds() as f64 / 1000.0),
+            _ => None,
+        }
+    }
+
+    fn text(&self, aircraft: &Aircraft, context: &FilterContext) -> Option<String> {
+        match self {
+            Field::Callsign => aircraft.callsign.as_ref().map(|c| c.trim().to_string()),
+            Field::Icao24 => Some(aircraft.icao24.clone()),
+            Field::Squawk => aircraft.squawk.clone(),
+            Field::Country => aircraft.country().map(str::to_string),
+            Field::Airline => aircraft.airline().map(|a| a.name.to_string()),
+            Field::Category => aircraft.category_code().map(str::to_string),
+            Field::Phase => context.phases.map(|p| p.phase(&aircraft.icao24).name().to_string()),
+            _ => None,
+        }
+    }
+
+    fn flag(&self, aircraft: &Aircraft) -> Option<bool> {
+        match self {
+            Field::OnGround => aircraft.on_ground,
+            Field::Military => Some(aircraft.is_military()),
+            Field::Emergency => Some(
+                aircraft
+                    .squawk
+                    .as_deref()
+                    .is_some_and(|s| matches!(s.trim(), "7500" | "7600" | "7700")),
+            ),
+            _ => None,
+        }
+    }
+}
+
+// What an expression can see besides the aircraft itself
+pub struct FilterContext<'a> {
+    pub home: &'a Location,
+    pub now: DateTime<Utc>,
+    // Without a tracker `phase` comparisons never match
+    pub phases: Option<&'a PhaseTracker>,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct FilterError {
+    pub message: String,
+    // Byte range in the source the error refers to
+    pub start: usize,
+    pub end: usize,
+}
+
+impl FilterError {
+    fn new(message: impl Into<String>, start: usize, end: usize) -> Self {
+        Self {
+            message: message.into(),
+            start,
+            end,
+        }
+    }
+
+    // The source with a caret line under the offending part
+    pub fn describe(&self, source: &str) -> String {
+        let column = source[..self.start.min(source.len())].chars().count();
+        let width = source[self.start.min(source.len())..self.end.min(source.len())].chars().count().max(1);
+        format!("{}\n{}{}\n{}", source, " ".repeat(column), "^".repeat(width), self.message)
+    }
+}
+
+impl fmt::Display for FilterError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{} (at column {})", self.message, self.start + 1)
+    }
+}
+
+impl std::error::Error for FilterError {}
+
+#[derive(Debug, Clone, PartialEq)]
+enum Token {
+    Ident(String),
+    Number(f64, Option<String>),
+    Text(String),
+    Op(CompareOp),
+    And,
+    Or,
+    Not,
+    Open,
+    Close,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum CompareOp {
+    Lt,
+    Le,
+    Gt,
+    Ge,
+    Eq,
+    Ne,
+    Match,
+    NotMatch,
+}
+
+impl CompareOp {
+    fn symbol(&self) -> &'static str {
+        match self {
+            CompareOp::Lt => "<",
+            CompareOp::Le => "<=",
+            CompareOp::Gt => ">",
+            CompareOp::Ge => ">=",
+            CompareOp::Eq => "==",
+            CompareOp::Ne => "!=",
+            CompareOp::Match => "~",
+            CompareOp::NotMatch => "!~",
+        }
+    }
+
+    fn compare<T: PartialOrd>(&self, a: T, b: T) -> bool {
+        match self {
+            CompareOp::Lt => a < b,
+            CompareOp::Le => a <= b,
+            CompareOp::Gt => a > b,
+            CompareOp::Ge => a >= b,
+            CompareOp::Eq => a == b,
+            CompareOp::Ne => a != b,
+            CompareOp::Match | CompareOp::NotMatch => false,
+        }
+    }
+}
+
+struct Spanned {
+    token: Token,
+    start: usize,
+    end: usize,
+}
+
+fn tokenize(source: &str) -> Result<Vec<Spanned>, FilterError> {
+    let mut tokens = Vec::new();
+    let chars: Vec<(usize, char)> = source.char_indices().collect();
+    let offset = |i: usize| chars.get(i).map(|(o, _)| *o).unwrap_or(source.len());
+    let mut i = 0;
+
+    while i < chars.len() {
+        let (start, c) = chars[i];
+        if c.is_whitespace() {
+            i += 1;
+            continue;
+        }
+        let next = chars.get(i + 1).map(|(_, c)| *c);
+
+        let (token, length) = match (c, next) {
+            ('(', _) => (Token::Open, 1),
+            (')', _) => (Token::Close, 1),
+            ('<', Some('=')) => (Token::Op(CompareOp::Le), 2),
+            ('<', _) => (Token::Op(CompareOp::Lt), 1),
+            ('>', Some('=')) => (Token::Op(CompareOp::Ge), 2),
+            ('>', _) => (Token::Op(CompareOp::Gt), 1),
+            ('=', Some('=')) => (Token::Op(CompareOp::Eq), 2),
+            ('=', _) => (Token::Op(CompareOp::Eq), 1),
+            ('!', Some('=')) => (Token::Op(CompareOp::Ne), 2),
+            ('!', Some('~')) => (Token::Op(CompareOp::NotMatch), 2),
+            ('!', _) => (Token::Not, 1),
+            ('~', _) => (Token::Op(CompareOp::Match), 1),
+            ('&', Some('&')) => (Token::And, 2),
+            ('|', Some('|')) => (Token::Or, 2),
+            ('"', _) | ('\'', _) => {
+                let mut text = String::new();
+                let mut j = i + 1;
+                loop {
+                    match chars.get(j) {
+                        None => return Err(FilterError::new("unterminated string", start, source.len())),
+                        Some((_, '\\')) if chars.get(j + 1).is_some() => {
+                            // Keep the backslash so regex escapes survive
+                            let escaped = chars[j + 1].1;
+                            if escaped != c {
+                                text.push('\\');
+                            }
+                            text.push(escaped);
+                            j += 2;
+                        }
+                        Some((_, q)) if *q == c => break,
+                        Some((_, other)) => {
+                            text.push(*other);
+                            j += 1;
+                        }
+                    }
+                }
+                (Token::Text(text), j + 1 - i)
+            }
+            (c, _) if c.is_ascii_digit() || (c == '-' || c == '.') && next.is_some_and(|n| n.is_ascii_digit()) => {
+                let mut j = i + 1;
+                while chars.get(j).is_some_and(|(_, c)| c.is_ascii_digit() || *c == '.') {
+                    j += 1;
+                }
+                let digits = &source[start..offset(j)];
+                let value = digits
+                    .parse::<f64>()
+                    .map_err(|_| FilterError::new(format!("invalid number `{}`", digits), start, offset(j)))?;
+                let unit_start = j;
+                while chars.get(j).is_some_and(|(_, c)| c.is_ascii_alphabetic()) {
+                    j += 1;
+                }
+                let unit = (j > unit_start).then(|| source[offset(unit_start)..offset(j)].to_ascii_lowercase());
+                (Token::Number(value, unit), j - i)
+            }
+            (c, _) if c.is_alphabetic() || c == '_' => {
+                let mut j = i + 1;
+                while chars.get(j).is_some_and(|(_, c)| c.is_alphanumeric() || *c == '_') {
+                    j += 1;
+                }
+                let word = &source[start..offset(j)];
+                let token = match word.to_ascii_lowercase().as_str() {
+                    "and" => Token::And,
+                    "or" => Token::Or,
+                    "not" => Token::Not,
+                    _ => Token::Ident(word.to_string()),
+                };
+                (token, j - i)
+            }
+            _ => return Err(FilterError::new(format!("unexpected character `{}`", c), start, offset(i + 1))),
+        };
+
+        tokens.push(Spanned {
+            token,
+            start,
+            end: offset(i + length),
+        });
+        i += length;
+    }
+    Ok(tokens)
+}
+
+#[derive(Debug, Clone)]
+enum Expr {
+    And(Box<Expr>, Box<Expr>),
+    Or(Box<Expr>, Box<Expr>),
+    Not(Box<Expr>),
+    Flag(Field),
+    Number(Field, CompareOp, f64),
+    Text(Field, CompareOp, String),
+    Pattern(Field, bool, Regex),
+}
+
+struct Parser<'a> {
+    source: &'a str,
+    tokens: Vec<Spanned>,
+    position: usize,
+}
+
+impl Parser<'_> {
+    fn peek(&self) -> Option<&Token> {
+        self.tokens.get(self.position).map(|t| &t.token)
+    }
+
+    fn advance(&mut self) -> Option<&Spanned> {
+        let token = self.tokens.get(self.position);
+        self.position += 1;
+        token
+    }
+
+    fn end_error(&self, message: &str) -> FilterError {
+        FilterError::new(message, self.source.len(), self.source.len())
+    }
+
+    fn or(&mut self) -> Result<Expr, FilterError> {
+        let mut expr = self.and()?;
+        while self.peek() == Some(&Token::Or) {
+            self.position += 1;
+            expr = Expr::Or(Box::new(expr), Box::new(self.and()?));
+        }
+        Ok(expr)
+    }
+
+    fn and(&mut self) -> Result<Expr, FilterError> {
+        let mut expr = self.unary()?;
+        while self.peek() == Some(&Token::And) {
+            self.position += 1;
+            expr = Expr::And(Box::new(expr), Box::new(self.unary()?));
+        }
+        Ok(expr)
+    }
+
+    fn unary(&mut self) -> Result<Expr, FilterError> {
+        if self.peek() == Some(&Token::Not) {
+            self.position += 1;
+            return Ok(Expr::Not(Box::new(self.unary()?)));
+        }
+        self.primary()
+    }
+
+    fn primary(&mut self) -> Result<Expr, FilterError> {
+        let source = self.source;
+        let Some(spanned) = self.advance() else {
+            return Err(self.end_error("expected a field name or `(`"));
+        };
+        let (start, end) = (spanned.start, spanned.end);
+        match spanned.token.clone() {
+            Token::Open => {
+                let expr = self.or()?;
+                match self.advance() {
+                    Some(Spanned { token: Token::Close, .. }) => Ok(expr),
+                    Some(other) => Err(FilterError::new("expected `)`", other.start, other.end)),
+                    None => Err(FilterError::new("missing `)` for this `(`", start, end)),
+                }
+            }
+            Token::Ident(name) => {
+                let field = Field::lookup(&name).ok_or_else(|| unknown_field(&name, start, end))?;
+                self.comparison(field, start, end)
+            }
+            _ => Err(FilterError::new(
+                format!("expected a field name, found `{}`", &source[start..end]),
+                start,
+                end,
+            )),
+        }
+    }
+
+    fn comparison(&mut self, field: Field, field_start: usize, field_end: usize) -> Result<Expr, FilterError> {
+        let op = match self.peek() {
+            Some(Token::Op(op)) => *op,
+            _ if field.field_type() == FieldType::Bool => return Ok(Expr::Flag(field)),
+            _ => {
+                return Err(FilterError::new(
+                    format!("`{}` needs a comparison, e.g. `{}`", field.name(), example(field)),
+                    field_start,
+                    field_end,
+                ))
+            }
+        };
+        let op_span = {
+            let op_token = &self.tokens[self.position];
+            (op_token.start, op_token.end)
+        };
+        self.position += 1;
+
+        let source = self.source;
+        let Some(value) = self.advance() else {
+            return Err(self.end_error(&format!("expected a value after `{}`", op.symbol())));
+        };
+        let (start, end) = (value.start, value.end);
+        let value_text = &source[start..end];
+
+        match (field.field_type(), &value.token) {
+            (FieldType::Number(dimension), Token::Number(number, unit)) => {
+                if matches!(op, CompareOp::Match | CompareOp::NotMatch) {
+                    return Err(FilterError::new(
+                        format!("`{}` only works on text fields", op.symbol()),
+                        op_span.0,
+                        op_span.1,
+                    ));
+                }
+                let factor = match unit {
+                    None => 1.0,
+                    Some(unit) => dimension.units().iter().find(|(u, _)| *u == unit.as_str()).map(|(_, f)| *f).ok_or_else(|| {
+                        let expected: Vec<&str> = dimension.units().iter().map(|(u, _)| *u).collect();
+                        FilterError::new(
+                            format!("unit `{}` doesn't apply to `{}` (use {})", unit, field.name(), expected.join(", ")),
+                            start,
+                            end,
+                        )
+                    })?,
+                };
+                Ok(Expr::Number(field, op, number * factor))
+            }
+            (FieldType::Text, Token::Text(text)) | (FieldType::Text, Token::Ident(text)) => match op {
+                CompareOp::Match | CompareOp::NotMatch => {
+                    let regex = RegexBuilder::new(text)
+                        .case_insensitive(true)
+                        .build()
+                        .map_err(|e| FilterError::new(format!("invalid pattern: {}", e), start, end))?;
+                    Ok(Expr::Pattern(field, op == CompareOp::Match, regex))
+                }
+                CompareOp::Eq | CompareOp::Ne => Ok(Expr::Text(field, op, text.clone())),
+                _ => Err(FilterError::new(
+                    format!("`{}` can't be used with text field `{}`, use ==, != or ~", op.symbol(), field.name()),
+                    op_span.0,
+                    op_span.1,
+                )),
+            },
+            (FieldType::Bool, Token::Ident(word)) if matches!(op, CompareOp::Eq | CompareOp::Ne) => {
+                let wanted = match word.to_ascii_lowercase().as_str() {
+                    "true" | "yes" => true,
+                    "false" | "no" => false,
+                    _ => return Err(FilterError::new("expected true or false", start, end)),
+                };
+                let expr = Expr::Flag(field);
+                Ok(if wanted == (op == CompareOp::Eq) { expr } else { Expr::Not(Box::new(expr)) })
+            }
+            (field_type, _) => {
+                let expected = match field_type {
+                    FieldType::Number(_) => "a number",
+                    FieldType::Text => "quoted text",
+                    FieldType::Bool => "true or false",
+                };
+                Err(FilterError::new(
+                    format!("`{}` compares with {}, found `{}`", field.name(), expected, value_text),
+                    start,
+                    end,
+                ))
+            }
+        }
+    }
+}
+
+fn example(field: Field) -> &'static str {
+    match field.field_type() {
+        FieldType::Number(Dimension::Distance) => "distance < 20km",
+        FieldType::Number(Dimension::Height) => "altitude < 10000",
+        FieldType::Number(Dimension::Speed) => "speed > 250kt",
+        FieldType::Number(_) => "vs > 500",
+        FieldType::Text => "callsign ~ \"^UAL\"",
+        FieldType::Bool => "military",
+    }
+}
+
+fn unknown_field(name: &str, start: usize, end: usize) -> FilterError {
+    let suggestion = FIELDS
+        .iter()
+        .map(|(candidate, _)| (*candidate, edit_distance(&name.to_ascii_lowercase(), candidate)))
+        .filter(|(_, distance)| *distance <= 2)
+        .min_by_key(|(_, distance)| *distance);
+    let message = match suggestion {
+        Some((candidate, _)) => format!("unknown field `{}`, did you mean `{}`?", name, candidate),
+        None => format!("unknown field `{}`", name),
+    };
+    FilterError::new(message, start, end)
+}
+
+fn edit_distance(a: &str, b: &str) -> usize {
+    let b: Vec<char> = b.chars().collect();
+    let mut row: Vec<usize> = (0..=b.len()).collect();
+    for (i, ca) in a.chars().enumerate() {
+        let mut previous = row[0];
+        row[0] = i + 1;
+        for (j, cb) in b.iter().enumerate() {
+            let substitution = previous + usize::from(ca != *cb);
+            previous = row[j + 1];
+            row[j + 1] = substitution.min(row[j] + 1).min(previous + 1);
+        }
+    }
+    row[b.len()]
+}
+
+// A parsed and type-checked expression, ready to evaluate
+#[derive(Debug, Clone)]
+pub struct Filter {
+    source: String,
+    expr: Expr,
+}
+
+impl Filter {
+    pub fn parse(source: &str) -> Result<Self, FilterError> {
+        let tokens = tokenize(source)?;
+        if tokens.is_empty() {
+            return Err(FilterError::new("empty expression", 0, 0));
+        }
+        let mut parser = Parser {
+            source,
+            tokens,
+            position: 0,
+        };
+        let expr = parser.or()?;
+        if let Some(extra) = parser.tokens.get(parser.position) {
+            let message = match extra.token {
+                Token::Close => "unmatched `)`".to_string(),
+                _ => format!("expected `and` or `or` before `{}`", &source[extra.start..extra.end]),
+            };
+            return Err(FilterError::new(message, extra.start, extra.end));
+        }
+        Ok(Self {
+            source: source.to_string(),
+            expr,
+        })
+    }
+
+    // Empty or whitespace-only input means no filter
+    pub fn parse_optional(source: &str) -> Result<Option<Self>, FilterError> {
+        if source.trim().is_empty() {
+            Ok(None)
+        } else {
+            Self::parse(source).map(Some)
+        }
+    }
+
+    pub fn source(&self) -> &str {
+        &self.source
+    }
+
+    pub fn matches(&self, aircraft: &Aircraft, context: &FilterContext) -> bool {
+        evaluate(&self.expr, aircraft, context)
+    }
+}
+
+fn evaluate(expr: &Expr, aircraft: &Aircraft, context: &FilterContext) -> bool {
+    match expr {
+        Expr::And(a, b) => evaluate(a, aircraft, context) && evaluate(b, aircraft, context),
+        Expr::Or(a, b) => evaluate(a, aircraft, context) || evaluate(b, aircraft, context),
+        Expr::Not(inner) => !evaluate(inner, aircraft, context),
+        Expr::Flag(field) => field.flag(aircraft).unwrap_or(false),
+        Expr::Number(field, op, value) => field.number(aircraft, context).is_some_and(|v| op.compare(v, *value)),
+        Expr::Text(field, op, value) => field
+            .text(aircraft, context)
+            .is_some_and(|v| op.compare(v.to_lowercase(), value.to_lowercase())),
+        Expr::Pattern(field, wanted, regex) => field
+            .text(aircraft, context)
+            .is_some_and(|v| regex.is_match(&v) == *wanted),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn context(home: &Location) -> FilterContext {
+        FilterContext {
+            home,
+            now: Utc::now(),
+            phases: None,
+        }
+    }
+
+    fn aircraft() -> Aircraft {
+        let home = Location::london();
+        let position = home.offset_by_km(0.0, 15.0);
+        let mut aircraft = Aircraft::new("a12345".to_string());
+        aircraft.callsign = Some("UAL123 ".to_string());
+        aircraft.latitude = Some(position.lat);
+        aircraft.longitude = Some(position.lon);
+        aircraft.altitude = Some(8000.0);
+        aircraft.velocity = Some(463.0);
+        aircraft
+    }
+
+    fn matches(source: &str) -> bool {
+        let home = Location::london();
+        Filter::parse(source).unwrap().matches(&aircraft(), &context(&home))
+    }
+
+    #[test]
+    fn test_example_expression() {
+        assert!(matches("altitude < 10000 and callsign ~ \"^UAL\" and distance < 20km"));
+        assert!(!matches("altitude < 10000 and distance < 5nm"));
+        assert!(matches("speed > 240kt or military"));
+        assert!(matches("not (squawk == \"7700\") && callsign == 'ual123'"));
+        // Missing squawk: neither equal nor different
+        assert!(!matches("squawk != \"1234\""));
+        assert!(matches("not emergency and on_ground != true"));
+    }
+
+    #[test]
+    fn test_units_convert_to_field_units() {
+        assert!(matches("altitude > 2400m"));
+        assert!(!matches("altitude > 2500m"));
+        assert!(matches("distance > 8nm and distance < 9.5mi"));
+    }
+
+    #[test]
+    fn test_error_messages() {
+        let error = Filter::parse("altitdue < 100").unwrap_err();
+        assert_eq!(error.message, "unknown field `altitdue`, did you mean `altitude`?");
+        assert_eq!((error.start, error.end), (0, 8));
+
+        let error = Filter::parse("distance < 20kt").unwrap_err();
+        assert!(error.message.contains("unit `kt`"), "{}", error.message);
+
+        let error = Filter::parse("callsign < \"A\"").unwrap_err();
+        assert_eq!(error.start, 9);
+
+        assert!(Filter::parse("altitude <").unwrap_err().message.contains("expected a value"));
+        assert!(Filter::parse("(altitude < 1").unwrap_err().message.contains("missing `)`"));
+        assert!(Filter::parse("callsign ~ \"(\"").unwrap_err().message.starts_with("invalid pattern"));
+        assert!(Filter::parse("altitude < 1 speed > 2").unwrap_err().message.contains("expected `and` or `or`"));
+        assert!(Filter::parse("altitude").unwrap_err().message.contains("needs a comparison"));
+
+        let described = Filter::parse("alt < \"x\"").unwrap_err().describe("alt < \"x\"");
+        assert_eq!(described.lines().nth(1), Some("      ^^^"));
+    }
+
+    #[test]
+    fn test_parse_optional() {
+        assert!(Filter::parse_optional("  ").unwrap().is_none());
+        assert!(Filter::parse_optional("alt > 0").unwrap().is_some());
+    }
+}
@@ -0,0 +1,343 @@
+use crate::aircraft::Aircraft;
+use crate::airports::{Airport, AirportDatabase};
+use crate::geo::Location;
+use egui::Color32;
+use std::collections::{HashMap, HashSet};
+
+// Altitude is in feet, vertical rate in ft/min and velocity in km/h, as
+// shown in the aircraft list.
+const AIRPORT_RADIUS_KM: f64 = 10.0;
+const TERMINAL_HEIGHT_FT: f64 = 3000.0;
+const INITIAL_CLIMB_HEIGHT_FT: f64 = 1000.0;
+const GROUND_HEIGHT_FT: f64 = 50.0;
+const ROLL_SPEED_KMH: f64 = 90.0;
+const TAXI_SPEED_KMH: f64 = 30.0;
+const CRUISE_ALTITUDE_FT: f64 = 10000.0;
+
+// Vertical rate deadband: entering a climb/descent needs a stronger signal
+// than staying in one.
+const ENTER_RATE_FPM: f64 = 500.0;
+const HOLD_RATE_FPM: f64 = 200.0;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+pub enum FlightPhase {
+    Taxi,
+    Takeoff,
+    Climb,
+    Cruise,
+    Descent,
+    Approach,
+    Landed,
+    #[default]
+    Unknown,
+}
+
+impl FlightPhase {
+    pub const ALL: [FlightPhase; 8] = [
+        FlightPhase::Taxi,
+        FlightPhase::Takeoff,
+        FlightPhase::Climb,
+        FlightPhase::Cruise,
+        FlightPhase::Descent,
+        FlightPhase::Approach,
+        FlightPhase::Landed,
+        FlightPhase::Unknown,
+    ];
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            FlightPhase::Taxi => "Taxi",
+            FlightPhase::Takeoff => "Takeoff",
+            FlightPhase::Climb => "Climb",
+            FlightPhase::Cruise => "Cruise",
+            FlightPhase::Descent => "Descent",
+            FlightPhase::Approach => "Approach",
+            FlightPhase::Landed => "Landed",
+            FlightPhase::Unknown => "Unknown",
+        }
+    }
+
+    pub fn short_name(&self) -> &'static str {
+        match self {
+            FlightPhase::Taxi => "TAX",
+            FlightPhase::Takeoff => "TOF",
+            FlightPhase::Climb => "CLB",
+            FlightPhase::Cruise => "CRZ",
+            FlightPhase::Descent => "DES",
+            FlightPhase::Approach => "APP",
+            FlightPhase::Landed => "LDG",
+            FlightPhase::Unknown => "---",
+        }
+    }
+
+    pub fn color(&self) -> Color32 {
+        match self {
+            FlightPhase::Taxi => Color32::from_rgb(160, 160, 160),
+            FlightPhase::Takeoff => Color32::from_rgb(255, 200, 0),
+            FlightPhase::Climb => Color32::from_rgb(0, 220, 120),
+            FlightPhase::Cruise => Color32::from_rgb(0, 200, 255),
+            FlightPhase::Descent => Color32::from_rgb(255, 120, 0),
+            FlightPhase::Approach => Color32::from_rgb(255, 60, 60),
+            FlightPhase::Landed => Color32::from_rgb(200, 120, 255),
+            FlightPhase::Unknown => Color32::from_rgb(128, 128, 128),
+        }
+    }
+
+    pub fn is_on_ground(&self) -> bool {
+        matches!(self, FlightPhase::Taxi | FlightPhase::Landed)
+    }
+}
+
+// Instantaneous classification; `previous` disambiguates ground rolls and
+// applies the vertical rate deadband.
+pub fn classify(aircraft: &Aircraft, previous: FlightPhase, nearby_airport: Option<&Airport>) -> FlightPhase {
+    let speed = aircraft.velocity.unwrap_or(0.0);
+    let vertical_rate = aircraft.vertical_rate.unwrap_or(0.0);
+    let field_elevation = nearby_airport.and_then(|a| a.elevation_ft).unwrap_or(0.0);
+    let height = aircraft.altitude.map(|alt| alt - field_elevation);
+
+    let on_ground = aircraft.on_ground.unwrap_or_else(|| {
+        nearby_airport.is_some() && height.is_some_and(|h| h < GROUND_HEIGHT_FT) && speed < ROLL_SPEED_KMH * 2.0
+    });
+
+    if on_ground {
+        let after_landing = matches!(previous, FlightPhase::Approach | FlightPhase::Descent | FlightPhase::Landed);
+        return if speed >= ROLL_SPEED_KMH {
+            if after_landing { FlightPhase::Landed } else { FlightPhase::Takeoff }
+        } else if after_landing && speed >= TAXI_SPEED_KMH {
+            FlightPhase::Landed
+        } else {
+            FlightPhase::Taxi
+        };
+    }
+
+    let Some(height) = height else {
+        return FlightPhase::Unknown;
+    };
+
+    let climb_threshold = if matches!(previous, FlightPhase::Climb | FlightPhase::Takeoff) {
+        HOLD_RATE_FPM
+    } else {
+        ENTER_RATE_FPM
+    };
+    let descent_threshold = if matches!(previous, FlightPhase::Descent | FlightPhase::Approach) {
+        HOLD_RATE_FPM
+    } else {
+        ENTER_RATE_FPM
+    };
+
+    let in_terminal_area = nearby_airport.is_some() && height < TERMINAL_HEIGHT_FT;
+
+    if vertical_rate > climb_threshold {
+        if in_terminal_area && height < INITIAL_CLIMB_HEIGHT_FT {
+            FlightPhase::Takeoff
+        } else {
+            FlightPhase::Climb
+        }
+    } else if vertical_rate < -descent_threshold {
+        if in_terminal_area {
+            FlightPhase::Approach
+        } else {
+            FlightPhase::Descent
+        }
+    } else if in_terminal_area && previous == FlightPhase::Approach {
+        // Level segments on an approach stay on the approach
+        FlightPhase::Approach
+    } else if aircraft.altitude.unwrap_or(0.0) >= CRUISE_ALTITUDE_FT || !in_terminal_area {
+        FlightPhase::Cruise
+    } else if matches!(previous, FlightPhase::Takeoff | FlightPhase::Climb | FlightPhase::Descent) {
+        previous
+    } else {
+        FlightPhase::Cruise
+    }
+}
+
+#[derive(Debug, Clone, Default)]
+struct PhaseState {
+    phase: FlightPhase,
+    candidate: FlightPhase,
+    candidate_count: u32,
+    // Consecutive updates the aircraft has been missing from
+    missed: u32,
+}
+
+// Tracks phases across updates; a new phase must be observed on
+// `confirm_updates` consecutive updates before it replaces the current one.
+#[derive(Debug, Clone)]
+pub struct PhaseTracker {
+    states: HashMap<String, PhaseState>,
+    pub confirm_updates: u32,
+}
+
+impl Default for PhaseTracker {
+    fn default() -> Self {
+        Self::new(2)
+    }
+}
+
+impl PhaseTracker {
+    pub fn new(confirm_updates: u32) -> Self {
+        Self {
+            states: HashMap::new(),
+            confirm_updates: confirm_updates.max(1),
+        }
+    }
+
+    pub fn update(&mut self, aircraft: &[Aircraft], airports: &AirportDatabase) {
+        // Dropping to Unknown needs confirming like any other change, so an
+        // aircraft missing from one update keeps its phase
+        let present: HashSet<&str> = aircraft.iter().map(|a| a.icao24.as_str()).collect();
+        let confirm_updates = self.confirm_updates;
+        self.states.retain(|icao24, state| {
+            if present.contains(icao24.as_str()) {
+                state.missed = 0;
+                return true;
+            }
+            state.missed += 1;
+            state.missed < confirm_updates
+        });
+
+        for aircraft in aircraft {
+            let nearby = aircraft
+                .position()
+                .and_then(|p| airports.nearest(&Location::from(p), AIRPORT_RADIUS_KM))
+                .map(|(airport, _)| airport);
+
+            let first_sighting = !self.states.contains_key(&aircraft.icao24);
+            let state = self.states.entry(aircraft.icao24.clone()).or_default();
+            let observed = classify(aircraft, state.phase, nearby);
+
+            if first_sighting {
+                // First sighting, nothing to flap against
+                state.phase = observed;
+                state.candidate_count = 0;
+            } else if observed == state.phase {
+                state.candidate_count = 0;
+            } else {
+                if observed == state.candidate {
+                    state.candidate_count += 1;
+                } else {
+                    state.candidate = observed;
+                    state.candidate_count = 1;
+                }
+                if state.candidate_count >= self.confirm_updates {
+                    state.phase = observed;
+                    state.candidate_count = 0;
+                }
+            }
+        }
+    }
+
+    pub fn phase(&self, icao24: &str) -> FlightPhase {
+        self.states
+            .get(icao24)
+            .map(|s| s.phase)
+            .unwrap_or_default()
+    }
+
+    pub fn phases(&self) -> HashMap<String, FlightPhase> {
+        self.states
+            .iter()
+            .map(|(icao24, state)| (icao24.clone(), state.phase))
+            .collect()
+    }
+
+    pub fn clear(&mut self) {
+        self.states.clear();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn aircraft(altitude: f64, velocity: f64, vertical_rate: f64) -> Aircraft {
+        let mut aircraft = Aircraft::new("a12345".to_string());
+        aircraft.latitude = Some(37.0);
+        aircraft.longitude = Some(-122.0);
+        aircraft.altitude = Some(altitude);
+        aircraft.velocity = Some(velocity);
+        aircraft.vertical_rate = Some(vertical_rate);
+        aircraft
+    }
+
+    #[test]
+    fn test_classify_airborne() {
+        assert_eq!(classify(&aircraft(35000.0, 850.0, 0.0), FlightPhase::Unknown, None), FlightPhase::Cruise);
+        assert_eq!(classify(&aircraft(15000.0, 700.0, 1500.0), FlightPhase::Unknown, None), FlightPhase::Climb);
+        assert_eq!(classify(&aircraft(15000.0, 700.0, -1500.0), FlightPhase::Unknown, None), FlightPhase::Descent);
+    }
+
+    #[test]
+    fn test_classify_deadband() {
+        // 300 ft/min is not enough to start a climb but keeps one going
+        let shallow = aircraft(20000.0, 700.0, 300.0);
+        assert_eq!(classify(&shallow, FlightPhase::Cruise, None), FlightPhase::Cruise);
+        assert_eq!(classify(&shallow, FlightPhase::Climb, None), FlightPhase::Climb);
+    }
+
+    #[test]
+    fn test_classify_ground() {
+        let mut rolling = aircraft(0.0, 250.0, 0.0);
+        rolling.on_ground = Some(true);
+        assert_eq!(classify(&rolling, FlightPhase::Taxi, None), FlightPhase::Takeoff);
+        assert_eq!(classify(&rolling, FlightPhase::Approach, None), FlightPhase::Landed);
+
+        rolling.velocity = Some(20.0);
+        assert_eq!(classify(&rolling, FlightPhase::Landed, None), FlightPhase::Taxi);
+    }
+
+    #[test]
+    fn test_tracker_hysteresis() {
+        let airports = AirportDatabase::default();
+        let mut tracker = PhaseTracker::new(2);
+
+        tracker.update(&[aircraft(35000.0, 850.0, 0.0)], &airports);
+        assert_eq!(tracker.phase("a12345"), FlightPhase::Cruise);
+
+        // A single climbing sample is not enough to switch
+        tracker.update(&[aircraft(35000.0, 850.0, 1000.0)], &airports);
+        assert_eq!(tracker.phase("a12345"), FlightPhase::Cruise);
+        tracker.update(&[aircraft(35000.0, 850.0, 0.0)], &airports);
+        assert_eq!(tracker.phase("a12345"), FlightPhase::Cruise);
+
+        tracker.update(&[aircraft(35000.0, 850.0, -1000.0)], &airports);
+        tracker.update(&[aircraft(34000.0, 850.0, -1000.0)], &airports);
+        assert_eq!(tracker.phase("a12345"), FlightPhase::Descent);
+
+        tracker.update(&[], &airports);
+        assert_eq!(tracker.phase("a12345"), FlightPhase::Descent);
+        tracker.update(&[], &airports);
+        assert_eq!(tracker.phase("a12345"), FlightPhase::Unknown);
+    }
+
+    #[test]
+    fn test_tracker_rides_out_dropped_reports() {
+        let airports = AirportDatabase::default();
+        let mut tracker = PhaseTracker::new(2);
+        let descending = aircraft(8000.0, 600.0, -1200.0);
+        tracker.update(&[descending.clone()], &airports);
+        assert_eq!(tracker.phase("a12345"), FlightPhase::Descent);
+
+        // One report without altitude, then one missing from the feed
+        let mut no_altitude = descending.clone();
+        no_altitude.altitude = None;
+        tracker.update(&[no_altitude.clone()], &airports);
+        assert_eq!(tracker.phase("a12345"), FlightPhase::Descent);
+        tracker.update(&[descending.clone()], &airports);
+        tracker.update(&[], &airports);
+        assert_eq!(tracker.phase("a12345"), FlightPhase::Descent);
+        tracker.update(&[descending], &airports);
+        assert_eq!(tracker.phase("a12345"), FlightPhase::Descent);
+
+        tracker.update(&[no_altitude.clone()], &airports);
+        tracker.update(&[no_altitude], &airports);
+        assert_eq!(tracker.phase("a12345"), FlightPhase::Unknown);
+
+        // Leaving Unknown is confirmed the same way
+        tracker.update(&[descending.clone()], &airports);
+        assert_eq!(tracker.phase("a12345"), FlightPhase::Unknown);
+        tracker.update(&[descending], &airports);
+        assert_eq!(tracker.phase("a12345"), FlightPhase::Descent);
+    }
+}
@@ -0,0 +1,22 @@
+// m:ss, or h:mm past an hour; used for every countdown and age in the UI
+pub fn format_duration(secs: f64) -> String {
+    let secs = secs.max(0.0).round() as u64;
+    if secs >= 3600 {
+        format!("{}h{:02}m", secs / 3600, secs % 3600 / 60)
+    } else {
+        format!("{}:{:02}", secs / 60, secs % 60)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_format_duration() {
+        assert_eq!(format_duration(95.0), "1:35");
+        assert_eq!(format_duration(3720.0), "1h02m");
+        assert_eq!(format_duration(4.4), "0:04");
+        assert_eq!(format_duration(-3.0), "0:00");
+    }
+}
@@ -1,7 +1,10 @@
 use geo::Point;
 use serde::{Deserialize, Serialize};
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+pub const KM_PER_DEG_LAT: f64 = 110.574;
+pub const KM_PER_DEG_LON: f64 = 111.320;
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
 pub struct Location {
     pub lat: f64,
     pub lon: f64,
@@ -75,6 +78,19 @@ impl Location {
         Location::new(lat2.to_degrees(), lon2.to_degrees())
     }
 
+    // Flat-earth east/north offset in km; fine for radar-sized areas
+    pub fn offset_km_to(&self, other: &Location) -> (f64, f64) {
+        let east = (other.lon - self.lon) * KM_PER_DEG_LON * self.lat.to_radians().cos();
+        let north = (other.lat - self.lat) * KM_PER_DEG_LAT;
+        (east, north)
+    }
+
+    pub fn offset_by_km(&self, east_km: f64, north_km: f64) -> Location {
+        let lat = self.lat + north_km / KM_PER_DEG_LAT;
+        let lon = self.lon + east_km / (KM_PER_DEG_LON * self.lat.to_radians().cos());
+        Location::new(lat, lon)
+    }
+
     pub fn to_point(&self) -> Point<f64> {
         Point::new(self.lon, self.lat)
     }
@@ -143,6 +159,17 @@ mod tests {
         assert!(bearing > 60.0 && bearing < 120.0);
     }
 
+    #[test]
+    fn test_local_offsets_round_trip() {
+        let sf = Location::san_francisco();
+        let point = sf.offset_by_km(3.0, -4.0);
+        let (east, north) = sf.offset_km_to(&point);
+
+        assert!((east - 3.0).abs() < 1e-9);
+        assert!((north + 4.0).abs() < 1e-9);
+        assert!((sf.distance_to(&point) - 5.0).abs() < 0.05);
+    }
+
     #[test]
     fn test_point_at_distance() {
         let sf = Location::san_francisco();
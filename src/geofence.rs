@@ -0,0 +1,421 @@
+use crate::aircraft::Aircraft;
+use crate::geo::Location;
+use crate::geojson;
+use crate::spatial::BoxGrid;
+use anyhow::Result;
+use chrono::{DateTime, Utc};
+use geo::{BoundingRect, Contains, Coord, Geometry, LineString, Point, Polygon};
+use serde::{Deserialize, Serialize};
+use std::collections::{HashMap, HashSet};
+use std::path::Path;
+
+const GRID_CELL_DEG: f64 = 0.25;
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub enum GeofenceShape {
+    Circle { center: Location, radius_km: f64 },
+    Polygon {
+        vertices: Vec<Location>,
+        // Interior rings cut out of the polygon, e.g. an airport inside a
+        // no-fly zone
+        #[serde(default)]
+        holes: Vec<Vec<Location>>,
+    },
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct Geofence {
+    pub name: String,
+    pub shape: GeofenceShape,
+    pub min_altitude_ft: Option<f64>,
+    pub max_altitude_ft: Option<f64>,
+    pub loiter_secs: f64,
+    pub enabled: bool,
+}
+
+impl Geofence {
+    pub fn circle(name: String, center: Location, radius_km: f64) -> Self {
+        Self::new(name, GeofenceShape::Circle { center, radius_km })
+    }
+
+    pub fn polygon(name: String, vertices: Vec<Location>) -> Self {
+        Self::new(name, GeofenceShape::Polygon { vertices, holes: Vec::new() })
+    }
+
+    pub fn from_polygon(name: String, polygon: &Polygon<f64>) -> Self {
+        let ring = |line: &LineString<f64>| line.coords().map(|c| Location::new(c.y, c.x)).collect();
+        let vertices = ring(polygon.exterior());
+        let holes = polygon.interiors().iter().map(ring).collect();
+        Self::new(name, GeofenceShape::Polygon { vertices, holes })
+    }
+
+    fn new(name: String, shape: GeofenceShape) -> Self {
+        Self {
+            name,
+            shape,
+            min_altitude_ft: None,
+            max_altitude_ft: None,
+            loiter_secs: 300.0,
+            enabled: true,
+        }
+    }
+
+    pub fn to_polygon(&self) -> Option<Polygon<f64>> {
+        match &self.shape {
+            GeofenceShape::Polygon { vertices, holes } if vertices.len() >= 3 => {
+                let ring = |ring: &[Location]| {
+                    LineString::from(ring.iter().map(|v| Coord { x: v.lon, y: v.lat }).collect::<Vec<_>>())
+                };
+                let interiors = holes.iter().filter(|h| h.len() >= 3).map(|h| ring(h)).collect();
+                Some(Polygon::new(ring(vertices), interiors))
+            }
+            _ => None,
+        }
+    }
+
+    pub fn in_altitude_band(&self, altitude: Option<f64>) -> bool {
+        if self.min_altitude_ft.is_none() && self.max_altitude_ft.is_none() {
+            return true;
+        }
+        let Some(altitude) = altitude else {
+            return false;
+        };
+        self.min_altitude_ft.map_or(true, |min| altitude >= min)
+            && self.max_altitude_ft.map_or(true, |max| altitude <= max)
+    }
+}
+
+// Polygons and named circles from a GeoJSON file. Points become circles
+// when they carry a `radius_km` property.
+pub fn import_geojson(path: &Path) -> Result<Vec<Geofence>> {
+    let mut fences = Vec::new();
+    for (idx, feature) in geojson::read_file(path)?.into_iter().enumerate() {
+        let name = feature
+            .property_str("name")
+            .map(str::to_string)
+            .unwrap_or_else(|| format!("Fence {}", idx + 1));
+
+        let mut imported = match &feature.geometry {
+            Geometry::Polygon(polygon) => vec![Geofence::from_polygon(name, polygon)],
+            Geometry::MultiPolygon(polygons) => polygons
+                .iter()
+                .enumerate()
+                .map(|(part, polygon)| Geofence::from_polygon(format!("{} {}", name, part + 1), polygon))
+                .collect(),
+            Geometry::Point(point) => match feature.property_f64("radius_km") {
+                Some(radius_km) => vec![Geofence::circle(name, Location::from_point(*point), radius_km)],
+                None => Vec::new(),
+            },
+            _ => Vec::new(),
+        };
+
+        for fence in &mut imported {
+            fence.min_altitude_ft = feature.property_f64("min_altitude_ft");
+            fence.max_altitude_ft = feature.property_f64("max_altitude_ft");
+        }
+        fences.extend(imported);
+    }
+    Ok(fences)
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum GeofenceEventKind {
+    Enter,
+    Exit,
+    Loiter,
+}
+
+impl GeofenceEventKind {
+    pub fn name(&self) -> &'static str {
+        match self {
+            GeofenceEventKind::Enter => "entered",
+            GeofenceEventKind::Exit => "exited",
+            GeofenceEventKind::Loiter => "is loitering in",
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct GeofenceEvent {
+    pub fence: String,
+    pub icao24: String,
+    pub kind: GeofenceEventKind,
+    pub time: DateTime<Utc>,
+}
+
+struct CompiledFence {
+    // Assigned by the monitor and carried across settings edits, so
+    // occupancy survives changes that don't touch the fence's geometry
+    id: u64,
+    fence: Geofence,
+    polygon: Option<Polygon<f64>>,
+    bounds: (f64, f64, f64, f64), // min lon, min lat, max lon, max lat
+}
+
+impl CompiledFence {
+    fn new(id: u64, fence: Geofence) -> Self {
+        let polygon = fence.to_polygon();
+        let bounds = match (&fence.shape, &polygon) {
+            (GeofenceShape::Circle { center, radius_km }, _) => {
+                let corner_sw = center.offset_by_km(-radius_km, -radius_km);
+                let corner_ne = center.offset_by_km(*radius_km, *radius_km);
+                (corner_sw.lon, corner_sw.lat, corner_ne.lon, corner_ne.lat)
+            }
+            (_, Some(polygon)) => polygon
+                .bounding_rect()
+                .map(|r| (r.min().x, r.min().y, r.max().x, r.max().y))
+                .unwrap_or((0.0, 0.0, -1.0, -1.0)),
+            _ => (0.0, 0.0, -1.0, -1.0),
+        };
+        Self { id, fence, polygon, bounds }
+    }
+
+    fn contains(&self, point: Point<f64>) -> bool {
+        let (min_x, min_y, max_x, max_y) = self.bounds;
+        if point.x() < min_x || point.x() > max_x || point.y() < min_y || point.y() > max_y {
+            return false;
+        }
+        match (&self.fence.shape, &self.polygon) {
+            (GeofenceShape::Circle { center, radius_km }, _) => {
+                center.distance_to(&Location::from_point(point)) <= *radius_km
+            }
+            (_, Some(polygon)) => polygon.contains(&point),
+            _ => false,
+        }
+    }
+}
+
+struct Occupancy {
+    entered: DateTime<Utc>,
+    loiter_reported: bool,
+}
+
+// Evaluates fences against each update. Fences are bucketed into a lat/lon
+// grid by bounding box so each aircraft only tests the fences near it.
+pub struct GeofenceMonitor {
+    fences: Vec<CompiledFence>,
+    grid: BoxGrid,
+    occupancy: HashMap<(u64, String), Occupancy>,
+    next_id: u64,
+}
+
+impl Default for GeofenceMonitor {
+    fn default() -> Self {
+        Self::new(&[])
+    }
+}
+
+impl GeofenceMonitor {
+    pub fn new(fences: &[Geofence]) -> Self {
+        let mut monitor = Self {
+            fences: Vec::new(),
+            grid: BoxGrid::new(GRID_CELL_DEG),
+            occupancy: HashMap::new(),
+            next_id: 0,
+        };
+        monitor.set_fences(fences);
+        monitor
+    }
+
+    pub fn set_fences(&mut self, fences: &[Geofence]) {
+        // Called on every settings edit, so fences whose shape is unchanged
+        // keep their id and aircraft already inside don't enter again
+        let mut previous = std::mem::take(&mut self.fences);
+        let compiled: Vec<CompiledFence> = fences
+            .iter()
+            .filter(|f| f.enabled)
+            .map(|fence| match previous.iter().position(|old| old.fence.shape == fence.shape) {
+                Some(pos) => {
+                    let mut kept = previous.swap_remove(pos);
+                    kept.fence = fence.clone();
+                    kept
+                }
+                None => {
+                    self.next_id += 1;
+                    CompiledFence::new(self.next_id, fence.clone())
+                }
+            })
+            .collect();
+        self.fences = compiled;
+
+        let ids: HashSet<u64> = self.fences.iter().map(|c| c.id).collect();
+        self.occupancy.retain(|(id, _), _| ids.contains(id));
+
+        self.grid.clear();
+        for (idx, compiled) in self.fences.iter().enumerate() {
+            self.grid.insert(idx, compiled.bounds);
+        }
+    }
+
+    pub fn update(&mut self, aircraft: &[Aircraft], now: DateTime<Utc>) -> Vec<GeofenceEvent> {
+        let mut events = Vec::new();
+        let mut seen = HashSet::new();
+
+        for aircraft in aircraft {
+            let Some(point) = aircraft.position() else {
+                continue;
+            };
+            let time = aircraft.time_position.unwrap_or(now);
+            for &idx in self.grid.query(point) {
+                let compiled = &self.fences[idx];
+                if !compiled.fence.in_altitude_band(aircraft.altitude) || !compiled.contains(point) {
+                    continue;
+                }
+
+                let key = (compiled.id, aircraft.icao24.clone());
+                seen.insert(key.clone());
+                let event = |kind| GeofenceEvent {
+                    fence: compiled.fence.name.clone(),
+                    icao24: aircraft.icao24.clone(),
+                    kind,
+                    time,
+                };
+
+                match self.occupancy.get_mut(&key) {
+                    None => {
+                        self.occupancy.insert(key, Occupancy { entered: time, loiter_reported: false });
+                        events.push(event(GeofenceEventKind::Enter));
+                    }
+                    Some(occupancy) => {
+                        let inside_secs = (time - occupancy.entered).num_seconds() as f64;
+                        if !occupancy.loiter_reported && inside_secs >= compiled.fence.loiter_secs {
+                            occupancy.loiter_reported = true;
+                            events.push(event(GeofenceEventKind::Loiter));
+                        }
+                    }
+                }
+            }
+        }
+
+        // Anything no longer inside has exited, unless the aircraft dropped out
+        // of the feed entirely
+        let present: Vec<&str> = aircraft.iter().map(|a| a.icao24.as_str()).collect();
+        let fences = &self.fences;
+        self.occupancy.retain(|key, _| {
+            if seen.contains(key) {
+                return true;
+            }
+            let fence = fences.iter().find(|c| c.id == key.0);
+            if let (Some(compiled), true) = (fence, present.contains(&key.1.as_str())) {
+                events.push(GeofenceEvent {
+                    fence: compiled.fence.name.clone(),
+                    icao24: key.1.clone(),
+                    kind: GeofenceEventKind::Exit,
+                    time: now,
+                });
+            }
+            false
+        });
+
+        events
+    }
+
+    pub fn occupants(&self, fence_name: &str) -> Vec<&str> {
+        self.occupancy
+            .keys()
+            .filter(|(id, _)| self.fences.iter().any(|c| c.id == *id && c.fence.name == fence_name))
+            .map(|(_, icao24)| icao24.as_str())
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::Duration;
+
+    fn aircraft_at(location: &Location, altitude: f64) -> Aircraft {
+        let mut aircraft = Aircraft::new("a12345".to_string());
+        aircraft.latitude = Some(location.lat);
+        aircraft.longitude = Some(location.lon);
+        aircraft.altitude = Some(altitude);
+        aircraft
+    }
+
+    #[test]
+    fn test_enter_loiter_exit() {
+        let home = Location::london();
+        let mut fence = Geofence::circle("Home".to_string(), home.clone(), 5.0);
+        fence.loiter_secs = 60.0;
+        let mut monitor = GeofenceMonitor::new(&[fence]);
+        let start = Utc::now();
+
+        let events = monitor.update(&[aircraft_at(&home, 3000.0)], start);
+        assert_eq!(events.len(), 1);
+        assert_eq!(events[0].kind, GeofenceEventKind::Enter);
+
+        assert!(monitor.update(&[aircraft_at(&home, 3000.0)], start + Duration::seconds(30)).is_empty());
+
+        let events = monitor.update(&[aircraft_at(&home, 3000.0)], start + Duration::seconds(90));
+        assert_eq!(events[0].kind, GeofenceEventKind::Loiter);
+
+        let outside = home.offset_by_km(20.0, 0.0);
+        let events = monitor.update(&[aircraft_at(&outside, 3000.0)], start + Duration::seconds(120));
+        assert_eq!(events[0].kind, GeofenceEventKind::Exit);
+    }
+
+    #[test]
+    fn test_polygon_with_altitude_band() {
+        let polygon: Polygon<f64> = Polygon::new(
+            LineString::from(vec![(-0.2, 51.4), (0.0, 51.4), (0.0, 51.6), (-0.2, 51.6), (-0.2, 51.4)]),
+            Vec::new(),
+        );
+        let mut fence = Geofence::from_polygon("Box".to_string(), &polygon);
+        fence.max_altitude_ft = Some(5000.0);
+        let mut monitor = GeofenceMonitor::new(&[fence]);
+        let home = Location::london();
+
+        assert!(monitor.update(&[aircraft_at(&home, 35000.0)], Utc::now()).is_empty());
+        assert_eq!(monitor.update(&[aircraft_at(&home, 2000.0)], Utc::now()).len(), 1);
+        assert_eq!(monitor.occupants("Box"), vec!["a12345"]);
+    }
+
+    #[test]
+    fn test_polygon_holes_are_kept() {
+        let polygon: Polygon<f64> = Polygon::new(
+            LineString::from(vec![(-0.5, 51.2), (0.3, 51.2), (0.3, 51.8), (-0.5, 51.8), (-0.5, 51.2)]),
+            vec![LineString::from(vec![
+                (-0.2, 51.4),
+                (0.0, 51.4),
+                (0.0, 51.6),
+                (-0.2, 51.6),
+                (-0.2, 51.4),
+            ])],
+        );
+        let fence = Geofence::from_polygon("Ring".to_string(), &polygon);
+        assert_eq!(fence.to_polygon(), Some(polygon));
+
+        // London sits in the hole, so only the aircraft out in the ring counts
+        let mut monitor = GeofenceMonitor::new(&[fence]);
+        let home = Location::london();
+        assert!(monitor.update(&[aircraft_at(&home, 3000.0)], Utc::now()).is_empty());
+        let ring = Location::new(51.3, -0.1);
+        assert_eq!(monitor.update(&[aircraft_at(&ring, 3000.0)], Utc::now()).len(), 1);
+    }
+
+    #[test]
+    fn test_settings_edits_keep_occupancy() {
+        let home = Location::london();
+        let away = Geofence::circle("Away".to_string(), home.offset_by_km(50.0, 0.0), 5.0);
+        let mut fences = vec![away, Geofence::circle("Home".to_string(), home.clone(), 5.0)];
+        let mut monitor = GeofenceMonitor::new(&fences);
+        let now = Utc::now();
+        assert_eq!(monitor.update(&[aircraft_at(&home, 3000.0)], now).len(), 1);
+
+        // Disabling another fence and editing this one's band isn't a new entry
+        fences[0].enabled = false;
+        fences[1].max_altitude_ft = Some(10000.0);
+        monitor.set_fences(&fences);
+        assert!(monitor.update(&[aircraft_at(&home, 3000.0)], now).is_empty());
+        assert_eq!(monitor.occupants("Home"), vec!["a12345"]);
+
+        // Moving it is
+        if let GeofenceShape::Circle { radius_km, .. } = &mut fences[1].shape {
+            *radius_km = 6.0;
+        }
+        monitor.set_fences(&fences);
+        let events = monitor.update(&[aircraft_at(&home, 3000.0)], now);
+        assert_eq!(events.len(), 1);
+        assert_eq!(events[0].kind, GeofenceEventKind::Enter);
+    }
+}
@@ -0,0 +1,166 @@
+// Minimal GeoJSON reader producing `geo` geometries. Only the parts needed
+// for overlays are supported: Point, LineString, Polygon and their Multi*
+// variants, plus GeometryCollection, inside a Feature or FeatureCollection.
+
+use anyhow::{anyhow, Context, Result};
+use geo::{Coord, Geometry, GeometryCollection, LineString, MultiLineString, MultiPoint, MultiPolygon, Point, Polygon};
+use serde_json::{Map, Value};
+use std::path::Path;
+
+#[derive(Debug, Clone)]
+pub struct Feature {
+    pub geometry: Geometry<f64>,
+    pub properties: Map<String, Value>,
+}
+
+impl Feature {
+    pub fn property_str(&self, key: &str) -> Option<&str> {
+        self.properties.get(key).and_then(Value::as_str)
+    }
+
+    pub fn property_f64(&self, key: &str) -> Option<f64> {
+        match self.properties.get(key)? {
+            Value::Number(n) => n.as_f64(),
+            Value::String(s) => s.trim().parse().ok(),
+            _ => None,
+        }
+    }
+}
+
+pub fn read_file(path: &Path) -> Result<Vec<Feature>> {
+    let text = std::fs::read_to_string(path)
+        .with_context(|| format!("Failed to read {}", path.display()))?;
+    parse(&text).with_context(|| format!("Invalid GeoJSON in {}", path.display()))
+}
+
+pub fn parse(text: &str) -> Result<Vec<Feature>> {
+    let root: Value = serde_json::from_str(text)?;
+    let mut features = Vec::new();
+    collect_features(&root, &mut features)?;
+    Ok(features)
+}
+
+fn collect_features(value: &Value, out: &mut Vec<Feature>) -> Result<()> {
+    match value.get("type").and_then(Value::as_str) {
+        Some("FeatureCollection") => {
+            let items = value
+                .get("features")
+                .and_then(Value::as_array)
+                .ok_or_else(|| anyhow!("FeatureCollection without features"))?;
+            for item in items {
+                collect_features(item, out)?;
+            }
+        }
+        Some("Feature") => {
+            // Features with null geometry are valid GeoJSON but carry nothing to draw
+            if let Some(geometry) = value.get("geometry").filter(|g| !g.is_null()) {
+                out.push(Feature {
+                    geometry: parse_geometry(geometry)?,
+                    properties: value
+                        .get("properties")
+                        .and_then(Value::as_object)
+                        .cloned()
+                        .unwrap_or_default(),
+                });
+            }
+        }
+        Some(_) => out.push(Feature {
+            geometry: parse_geometry(value)?,
+            properties: Map::new(),
+        }),
+        None => return Err(anyhow!("GeoJSON object without type")),
+    }
+    Ok(())
+}
+
+fn parse_geometry(value: &Value) -> Result<Geometry<f64>> {
+    let kind = value
+        .get("type")
+        .and_then(Value::as_str)
+        .ok_or_else(|| anyhow!("Geometry without type"))?;
+
+    if kind == "GeometryCollection" {
+        let members = value
+            .get("geometries")
+            .and_then(Value::as_array)
+            .ok_or_else(|| anyhow!("GeometryCollection without geometries"))?;
+        let geometries = members.iter().map(parse_geometry).collect::<Result<Vec<_>>>()?;
+        return Ok(Geometry::GeometryCollection(GeometryCollection(geometries)));
+    }
+
+    let coords = value
+        .get("coordinates")
+        .ok_or_else(|| anyhow!("{} without coordinates", kind))?;
+
+    Ok(match kind {
+        "Point" => Geometry::Point(Point::from(coord(coords)?)),
+        "MultiPoint" => Geometry::MultiPoint(MultiPoint(
+            array(coords)?.iter().map(|c| coord(c).map(Point::from)).collect::<Result<_>>()?,
+        )),
+        "LineString" => Geometry::LineString(line_string(coords)?),
+        "MultiLineString" => Geometry::MultiLineString(MultiLineString(
+            array(coords)?.iter().map(line_string).collect::<Result<_>>()?,
+        )),
+        "Polygon" => Geometry::Polygon(polygon(coords)?),
+        "MultiPolygon" => Geometry::MultiPolygon(MultiPolygon(
+            array(coords)?.iter().map(polygon).collect::<Result<_>>()?,
+        )),
+        other => return Err(anyhow!("Unsupported geometry type {}", other)),
+    })
+}
+
+fn array(value: &Value) -> Result<&Vec<Value>> {
+    value.as_array().ok_or_else(|| anyhow!("Expected a coordinate array"))
+}
+
+fn coord(value: &Value) -> Result<Coord<f64>> {
+    let items = array(value)?;
+    match (items.first().and_then(Value::as_f64), items.get(1).and_then(Value::as_f64)) {
+        (Some(x), Some(y)) => Ok(Coord { x, y }),
+        _ => Err(anyhow!("Invalid position {}", value)),
+    }
+}
+
+fn line_string(value: &Value) -> Result<LineString<f64>> {
+    Ok(LineString(array(value)?.iter().map(coord).collect::<Result<_>>()?))
+}
+
+fn polygon(value: &Value) -> Result<Polygon<f64>> {
+    let mut rings = array(value)?.iter().map(line_string);
+    let exterior = rings.next().ok_or_else(|| anyhow!("Polygon without rings"))??;
+    let interiors = rings.collect::<Result<Vec<_>>>()?;
+    Ok(Polygon::new(exterior, interiors))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_feature_collection() {
+        let features = parse(
+            r#"{
+                "type": "FeatureCollection",
+                "features": [
+                    {"type": "Feature", "properties": {"name": "Box", "floor": "1500"},
+                     "geometry": {"type": "Polygon", "coordinates": [[[0,0],[1,0],[1,1],[0,1],[0,0]]]}},
+                    {"type": "Feature", "properties": {}, "geometry": null},
+                    {"type": "Feature", "properties": {},
+                     "geometry": {"type": "LineString", "coordinates": [[0,0],[2,2]]}}
+                ]
+            }"#,
+        )
+        .unwrap();
+
+        assert_eq!(features.len(), 2);
+        assert_eq!(features[0].property_str("name"), Some("Box"));
+        assert_eq!(features[0].property_f64("floor"), Some(1500.0));
+        assert!(matches!(features[0].geometry, Geometry::Polygon(_)));
+        assert!(matches!(features[1].geometry, Geometry::LineString(_)));
+    }
+
+    #[test]
+    fn test_rejects_unknown_geometry() {
+        assert!(parse(r#"{"type": "Curve", "coordinates": []}"#).is_err());
+    }
+}
@@ -0,0 +1,171 @@
+use crate::geo::Location;
+use crate::track_store::{TrackPoint, TrackStore};
+use chrono::{DateTime, Duration, Utc};
+use egui::Color32;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+
+// Consecutive fixes further apart than this are separate visits, not a
+// path to fill in
+const MAX_GAP_SECS: i64 = 300;
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct HeatmapSettings {
+    pub enabled: bool,
+    pub window_minutes: u64,
+    pub cell_km: f64,
+    pub opacity: f32,
+}
+
+impl Default for HeatmapSettings {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            window_minutes: 120,
+            cell_km: 1.0,
+            opacity: 0.6,
+        }
+    }
+}
+
+// Traffic counts on a square grid aligned east/north at `origin`
+#[derive(Debug, Clone)]
+pub struct DensityGrid {
+    pub origin: Location,
+    pub cell_km: f64,
+    cells: HashMap<(i32, i32), u32>,
+    max: u32,
+}
+
+impl DensityGrid {
+    // Accumulates every track in the store over the `window_minutes` before
+    // `until`, filling cells along each leg between fixes so sparse updates
+    // still draw continuous paths
+    pub fn build(store: &TrackStore, origin: &Location, settings: &HeatmapSettings, until: DateTime<Utc>) -> Self {
+        let mut grid = Self {
+            origin: origin.clone(),
+            cell_km: settings.cell_km.max(0.1),
+            cells: HashMap::new(),
+            max: 0,
+        };
+        let since = until - Duration::minutes(settings.window_minutes as i64);
+        for track in store.tracks_since(since) {
+            grid.add_track(track.iter().take_while(|p| p.time <= until));
+        }
+        grid
+    }
+
+    fn add_track<'a>(&mut self, points: impl Iterator<Item = &'a TrackPoint>) {
+        let mut previous: Option<(&TrackPoint, (f64, f64))> = None;
+        for point in points {
+            let offset = self.origin.offset_km_to(&Location::new(point.lat, point.lon));
+            let mut visited = Vec::new();
+            match previous {
+                Some((last, from)) if (point.time - last.time).num_seconds() <= MAX_GAP_SECS => {
+                    // Half-cell steps so no cell along the leg is skipped
+                    let length = (offset.0 - from.0).hypot(offset.1 - from.1);
+                    let steps = (length / (self.cell_km / 2.0)).ceil().max(1.0) as usize;
+                    for step in 1..=steps {
+                        let t = step as f64 / steps as f64;
+                        visited.push(self.cell(from.0 + (offset.0 - from.0) * t, from.1 + (offset.1 - from.1) * t));
+                    }
+                }
+                _ => visited.push(self.cell(offset.0, offset.1)),
+            }
+            // Each leg counts once per cell however many steps land in it
+            visited.dedup();
+            for cell in visited {
+                let count = self.cells.entry(cell).or_insert(0);
+                *count += 1;
+                self.max = self.max.max(*count);
+            }
+            previous = Some((point, offset));
+        }
+    }
+
+    fn cell(&self, east_km: f64, north_km: f64) -> (i32, i32) {
+        ((east_km / self.cell_km).floor() as i32, (north_km / self.cell_km).floor() as i32)
+    }
+
+    pub fn count(&self, east_km: f64, north_km: f64) -> u32 {
+        self.cells.get(&self.cell(east_km, north_km)).copied().unwrap_or(0)
+    }
+
+    pub fn max(&self) -> u32 {
+        self.max
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.cells.is_empty()
+    }
+
+    // Cells with their south-west corner in km from the origin and their
+    // density scaled to 0..=1. Log scaling keeps quieter routes visible
+    // next to the busiest approach.
+    pub fn cells(&self) -> impl Iterator<Item = ((f64, f64), f32)> + '_ {
+        let scale = (self.max as f64).ln_1p().max(f64::EPSILON);
+        self.cells.iter().map(move |(&(x, y), &count)| {
+            let corner = (x as f64 * self.cell_km, y as f64 * self.cell_km);
+            (corner, ((count as f64).ln_1p() / scale) as f32)
+        })
+    }
+}
+
+// Blue through green and yellow to red, fading in from transparent
+pub fn color_ramp(density: f32, opacity: f32) -> Color32 {
+    const STOPS: [(f32, [u8; 3]); 4] = [
+        (0.0, [40, 60, 255]),
+        (0.35, [0, 220, 120]),
+        (0.7, [255, 230, 0]),
+        (1.0, [255, 40, 20]),
+    ];
+    let density = density.clamp(0.0, 1.0);
+    let upper = STOPS.iter().position(|(stop, _)| *stop >= density).unwrap_or(STOPS.len() - 1).max(1);
+    let (low_stop, low) = STOPS[upper - 1];
+    let (high_stop, high) = STOPS[upper];
+    let t = (density - low_stop) / (high_stop - low_stop);
+    let channel = |i: usize| (low[i] as f32 + (high[i] as f32 - low[i] as f32) * t).round() as u8;
+
+    let alpha = opacity * (0.25 + 0.75 * density);
+    Color32::from_rgb(channel(0), channel(1), channel(2)).gamma_multiply(alpha)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::aircraft::Aircraft;
+
+    #[test]
+    fn test_legs_fill_cells_between_fixes() {
+        let home = Location::london();
+        let start = Utc::now();
+        let mut store = TrackStore::new(600);
+        for i in 0..3 {
+            // 5 km legs north, 30 s apart
+            let fix = home.offset_by_km(0.5, i as f64 * 5.0);
+            let mut aircraft = Aircraft::new("aaaaaa".to_string());
+            aircraft.latitude = Some(fix.lat);
+            aircraft.longitude = Some(fix.lon);
+            aircraft.time_position = Some(start + Duration::seconds(i * 30));
+            store.record(&[aircraft], start);
+        }
+
+        let grid = DensityGrid::build(&store, &home, &HeatmapSettings::default(), start + Duration::minutes(1));
+        for north in 0..10 {
+            assert!(grid.count(0.5, north as f64 + 0.5) > 0, "gap at {} km", north);
+        }
+        assert_eq!(grid.count(3.5, 3.5), 0);
+        // The cell shared by both legs is counted by each
+        assert_eq!(grid.max(), 2);
+
+        // Nothing falls inside a window that ends before the first fix
+        let earlier = DensityGrid::build(&store, &home, &HeatmapSettings::default(), start - Duration::minutes(1));
+        assert!(earlier.is_empty());
+    }
+
+    #[test]
+    fn test_color_ramp() {
+        assert_eq!(color_ramp(1.0, 1.0), Color32::from_rgb(255, 40, 20));
+        assert!(color_ramp(0.0, 1.0).a() < color_ramp(1.0, 1.0).a());
+    }
+}
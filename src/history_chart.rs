@@ -0,0 +1,473 @@
+use crate::geo::Location;
+use crate::track_store::{TrackPoint, TrackStore};
+use anyhow::{Context, Result};
+use chrono::{DateTime, Duration, Utc};
+use egui::{Color32, Pos2, Rect, Sense, Shape, Stroke, Ui, Vec2};
+use std::path::Path;
+
+pub const MAX_PINNED: usize = 5;
+// Zooming in stops at this span so a single fix never fills the chart
+const MIN_SPAN_SECS: i64 = 30;
+const MARGIN: Vec2 = Vec2::new(52.0, 14.0);
+const PALETTE: [Color32; 6] = [
+    Color32::from_rgb(0, 200, 255),
+    Color32::from_rgb(255, 170, 0),
+    Color32::from_rgb(120, 230, 90),
+    Color32::from_rgb(255, 90, 160),
+    Color32::from_rgb(190, 140, 255),
+    Color32::from_rgb(240, 240, 120),
+];
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ChartSeries {
+    Altitude,
+    GroundSpeed,
+    VerticalRate,
+    Distance,
+}
+
+impl ChartSeries {
+    pub const ALL: [ChartSeries; 4] = [
+        ChartSeries::Altitude,
+        ChartSeries::GroundSpeed,
+        ChartSeries::VerticalRate,
+        ChartSeries::Distance,
+    ];
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            ChartSeries::Altitude => "Altitude",
+            ChartSeries::GroundSpeed => "Ground speed",
+            ChartSeries::VerticalRate => "Vertical rate",
+            ChartSeries::Distance => "Distance",
+        }
+    }
+
+    pub fn unit(&self) -> &'static str {
+        match self {
+            ChartSeries::Altitude => "ft",
+            ChartSeries::GroundSpeed => "km/h",
+            ChartSeries::VerticalRate => "fpm",
+            ChartSeries::Distance => "km",
+        }
+    }
+
+    pub fn value(&self, point: &TrackPoint, home: &Location) -> Option<f64> {
+        match self {
+            ChartSeries::Altitude => point.altitude,
+            ChartSeries::GroundSpeed => point.velocity,
+            ChartSeries::VerticalRate => point.vertical_rate,
+            ChartSeries::Distance => Some(home.distance_to(&Location::new(point.lat, point.lon))),
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct PlotLine {
+    pub icao24: String,
+    pub series: ChartSeries,
+    pub points: Vec<(DateTime<Utc>, f64)>,
+}
+
+// Time-series plots for the selected aircraft plus any pinned ones
+#[derive(Debug, Clone)]
+pub struct HistoryChart {
+    pinned: Vec<String>,
+    series: Vec<ChartSeries>,
+    // Visible time span; None shows the whole history and follows new data
+    view: Option<(DateTime<Utc>, DateTime<Utc>)>,
+}
+
+impl Default for HistoryChart {
+    fn default() -> Self {
+        Self {
+            pinned: Vec::new(),
+            series: vec![ChartSeries::Altitude, ChartSeries::GroundSpeed],
+            view: None,
+        }
+    }
+}
+
+impl HistoryChart {
+    pub fn pinned(&self) -> &[String] {
+        &self.pinned
+    }
+
+    pub fn is_pinned(&self, icao24: &str) -> bool {
+        self.pinned.iter().any(|p| p == icao24)
+    }
+
+    // Returns false when already at the pin limit
+    pub fn toggle_pin(&mut self, icao24: &str) -> bool {
+        if self.is_pinned(icao24) {
+            self.pinned.retain(|p| p != icao24);
+        } else if self.pinned.len() < MAX_PINNED {
+            self.pinned.push(icao24.to_string());
+        } else {
+            return false;
+        }
+        true
+    }
+
+    pub fn shows(&self, series: ChartSeries) -> bool {
+        self.series.contains(&series)
+    }
+
+    pub fn toggle_series(&mut self, series: ChartSeries) {
+        if self.shows(series) {
+            self.series.retain(|s| *s != series);
+        } else {
+            self.series.push(series);
+            self.series.sort_by_key(|s| ChartSeries::ALL.iter().position(|a| a == s));
+        }
+    }
+
+    // Pinned aircraft first, then the selection if it isn't pinned
+    pub fn aircraft(&self, selected: Option<&str>) -> Vec<String> {
+        let mut aircraft = self.pinned.clone();
+        if let Some(selected) = selected.filter(|s| !self.is_pinned(s)) {
+            aircraft.push(selected.to_string());
+        }
+        aircraft
+    }
+
+    pub fn is_zoomed(&self) -> bool {
+        self.view.is_some()
+    }
+
+    pub fn visible(&self, extent: (DateTime<Utc>, DateTime<Utc>)) -> (DateTime<Utc>, DateTime<Utc>) {
+        self.view.unwrap_or(extent)
+    }
+
+    // `factor` below 1 zooms in, keeping the time at `anchor` (0 = left
+    // edge, 1 = right edge) in place
+    pub fn zoom(&mut self, factor: f64, anchor: f64, extent: (DateTime<Utc>, DateTime<Utc>)) {
+        let (start, end) = self.visible(extent);
+        let span = (end - start).num_milliseconds() as f64;
+        let new_span = (span * factor).max(MIN_SPAN_SECS as f64 * 1000.0);
+        let pivot = start + Duration::milliseconds((span * anchor.clamp(0.0, 1.0)) as i64);
+        let new_start = pivot - Duration::milliseconds((new_span * anchor.clamp(0.0, 1.0)) as i64);
+        self.set_view(new_start, new_start + Duration::milliseconds(new_span as i64), extent);
+    }
+
+    // Shifts the view by a fraction of its width; positive moves later
+    pub fn pan(&mut self, fraction: f64, extent: (DateTime<Utc>, DateTime<Utc>)) {
+        let (start, end) = self.visible(extent);
+        let shift = Duration::milliseconds(((end - start).num_milliseconds() as f64 * fraction) as i64);
+        self.set_view(start + shift, end + shift, extent);
+    }
+
+    pub fn reset(&mut self) {
+        self.view = None;
+    }
+
+    // Keeps the view inside the data; a view covering all of it goes back
+    // to following new data
+    fn set_view(&mut self, start: DateTime<Utc>, end: DateTime<Utc>, extent: (DateTime<Utc>, DateTime<Utc>)) {
+        let span = end - start;
+        if span >= extent.1 - extent.0 {
+            self.view = None;
+            return;
+        }
+        let start = start.max(extent.0).min(extent.1 - span);
+        self.view = Some((start, start + span));
+    }
+
+    pub fn lines(
+        &self,
+        store: &TrackStore,
+        aircraft: &[String],
+        home: &Location,
+        extent: (DateTime<Utc>, DateTime<Utc>),
+    ) -> Vec<PlotLine> {
+        let (start, end) = self.visible(extent);
+        let mut lines = Vec::new();
+        for series in &self.series {
+            for icao24 in aircraft {
+                let points = store
+                    .track(icao24)
+                    .iter()
+                    .filter(|p| p.time >= start && p.time <= end)
+                    .filter_map(|p| Some((p.time, series.value(p, home)?)))
+                    .collect();
+                lines.push(PlotLine {
+                    icao24: icao24.clone(),
+                    series: *series,
+                    points,
+                });
+            }
+        }
+        lines
+    }
+
+    pub fn draw(
+        &mut self,
+        ui: &mut Ui,
+        store: &TrackStore,
+        home: &Location,
+        aircraft: &[String],
+        label: impl Fn(&str) -> String,
+    ) {
+        let Some(extent) = data_extent(store, aircraft) else {
+            ui.label("No history recorded for these aircraft yet");
+            return;
+        };
+        if self.series.is_empty() {
+            ui.label("Choose something to plot");
+            return;
+        }
+
+        ui.horizontal_wrapped(|ui| {
+            for (idx, icao24) in aircraft.iter().enumerate() {
+                let pin = if self.is_pinned(icao24) { "📌 " } else { "" };
+                ui.colored_label(PALETTE[idx % PALETTE.len()], format!("{}{}", pin, label(icao24)));
+            }
+        });
+
+        let (response, painter) = ui.allocate_painter(
+            Vec2::new(ui.available_width(), ui.available_height().max(120.0)),
+            Sense::click_and_drag(),
+        );
+        let area = Rect::from_min_max(response.rect.min + MARGIN, response.rect.max - Vec2::new(MARGIN.y, MARGIN.y));
+
+        // Drag pans, ctrl+scroll or pinch zooms about the pointer, and a
+        // double click shows everything again
+        if response.double_clicked() {
+            self.reset();
+        } else if response.dragged() && area.width() > 0.0 {
+            self.pan(-(response.drag_delta().x / area.width()) as f64, extent);
+        }
+        if response.hovered() {
+            let zoom = ui.input(|i| i.zoom_delta());
+            if (zoom - 1.0).abs() > f32::EPSILON {
+                let anchor = response
+                    .hover_pos()
+                    .map(|p| ((p.x - area.left()) / area.width()) as f64)
+                    .unwrap_or(0.5);
+                self.zoom(1.0 / zoom as f64, anchor, extent);
+            }
+        }
+
+        let (start, end) = self.visible(extent);
+        let span_ms = ((end - start).num_milliseconds() as f64).max(1.0);
+        let lines = self.lines(store, aircraft, home, extent);
+        let visuals = ui.visuals();
+        let grid = visuals.weak_text_color().gamma_multiply(0.4);
+        let text = visuals.text_color();
+        let font = egui::FontId::proportional(10.0);
+
+        let rows = self.series.len();
+        let row_height = area.height() / rows as f32;
+        for (row, series) in self.series.iter().enumerate() {
+            let plot = Rect::from_min_size(
+                Pos2::new(area.left(), area.top() + row as f32 * row_height),
+                Vec2::new(area.width(), row_height - 12.0),
+            );
+            painter.rect_filled(plot, 2.0, visuals.extreme_bg_color);
+            painter.text(
+                plot.left_top() + Vec2::new(4.0, 2.0),
+                egui::Align2::LEFT_TOP,
+                format!("{} ({})", series.name(), series.unit()),
+                font.clone(),
+                text,
+            );
+
+            let series_lines: Vec<(usize, &PlotLine)> = lines
+                .iter()
+                .filter(|l| l.series == *series)
+                .map(|l| (aircraft.iter().position(|a| a == &l.icao24).unwrap_or(0), l))
+                .collect();
+            let values = series_lines.iter().flat_map(|(_, l)| l.points.iter().map(|p| p.1));
+            let Some((low, high)) = value_range(values, *series) else {
+                continue;
+            };
+
+            let to_screen = |time: DateTime<Utc>, value: f64| {
+                let x = plot.left() + ((time - start).num_milliseconds() as f64 / span_ms) as f32 * plot.width();
+                let y = plot.bottom() - ((value - low) / (high - low)) as f32 * plot.height();
+                Pos2::new(x, y)
+            };
+
+            for value in [low, (low + high) / 2.0, high] {
+                let y = to_screen(start, value).y;
+                painter.line_segment([Pos2::new(plot.left(), y), Pos2::new(plot.right(), y)], Stroke::new(1.0, grid));
+                painter.text(Pos2::new(plot.left() - 4.0, y), egui::Align2::RIGHT_CENTER, format!("{:.0}", value), font.clone(), text);
+            }
+
+            let clipped = painter.with_clip_rect(plot);
+            for (idx, line) in series_lines {
+                let color = PALETTE[idx % PALETTE.len()];
+                let points: Vec<Pos2> = line.points.iter().map(|(t, v)| to_screen(*t, *v)).collect();
+                match points.len() {
+                    0 => {}
+                    1 => {
+                        clipped.circle_filled(points[0], 2.5, color);
+                    }
+                    _ => {
+                        clipped.add(Shape::line(points, Stroke::new(1.5, color)));
+                    }
+                }
+            }
+        }
+
+        // Time labels along the bottom
+        for step in 0..=4 {
+            let fraction = step as f64 / 4.0;
+            let time = start + Duration::milliseconds((span_ms * fraction) as i64);
+            let x = area.left() + fraction as f32 * area.width();
+            painter.text(
+                Pos2::new(x, area.bottom()),
+                egui::Align2::CENTER_TOP,
+                time.format("%H:%M:%S").to_string(),
+                font.clone(),
+                text,
+            );
+        }
+
+        if let Some(pos) = response.hover_pos().filter(|p| area.contains(*p)) {
+            painter.line_segment([Pos2::new(pos.x, area.top()), Pos2::new(pos.x, area.bottom())], Stroke::new(1.0, grid));
+            let time = start + Duration::milliseconds((span_ms * ((pos.x - area.left()) / area.width()) as f64) as i64);
+            response.on_hover_text(time.format("%H:%M:%S UTC").to_string());
+        }
+    }
+}
+
+// First and last recorded time across the given aircraft
+pub fn data_extent(store: &TrackStore, aircraft: &[String]) -> Option<(DateTime<Utc>, DateTime<Utc>)> {
+    let times = aircraft.iter().flat_map(|icao24| {
+        let track = store.track(icao24);
+        track.first().zip(track.last()).map(|(first, last)| (first.time, last.time))
+    });
+    let (start, end) = times.reduce(|a, b| (a.0.min(b.0), a.1.max(b.1)))?;
+    // A single fix still gets a readable span around it
+    Some((start.min(end - Duration::seconds(MIN_SPAN_SECS)), end))
+}
+
+// Padded value range; vertical rate always includes zero so climbs and
+// descents read at a glance
+fn value_range(values: impl Iterator<Item = f64>, series: ChartSeries) -> Option<(f64, f64)> {
+    let (mut low, mut high) = values.fold((f64::INFINITY, f64::NEG_INFINITY), |(lo, hi), v| (lo.min(v), hi.max(v)));
+    if low > high {
+        return None;
+    }
+    if series == ChartSeries::VerticalRate {
+        low = low.min(0.0);
+        high = high.max(0.0);
+    }
+    let pad = ((high - low) * 0.1).max(1.0);
+    Some((low - pad, high + pad))
+}
+
+// One row per plotted value, long format so any number of aircraft and
+// series fit the same columns
+pub fn to_csv(lines: &[PlotLine]) -> String {
+    let mut rows: Vec<(DateTime<Utc>, &str, ChartSeries, f64)> = lines
+        .iter()
+        .flat_map(|line| line.points.iter().map(move |(time, value)| (*time, line.icao24.as_str(), line.series, *value)))
+        .collect();
+    rows.sort_by(|a, b| a.0.cmp(&b.0).then(a.1.cmp(b.1)));
+
+    let mut csv = String::from("time,icao24,series,unit,value\n");
+    for (time, icao24, series, value) in rows {
+        csv.push_str(&format!(
+            "{},{},{},{},{}\n",
+            time.to_rfc3339(),
+            icao24,
+            series.name(),
+            series.unit(),
+            value
+        ));
+    }
+    csv
+}
+
+pub fn export_csv(lines: &[PlotLine], path: &Path) -> Result<()> {
+    std::fs::write(path, to_csv(lines)).with_context(|| format!("Failed to write {}", path.display()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::aircraft::Aircraft;
+
+    fn store(start: DateTime<Utc>, home: &Location) -> TrackStore {
+        let mut store = TrackStore::new(600);
+        for i in 0..10 {
+            let fix = home.offset_by_km(0.0, i as f64);
+            let mut aircraft = Aircraft::new("aaaaaa".to_string());
+            aircraft.latitude = Some(fix.lat);
+            aircraft.longitude = Some(fix.lon);
+            aircraft.altitude = Some(1000.0 + i as f64 * 100.0);
+            aircraft.velocity = if i == 3 { None } else { Some(300.0) };
+            aircraft.time_position = Some(start + Duration::seconds(i * 60));
+            store.record(&[aircraft], start);
+        }
+        store
+    }
+
+    #[test]
+    fn test_lines_follow_view() {
+        let home = Location::london();
+        let start = Utc::now();
+        let store = store(start, &home);
+        let aircraft = vec!["aaaaaa".to_string()];
+        let extent = data_extent(&store, &aircraft).unwrap();
+        assert_eq!(extent, (start, start + Duration::seconds(540)));
+
+        let mut chart = HistoryChart::default();
+        chart.toggle_series(ChartSeries::Distance);
+        let lines = chart.lines(&store, &aircraft, &home, extent);
+        let series: Vec<ChartSeries> = lines.iter().map(|l| l.series).collect();
+        assert_eq!(series, vec![ChartSeries::Altitude, ChartSeries::GroundSpeed, ChartSeries::Distance]);
+        assert_eq!(lines[0].points.len(), 10);
+        // Points without a value are skipped
+        assert_eq!(lines[1].points.len(), 9);
+        assert!((lines[2].points[9].1 - 9.0).abs() < 0.05);
+
+        // Zooming in on the right half keeps the last fix and drops early ones
+        chart.zoom(0.5, 1.0, extent);
+        assert_eq!(chart.lines(&store, &aircraft, &home, extent)[0].points.len(), 5);
+
+        // Panning can't run past the data, and zooming out fully follows it again
+        chart.pan(-10.0, extent);
+        assert_eq!(chart.visible(extent).0, start);
+        chart.zoom(4.0, 0.5, extent);
+        assert!(!chart.is_zoomed());
+    }
+
+    #[test]
+    fn test_pins() {
+        let mut chart = HistoryChart::default();
+        for i in 0..MAX_PINNED {
+            assert!(chart.toggle_pin(&format!("{:06}", i)));
+        }
+        assert!(!chart.toggle_pin("ffffff"));
+        assert_eq!(chart.aircraft(Some("000001")).len(), MAX_PINNED);
+        assert!(chart.toggle_pin("000001"));
+        assert_eq!(chart.aircraft(Some("ffffff")).last().unwrap(), "ffffff");
+    }
+
+    #[test]
+    fn test_csv() {
+        let start = DateTime::parse_from_rfc3339("2024-05-01T12:00:00Z").unwrap().with_timezone(&Utc);
+        let lines = vec![
+            PlotLine {
+                icao24: "bbbbbb".to_string(),
+                series: ChartSeries::Altitude,
+                points: vec![(start, 1200.0)],
+            },
+            PlotLine {
+                icao24: "aaaaaa".to_string(),
+                series: ChartSeries::GroundSpeed,
+                points: vec![(start + Duration::seconds(5), 310.5), (start, 300.0)],
+            },
+        ];
+        assert_eq!(
+            to_csv(&lines),
+            "time,icao24,series,unit,value\n\
+             2024-05-01T12:00:00+00:00,aaaaaa,Ground speed,km/h,300\n\
+             2024-05-01T12:00:00+00:00,bbbbbb,Altitude,ft,1200\n\
+             2024-05-01T12:00:05+00:00,aaaaaa,Ground speed,km/h,310.5\n"
+        );
+    }
+}
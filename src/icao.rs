@@ -0,0 +1,353 @@
+// ICAO 24-bit address allocations (ICAO Annex 10, Volume III, Chapter 9).
+// Used to resolve the state of registration when the feed does not supply
+// `origin_country`, e.g. for SBS/Beast sources.
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct AddressBlock {
+    pub start: u32,
+    pub end: u32,
+    pub country: &'static str,
+    pub iso_code: &'static str,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum AddressKind {
+    Civil,
+    Military,
+    NonIcao,     // TIS-B / ADS-R anonymous or self-assigned addresses
+    Unallocated, // Valid hex but outside every allocated block
+}
+
+impl AddressKind {
+    pub fn name(&self) -> &'static str {
+        match self {
+            AddressKind::Civil => "Civil",
+            AddressKind::Military => "Military",
+            AddressKind::NonIcao => "Non-ICAO",
+            AddressKind::Unallocated => "Unallocated",
+        }
+    }
+}
+
+const fn block(start: u32, end: u32, country: &'static str, iso_code: &'static str) -> AddressBlock {
+    AddressBlock { start, end, country, iso_code }
+}
+
+// Sorted by start address, non-overlapping
+static ALLOCATIONS: &[AddressBlock] = &[
+    block(0x004000, 0x0043FF, "Zimbabwe", "ZW"),
+    block(0x006000, 0x006FFF, "Mozambique", "MZ"),
+    block(0x008000, 0x00FFFF, "South Africa", "ZA"),
+    block(0x010000, 0x017FFF, "Egypt", "EG"),
+    block(0x018000, 0x01FFFF, "Libya", "LY"),
+    block(0x020000, 0x027FFF, "Morocco", "MA"),
+    block(0x028000, 0x02FFFF, "Tunisia", "TN"),
+    block(0x030000, 0x0303FF, "Botswana", "BW"),
+    block(0x032000, 0x032FFF, "Burundi", "BI"),
+    block(0x034000, 0x034FFF, "Cameroon", "CM"),
+    block(0x035000, 0x0353FF, "Comoros", "KM"),
+    block(0x036000, 0x036FFF, "Congo", "CG"),
+    block(0x038000, 0x038FFF, "Côte d'Ivoire", "CI"),
+    block(0x03E000, 0x03EFFF, "Gabon", "GA"),
+    block(0x040000, 0x040FFF, "Ethiopia", "ET"),
+    block(0x042000, 0x042FFF, "Equatorial Guinea", "GQ"),
+    block(0x044000, 0x044FFF, "Ghana", "GH"),
+    block(0x046000, 0x046FFF, "Guinea", "GN"),
+    block(0x04C000, 0x04CFFF, "Kenya", "KE"),
+    block(0x050000, 0x050FFF, "Liberia", "LR"),
+    block(0x054000, 0x054FFF, "Madagascar", "MG"),
+    block(0x058000, 0x058FFF, "Malawi", "MW"),
+    block(0x05C000, 0x05CFFF, "Mali", "ML"),
+    block(0x062000, 0x062FFF, "Niger", "NE"),
+    block(0x064000, 0x064FFF, "Nigeria", "NG"),
+    block(0x068000, 0x068FFF, "Uganda", "UG"),
+    block(0x06C000, 0x06CFFF, "Central African Republic", "CF"),
+    block(0x06E000, 0x06EFFF, "Rwanda", "RW"),
+    block(0x070000, 0x070FFF, "Senegal", "SN"),
+    block(0x074000, 0x0743FF, "Seychelles", "SC"),
+    block(0x076000, 0x0763FF, "Sierra Leone", "SL"),
+    block(0x078000, 0x078FFF, "Somalia", "SO"),
+    block(0x07C000, 0x07CFFF, "Sudan", "SD"),
+    block(0x080000, 0x080FFF, "Tanzania", "TZ"),
+    block(0x084000, 0x084FFF, "Chad", "TD"),
+    block(0x088000, 0x088FFF, "Togo", "TG"),
+    block(0x08A000, 0x08AFFF, "Zambia", "ZM"),
+    block(0x08C000, 0x08CFFF, "DR Congo", "CD"),
+    block(0x090000, 0x090FFF, "Angola", "AO"),
+    block(0x094000, 0x0943FF, "Benin", "BJ"),
+    block(0x096000, 0x0963FF, "Cape Verde", "CV"),
+    block(0x098000, 0x0983FF, "Djibouti", "DJ"),
+    block(0x09A000, 0x09AFFF, "Gambia", "GM"),
+    block(0x09C000, 0x09CFFF, "Burkina Faso", "BF"),
+    block(0x09E000, 0x09E3FF, "Sao Tome and Principe", "ST"),
+    block(0x0A0000, 0x0A7FFF, "Algeria", "DZ"),
+    block(0x0A8000, 0x0A8FFF, "Bahamas", "BS"),
+    block(0x0AA000, 0x0AA3FF, "Barbados", "BB"),
+    block(0x0AB000, 0x0AB3FF, "Belize", "BZ"),
+    block(0x0AC000, 0x0ACFFF, "Colombia", "CO"),
+    block(0x0AE000, 0x0AEFFF, "Costa Rica", "CR"),
+    block(0x0B0000, 0x0B0FFF, "Cuba", "CU"),
+    block(0x0B2000, 0x0B2FFF, "El Salvador", "SV"),
+    block(0x0B4000, 0x0B4FFF, "Guatemala", "GT"),
+    block(0x0B6000, 0x0B6FFF, "Guyana", "GY"),
+    block(0x0B8000, 0x0B8FFF, "Haiti", "HT"),
+    block(0x0BA000, 0x0BAFFF, "Honduras", "HN"),
+    block(0x0BE000, 0x0BEFFF, "Jamaica", "JM"),
+    block(0x0C0000, 0x0C0FFF, "Nicaragua", "NI"),
+    block(0x0C2000, 0x0C2FFF, "Panama", "PA"),
+    block(0x0C4000, 0x0C4FFF, "Dominican Republic", "DO"),
+    block(0x0C6000, 0x0C6FFF, "Trinidad and Tobago", "TT"),
+    block(0x0C8000, 0x0C8FFF, "Suriname", "SR"),
+    block(0x0D0000, 0x0D7FFF, "Mexico", "MX"),
+    block(0x0D8000, 0x0DFFFF, "Venezuela", "VE"),
+    block(0x100000, 0x1FFFFF, "Russia", "RU"),
+    block(0x201000, 0x2013FF, "Namibia", "NA"),
+    block(0x202000, 0x2023FF, "Eritrea", "ER"),
+    block(0x300000, 0x33FFFF, "Italy", "IT"),
+    block(0x340000, 0x37FFFF, "Spain", "ES"),
+    block(0x380000, 0x3BFFFF, "France", "FR"),
+    block(0x3C0000, 0x3FFFFF, "Germany", "DE"),
+    block(0x400000, 0x43FFFF, "United Kingdom", "GB"),
+    block(0x440000, 0x447FFF, "Austria", "AT"),
+    block(0x448000, 0x44FFFF, "Belgium", "BE"),
+    block(0x450000, 0x457FFF, "Bulgaria", "BG"),
+    block(0x458000, 0x45FFFF, "Denmark", "DK"),
+    block(0x460000, 0x467FFF, "Finland", "FI"),
+    block(0x468000, 0x46FFFF, "Greece", "GR"),
+    block(0x470000, 0x477FFF, "Hungary", "HU"),
+    block(0x478000, 0x47FFFF, "Norway", "NO"),
+    block(0x480000, 0x487FFF, "Netherlands", "NL"),
+    block(0x488000, 0x48FFFF, "Poland", "PL"),
+    block(0x490000, 0x497FFF, "Portugal", "PT"),
+    block(0x498000, 0x49FFFF, "Czech Republic", "CZ"),
+    block(0x4A0000, 0x4A7FFF, "Romania", "RO"),
+    block(0x4A8000, 0x4AFFFF, "Sweden", "SE"),
+    block(0x4B0000, 0x4B7FFF, "Switzerland", "CH"),
+    block(0x4B8000, 0x4BFFFF, "Turkey", "TR"),
+    block(0x4C0000, 0x4C7FFF, "Serbia", "RS"),
+    block(0x4C8000, 0x4C83FF, "Cyprus", "CY"),
+    block(0x4CA000, 0x4CAFFF, "Ireland", "IE"),
+    block(0x4CC000, 0x4CCFFF, "Iceland", "IS"),
+    block(0x4D0000, 0x4D03FF, "Luxembourg", "LU"),
+    block(0x4D2000, 0x4D23FF, "Malta", "MT"),
+    block(0x4D4000, 0x4D43FF, "Monaco", "MC"),
+    block(0x500000, 0x5003FF, "San Marino", "SM"),
+    block(0x501000, 0x5013FF, "Albania", "AL"),
+    block(0x501C00, 0x501FFF, "Croatia", "HR"),
+    block(0x502C00, 0x502FFF, "Latvia", "LV"),
+    block(0x503C00, 0x503FFF, "Lithuania", "LT"),
+    block(0x504C00, 0x504FFF, "Moldova", "MD"),
+    block(0x505C00, 0x505FFF, "Slovakia", "SK"),
+    block(0x506C00, 0x506FFF, "Slovenia", "SI"),
+    block(0x507C00, 0x507FFF, "Uzbekistan", "UZ"),
+    block(0x508000, 0x50FFFF, "Ukraine", "UA"),
+    block(0x510000, 0x5103FF, "Belarus", "BY"),
+    block(0x511000, 0x5113FF, "Estonia", "EE"),
+    block(0x512000, 0x5123FF, "North Macedonia", "MK"),
+    block(0x513000, 0x5133FF, "Bosnia and Herzegovina", "BA"),
+    block(0x514000, 0x5143FF, "Georgia", "GE"),
+    block(0x515000, 0x5153FF, "Tajikistan", "TJ"),
+    block(0x516000, 0x5163FF, "Montenegro", "ME"),
+    block(0x600000, 0x6003FF, "Armenia", "AM"),
+    block(0x600800, 0x600BFF, "Azerbaijan", "AZ"),
+    block(0x601000, 0x6013FF, "Kyrgyzstan", "KG"),
+    block(0x601800, 0x601BFF, "Turkmenistan", "TM"),
+    block(0x680000, 0x6803FF, "Bhutan", "BT"),
+    block(0x681000, 0x6813FF, "Micronesia", "FM"),
+    block(0x682000, 0x6823FF, "Mongolia", "MN"),
+    block(0x683000, 0x6833FF, "Kazakhstan", "KZ"),
+    block(0x684000, 0x6843FF, "Palau", "PW"),
+    block(0x700000, 0x700FFF, "Afghanistan", "AF"),
+    block(0x702000, 0x702FFF, "Bangladesh", "BD"),
+    block(0x704000, 0x704FFF, "Myanmar", "MM"),
+    block(0x706000, 0x706FFF, "Kuwait", "KW"),
+    block(0x708000, 0x708FFF, "Laos", "LA"),
+    block(0x70A000, 0x70AFFF, "Nepal", "NP"),
+    block(0x70C000, 0x70C3FF, "Oman", "OM"),
+    block(0x70E000, 0x70EFFF, "Cambodia", "KH"),
+    block(0x710000, 0x717FFF, "Saudi Arabia", "SA"),
+    block(0x718000, 0x71FFFF, "South Korea", "KR"),
+    block(0x720000, 0x727FFF, "North Korea", "KP"),
+    block(0x728000, 0x72FFFF, "Iraq", "IQ"),
+    block(0x730000, 0x737FFF, "Iran", "IR"),
+    block(0x738000, 0x73FFFF, "Israel", "IL"),
+    block(0x740000, 0x747FFF, "Jordan", "JO"),
+    block(0x748000, 0x74FFFF, "Lebanon", "LB"),
+    block(0x750000, 0x757FFF, "Malaysia", "MY"),
+    block(0x758000, 0x75FFFF, "Philippines", "PH"),
+    block(0x760000, 0x767FFF, "Pakistan", "PK"),
+    block(0x768000, 0x76FFFF, "Singapore", "SG"),
+    block(0x770000, 0x777FFF, "Sri Lanka", "LK"),
+    block(0x778000, 0x77FFFF, "Syria", "SY"),
+    block(0x780000, 0x7BFFFF, "China", "CN"),
+    block(0x7C0000, 0x7FFFFF, "Australia", "AU"),
+    block(0x800000, 0x83FFFF, "India", "IN"),
+    block(0x840000, 0x87FFFF, "Japan", "JP"),
+    block(0x880000, 0x887FFF, "Thailand", "TH"),
+    block(0x888000, 0x88FFFF, "Vietnam", "VN"),
+    block(0x890000, 0x890FFF, "Yemen", "YE"),
+    block(0x894000, 0x894FFF, "Bahrain", "BH"),
+    block(0x895000, 0x8953FF, "Brunei", "BN"),
+    block(0x896000, 0x896FFF, "United Arab Emirates", "AE"),
+    block(0x897000, 0x8973FF, "Solomon Islands", "SB"),
+    block(0x898000, 0x898FFF, "Papua New Guinea", "PG"),
+    block(0x899000, 0x8993FF, "Taiwan", "TW"),
+    block(0x8A0000, 0x8A7FFF, "Indonesia", "ID"),
+    block(0x900000, 0x9003FF, "Marshall Islands", "MH"),
+    block(0x901000, 0x9013FF, "Cook Islands", "CK"),
+    block(0x902000, 0x9023FF, "Samoa", "WS"),
+    block(0xA00000, 0xAFFFFF, "United States", "US"),
+    block(0xC00000, 0xC3FFFF, "Canada", "CA"),
+    block(0xC80000, 0xC87FFF, "New Zealand", "NZ"),
+    block(0xC88000, 0xC88FFF, "Fiji", "FJ"),
+    block(0xC8A000, 0xC8A3FF, "Nauru", "NR"),
+    block(0xC8C000, 0xC8C3FF, "Saint Lucia", "LC"),
+    block(0xC8D000, 0xC8D3FF, "Tonga", "TO"),
+    block(0xC8E000, 0xC8E3FF, "Kiribati", "KI"),
+    block(0xC90000, 0xC903FF, "Vanuatu", "VU"),
+    block(0xE00000, 0xE3FFFF, "Argentina", "AR"),
+    block(0xE40000, 0xE7FFFF, "Brazil", "BR"),
+    block(0xE80000, 0xE80FFF, "Chile", "CL"),
+    block(0xE84000, 0xE84FFF, "Ecuador", "EC"),
+    block(0xE88000, 0xE88FFF, "Paraguay", "PY"),
+    block(0xE8C000, 0xE8CFFF, "Peru", "PE"),
+    block(0xE90000, 0xE90FFF, "Uruguay", "UY"),
+    block(0xE94000, 0xE94FFF, "Bolivia", "BO"),
+    block(0xF00000, 0xF07FFF, "ICAO (temporary)", "XX"),
+    block(0xF09000, 0xF093FF, "ICAO (special use)", "XX"),
+];
+
+// Sub-blocks commonly reserved for state/military aircraft
+static MILITARY_RANGES: &[(u32, u32)] = &[
+    (0x010070, 0x01008F), // Egypt
+    (0x0A4000, 0x0A4FFF), // Algeria
+    (0x33FF00, 0x33FFFF), // Italy
+    (0x350000, 0x37FFFF), // Spain
+    (0x3AA000, 0x3AFFFF), // France
+    (0x3B7000, 0x3BFFFF), // France
+    (0x3EA000, 0x3EBFFF), // Germany
+    (0x3F4000, 0x3FBFFF), // Germany
+    (0x400000, 0x40003F), // United Kingdom
+    (0x43C000, 0x43CFFF), // United Kingdom
+    (0x444000, 0x446FFF), // Austria
+    (0x44F000, 0x44FFFF), // Belgium
+    (0x457000, 0x457FFF), // Bulgaria
+    (0x45F400, 0x45F4FF), // Denmark
+    (0x468000, 0x4683FF), // Greece
+    (0x473C00, 0x473C0F), // Hungary
+    (0x478100, 0x4781FF), // Norway
+    (0x480000, 0x480FFF), // Netherlands
+    (0x48D800, 0x48D87F), // Poland
+    (0x497C00, 0x497CFF), // Portugal
+    (0x498420, 0x49842F), // Czech Republic
+    (0x4B7000, 0x4B7FFF), // Switzerland
+    (0x4B8200, 0x4B82FF), // Turkey
+    (0x506F00, 0x506FFF), // Slovenia
+    (0x70C070, 0x70C07F), // Oman
+    (0x710258, 0x71028F), // Saudi Arabia
+    (0x710380, 0x71039F), // Saudi Arabia
+    (0x738A00, 0x738AFF), // Israel
+    (0x7C822E, 0x7C84FF), // Australia
+    (0x7C8800, 0x7C88FF), // Australia
+    (0x7C9000, 0x7CBFFF), // Australia
+    (0x7CF800, 0x7CFAFF), // Australia
+    (0x7D0000, 0x7FFFFF), // Australia
+    (0x800200, 0x8002FF), // India
+    (0xADF7C8, 0xAFFFFF), // United States
+    (0xC20000, 0xC3FFFF), // Canada
+    (0xE40000, 0xE41FFF), // Brazil
+];
+
+pub fn parse_address(icao24: &str) -> Option<u32> {
+    let hex = icao24.trim();
+    if hex.len() != 6 {
+        return None;
+    }
+    u32::from_str_radix(hex, 16).ok()
+}
+
+pub fn lookup(address: u32) -> Option<&'static AddressBlock> {
+    let idx = ALLOCATIONS.partition_point(|b| b.start <= address);
+    if idx == 0 {
+        return None;
+    }
+    let block = &ALLOCATIONS[idx - 1];
+    if address <= block.end {
+        Some(block)
+    } else {
+        None
+    }
+}
+
+pub fn is_military(address: u32) -> bool {
+    MILITARY_RANGES
+        .iter()
+        .any(|&(start, end)| address >= start && address <= end)
+}
+
+// dump1090-style feeds prefix non-ICAO (TIS-B/ADS-R) addresses with '~'
+pub fn classify(icao24: &str) -> AddressKind {
+    let trimmed = icao24.trim();
+    if trimmed.starts_with('~') {
+        return AddressKind::NonIcao;
+    }
+
+    match parse_address(trimmed) {
+        None | Some(0x000000) | Some(0xFFFFFF) => AddressKind::NonIcao,
+        Some(address) if is_military(address) => AddressKind::Military,
+        Some(address) if lookup(address).is_some() => AddressKind::Civil,
+        Some(_) => AddressKind::Unallocated,
+    }
+}
+
+// Converts an ISO 3166 alpha-2 code into regional indicator symbols
+pub fn flag_emoji(iso_code: &str) -> Option<String> {
+    if iso_code.len() != 2 || !iso_code.chars().all(|c| c.is_ascii_alphabetic()) {
+        return None;
+    }
+    if iso_code.eq_ignore_ascii_case("XX") {
+        return None;
+    }
+
+    iso_code
+        .to_ascii_uppercase()
+        .chars()
+        .map(|c| char::from_u32(0x1F1E6 + (c as u32 - 'A' as u32)))
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_allocations_sorted_and_disjoint() {
+        for pair in ALLOCATIONS.windows(2) {
+            assert!(pair[0].start <= pair[0].end);
+            assert!(pair[0].end < pair[1].start, "{} overlaps {}", pair[0].country, pair[1].country);
+        }
+    }
+
+    #[test]
+    fn test_lookup_country() {
+        assert_eq!(lookup(0xA12345).unwrap().iso_code, "US");
+        assert_eq!(lookup(0x406A3B).unwrap().iso_code, "GB");
+        assert_eq!(lookup(0x3C6444).unwrap().iso_code, "DE");
+        assert!(lookup(0xB67890).is_none());
+    }
+
+    #[test]
+    fn test_classify_address() {
+        assert_eq!(classify("a12345"), AddressKind::Civil);
+        assert_eq!(classify("AE1234"), AddressKind::Military);
+        assert_eq!(classify("~1a2b3c"), AddressKind::NonIcao);
+        assert_eq!(classify("000000"), AddressKind::NonIcao);
+        assert_eq!(classify("b67890"), AddressKind::Unallocated);
+    }
+
+    #[test]
+    fn test_flag_emoji() {
+        assert_eq!(flag_emoji("gb").as_deref(), Some("🇬🇧"));
+        assert!(flag_emoji("XX").is_none());
+    }
+}
@@ -0,0 +1,288 @@
+use crate::aircraft::Aircraft;
+use crate::config::CallsignDisplay;
+use egui::{Pos2, Rect, Vec2};
+use serde::{Deserialize, Serialize};
+
+const KMH_PER_KNOT: f64 = 1.852;
+const TREND_THRESHOLD_FPM: f64 = 200.0;
+// Extra cost for a label moving to a different slot than last frame, so
+// labels don't flicker between equally good positions
+const SLOT_CHANGE_COST: f32 = 40.0;
+const SLOT_PREFERENCE_COST: f32 = 2.0;
+
+// Leader directions in order of preference, screen coordinates (y down)
+const SLOTS: [(f32, f32); 8] = [
+    (1.0, -1.0),
+    (1.0, 1.0),
+    (-1.0, -1.0),
+    (-1.0, 1.0),
+    (1.0, 0.0),
+    (-1.0, 0.0),
+    (0.0, -1.0),
+    (0.0, 1.0),
+];
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+pub enum DataBlockField {
+    Callsign,
+    Type,
+    FlightLevel,
+    VerticalTrend,
+    GroundSpeed,
+    Squawk,
+}
+
+impl DataBlockField {
+    pub const ALL: [DataBlockField; 6] = [
+        DataBlockField::Callsign,
+        DataBlockField::Type,
+        DataBlockField::FlightLevel,
+        DataBlockField::VerticalTrend,
+        DataBlockField::GroundSpeed,
+        DataBlockField::Squawk,
+    ];
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            DataBlockField::Callsign => "Callsign",
+            DataBlockField::Type => "Type",
+            DataBlockField::FlightLevel => "Flight level",
+            DataBlockField::VerticalTrend => "Vertical trend",
+            DataBlockField::GroundSpeed => "Ground speed",
+            DataBlockField::Squawk => "Squawk",
+        }
+    }
+
+    fn line(&self) -> usize {
+        match self {
+            DataBlockField::Callsign | DataBlockField::Type => 0,
+            DataBlockField::FlightLevel | DataBlockField::VerticalTrend => 1,
+            DataBlockField::GroundSpeed | DataBlockField::Squawk => 2,
+        }
+    }
+
+    fn text(&self, aircraft: &Aircraft, display: CallsignDisplay) -> Option<String> {
+        match self {
+            DataBlockField::Callsign => Some(aircraft.label(display)).filter(|l| !l.is_empty()),
+            DataBlockField::Type => aircraft.category_code().map(str::to_string),
+            DataBlockField::FlightLevel => {
+                if aircraft.on_ground == Some(true) {
+                    return Some("GND".to_string());
+                }
+                aircraft.altitude.map(|ft| format!("{:03.0}", ft / 100.0))
+            }
+            DataBlockField::VerticalTrend => match aircraft.vertical_rate? {
+                rate if rate >= TREND_THRESHOLD_FPM => Some("↑".to_string()),
+                rate if rate <= -TREND_THRESHOLD_FPM => Some("↓".to_string()),
+                _ => None,
+            },
+            DataBlockField::GroundSpeed => aircraft.velocity.map(|kmh| format!("{:.0}kt", kmh / KMH_PER_KNOT)),
+            DataBlockField::Squawk => aircraft.squawk.clone(),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct DataBlockSettings {
+    pub fields: Vec<DataBlockField>,
+    pub leader_px: f32,
+    pub declutter: bool,
+}
+
+impl Default for DataBlockSettings {
+    fn default() -> Self {
+        Self {
+            fields: vec![
+                DataBlockField::Callsign,
+                DataBlockField::FlightLevel,
+                DataBlockField::VerticalTrend,
+                DataBlockField::GroundSpeed,
+            ],
+            leader_px: 24.0,
+            declutter: true,
+        }
+    }
+}
+
+impl DataBlockSettings {
+    pub fn has(&self, field: DataBlockField) -> bool {
+        self.fields.contains(&field)
+    }
+
+    pub fn toggle(&mut self, field: DataBlockField, enabled: bool) {
+        self.fields.retain(|f| *f != field);
+        if enabled {
+            self.fields.push(field);
+        }
+    }
+
+    // Up to three lines: identity, level and trend, speed and squawk. The
+    // trend arrow sits directly after the level as on a scope.
+    pub fn text(&self, aircraft: &Aircraft, display: CallsignDisplay) -> String {
+        let mut lines = [String::new(), String::new(), String::new()];
+        for field in DataBlockField::ALL.iter().filter(|f| self.has(**f)) {
+            let Some(text) = field.text(aircraft, display) else {
+                continue;
+            };
+            let line = &mut lines[field.line()];
+            if !line.is_empty() && *field != DataBlockField::VerticalTrend {
+                line.push(' ');
+            }
+            line.push_str(&text);
+        }
+        lines
+            .into_iter()
+            .filter(|l| !l.is_empty())
+            .collect::<Vec<_>>()
+            .join("\n")
+    }
+}
+
+pub struct LabelRequest<'a> {
+    pub id: &'a str,
+    pub anchor: Pos2,
+    pub size: Vec2,
+    // Offset of the label's top-left corner from the anchor, set by dragging
+    pub manual_offset: Option<Vec2>,
+    pub previous_slot: Option<usize>,
+}
+
+#[derive(Debug, Clone)]
+pub struct LabelPlacement {
+    pub id: String,
+    pub anchor: Pos2,
+    pub rect: Rect,
+    pub slot: Option<usize>,
+}
+
+// Greedy placement: labels are placed in request order, each taking the
+// candidate slot that overlaps least with labels already placed and with
+// `obstacles` (usually the aircraft symbols). Put important labels first.
+pub fn place_labels(requests: &[LabelRequest], leader_px: f32, obstacles: &[Rect], declutter: bool) -> Vec<LabelPlacement> {
+    let mut placed: Vec<LabelPlacement> = Vec::with_capacity(requests.len());
+
+    for request in requests {
+        if let Some(offset) = request.manual_offset {
+            placed.push(LabelPlacement {
+                id: request.id.to_string(),
+                anchor: request.anchor,
+                rect: Rect::from_min_size(request.anchor + offset, request.size),
+                slot: None,
+            });
+            continue;
+        }
+
+        let candidates = if declutter { SLOTS.len() } else { 1 };
+        let (slot, rect) = (0..candidates)
+            .map(|slot| {
+                let rect = slot_rect(request.anchor, request.size, slot, leader_px);
+                let overlap: f32 = placed
+                    .iter()
+                    .map(|p| &p.rect)
+                    .chain(obstacles.iter().filter(|o| !o.contains(request.anchor)))
+                    .map(|other| overlap_area(&rect, other))
+                    .sum();
+                let mut cost = overlap + slot as f32 * SLOT_PREFERENCE_COST;
+                if request.previous_slot.is_some_and(|previous| previous != slot) {
+                    cost += SLOT_CHANGE_COST;
+                }
+                (slot, rect, cost)
+            })
+            .min_by(|a, b| a.2.total_cmp(&b.2))
+            .map(|(slot, rect, _)| (slot, rect))
+            .unwrap_or((0, slot_rect(request.anchor, request.size, 0, leader_px)));
+
+        placed.push(LabelPlacement {
+            id: request.id.to_string(),
+            anchor: request.anchor,
+            rect,
+            slot: Some(slot),
+        });
+    }
+    placed
+}
+
+fn slot_rect(anchor: Pos2, size: Vec2, slot: usize, leader_px: f32) -> Rect {
+    let (dx, dy) = SLOTS[slot];
+    let direction = Vec2::new(dx, dy).normalized();
+    let end = anchor + direction * leader_px;
+
+    // The leader meets the label at the corner or edge facing the anchor
+    let min_x = match dx {
+        d if d > 0.0 => end.x,
+        d if d < 0.0 => end.x - size.x,
+        _ => end.x - size.x / 2.0,
+    };
+    let min_y = match dy {
+        d if d > 0.0 => end.y,
+        d if d < 0.0 => end.y - size.y,
+        _ => end.y - size.y / 2.0,
+    };
+    Rect::from_min_size(Pos2::new(min_x, min_y), size)
+}
+
+fn overlap_area(a: &Rect, b: &Rect) -> f32 {
+    let intersection = a.intersect(*b);
+    if intersection.is_positive() {
+        intersection.area()
+    } else {
+        0.0
+    }
+}
+
+// Point on the label's border where the leader line ends
+pub fn leader_end(placement: &LabelPlacement) -> Pos2 {
+    placement.rect.clamp(placement.anchor)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn request(id: &str, anchor: Pos2) -> LabelRequest {
+        LabelRequest {
+            id,
+            anchor,
+            size: Vec2::new(60.0, 30.0),
+            manual_offset: None,
+            previous_slot: None,
+        }
+    }
+
+    #[test]
+    fn test_data_block_text() {
+        let mut aircraft = Aircraft::new("a12345".to_string());
+        aircraft.callsign = Some("BAW123".to_string());
+        aircraft.altitude = Some(35000.0);
+        aircraft.vertical_rate = Some(-1500.0);
+        aircraft.velocity = Some(833.4);
+
+        let text = DataBlockSettings::default().text(&aircraft, CallsignDisplay::Callsign);
+        assert_eq!(text, "BAW123\n350↓\n450kt");
+    }
+
+    #[test]
+    fn test_close_labels_do_not_overlap() {
+        let requests = [request("a", Pos2::new(100.0, 100.0)), request("b", Pos2::new(110.0, 100.0))];
+        let placed = place_labels(&requests, 20.0, &[], true);
+        assert_eq!(placed.len(), 2);
+        assert!(!placed[0].rect.intersects(placed[1].rect));
+
+        // Without decluttering both take the preferred slot and collide
+        let placed = place_labels(&requests, 20.0, &[], false);
+        assert!(placed[0].rect.intersects(placed[1].rect));
+    }
+
+    #[test]
+    fn test_manual_offset_and_stability() {
+        let mut dragged = request("a", Pos2::new(100.0, 100.0));
+        dragged.manual_offset = Some(Vec2::new(-80.0, 10.0));
+        let placed = place_labels(&[dragged], 20.0, &[], true);
+        assert_eq!(placed[0].rect.min, Pos2::new(20.0, 110.0));
+        assert_eq!(placed[0].slot, None);
+
+        let mut stable = request("a", Pos2::new(100.0, 100.0));
+        stable.previous_slot = Some(3);
+        assert_eq!(place_labels(&[stable], 20.0, &[], true)[0].slot, Some(3));
+    }
+}
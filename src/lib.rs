@@ -1,9 +1,38 @@
 pub mod app;
 pub mod aircraft;
+pub mod aircraft_details;
+pub mod aircraft_query;
+pub mod airline;
+pub mod airports;
+pub mod airspace;
+pub mod alerts;
 pub mod api;
+pub mod basemap;
 pub mod config;
+pub mod conflict;
+pub mod cpa;
+pub mod filter_expr;
+pub mod flight_phase;
+pub mod format;
 pub mod geo;
+pub mod geofence;
+pub mod geojson;
+pub mod heatmap;
+pub mod history_chart;
+pub mod icao;
+pub mod labels;
+pub mod map_layers;
+pub mod measure;
+pub mod prediction;
+pub mod profile_view;
+pub mod projection;
 pub mod radar_view;
+pub mod shapefile;
+pub mod spatial;
+pub mod sweep;
 pub mod theme;
+pub mod track_store;
+pub mod watchlist;
+pub mod webhook;
 
 pub use app::SkyRadarApp; 
\ No newline at end of file
@@ -0,0 +1,200 @@
+use crate::geo::KM_PER_DEG_LAT;
+use crate::geojson;
+use crate::shapefile;
+use anyhow::Result;
+use geo::{BoundingRect, Geometry, LineString, Rect, Simplify};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use tracing::warn;
+
+// Lines are simplified to within this many pixels of the original
+const SIMPLIFY_TOLERANCE_PX: f64 = 0.75;
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct MapLayerConfig {
+    pub name: String,
+    pub path: PathBuf,
+    pub color: [u8; 3],
+    pub width: f32,
+    pub visible: bool,
+}
+
+impl MapLayerConfig {
+    pub fn new(path: PathBuf) -> Self {
+        let name = path
+            .file_stem()
+            .map(|s| s.to_string_lossy().into_owned())
+            .unwrap_or_else(|| "Layer".to_string());
+        Self {
+            name,
+            path,
+            color: [90, 110, 90],
+            width: 1.0,
+            visible: true,
+        }
+    }
+}
+
+struct SimplifiedLine {
+    line: LineString<f64>,
+    bounds: Rect<f64>,
+}
+
+// Line work from one file in lon/lat. Polygons contribute their rings and
+// points are dropped. Simplified copies are built lazily per zoom level.
+pub struct MapLayer {
+    lines: Vec<LineString<f64>>,
+    levels: HashMap<i32, Vec<SimplifiedLine>>,
+}
+
+impl MapLayer {
+    pub fn load(path: &Path) -> Result<Self> {
+        let is_shapefile = path
+            .extension()
+            .and_then(|e| e.to_str())
+            .is_some_and(|e| e.eq_ignore_ascii_case("shp"));
+
+        let geometries = if is_shapefile {
+            shapefile::read_file(path)?
+        } else {
+            geojson::read_file(path)?.into_iter().map(|f| f.geometry).collect()
+        };
+        Ok(Self::from_geometries(geometries))
+    }
+
+    pub fn from_geometries(geometries: impl IntoIterator<Item = Geometry<f64>>) -> Self {
+        let mut lines = Vec::new();
+        for geometry in geometries {
+            collect_lines(geometry, &mut lines);
+        }
+        lines.retain(|l| l.0.len() >= 2);
+        Self {
+            lines,
+            levels: HashMap::new(),
+        }
+    }
+
+    pub fn line_count(&self) -> usize {
+        self.lines.len()
+    }
+
+    // Lines intersecting `view` (lon/lat), simplified for the given scale.
+    // Scales are bucketed into half-octave levels so zooming doesn't
+    // re-simplify on every frame.
+    pub fn lines_in_view(&mut self, pixels_per_km: f64, view: Rect<f64>) -> impl Iterator<Item = &LineString<f64>> {
+        let level = zoom_level(pixels_per_km);
+        let lines = &self.lines;
+        let simplified = self.levels.entry(level).or_insert_with(|| {
+            let level_px_per_km = 2f64.powf(level as f64 / 2.0);
+            let epsilon_deg = SIMPLIFY_TOLERANCE_PX / level_px_per_km / KM_PER_DEG_LAT;
+            lines
+                .iter()
+                .filter_map(|line| {
+                    let line = line.simplify(&epsilon_deg);
+                    let bounds = line.bounding_rect()?;
+                    Some(SimplifiedLine { line, bounds })
+                })
+                .collect()
+        });
+
+        simplified
+            .iter()
+            .filter(move |s| intersects(&s.bounds, &view))
+            .map(|s| &s.line)
+    }
+}
+
+fn zoom_level(pixels_per_km: f64) -> i32 {
+    (pixels_per_km.max(1e-6).log2() * 2.0).floor() as i32
+}
+
+fn intersects(a: &Rect<f64>, b: &Rect<f64>) -> bool {
+    a.min().x <= b.max().x && a.max().x >= b.min().x && a.min().y <= b.max().y && a.max().y >= b.min().y
+}
+
+fn collect_lines(geometry: Geometry<f64>, out: &mut Vec<LineString<f64>>) {
+    match geometry {
+        Geometry::Line(line) => out.push(LineString::from(vec![line.start, line.end])),
+        Geometry::LineString(line) => out.push(line),
+        Geometry::MultiLineString(lines) => out.extend(lines.0),
+        Geometry::Polygon(polygon) => {
+            let (exterior, interiors) = polygon.into_inner();
+            out.push(exterior);
+            out.extend(interiors);
+        }
+        Geometry::MultiPolygon(polygons) => {
+            for polygon in polygons {
+                collect_lines(Geometry::Polygon(polygon), out);
+            }
+        }
+        Geometry::Rect(rect) => collect_lines(Geometry::Polygon(rect.to_polygon()), out),
+        Geometry::Triangle(triangle) => collect_lines(Geometry::Polygon(triangle.to_polygon()), out),
+        Geometry::GeometryCollection(collection) => {
+            for geometry in collection {
+                collect_lines(geometry, out);
+            }
+        }
+        Geometry::Point(_) | Geometry::MultiPoint(_) => {}
+    }
+}
+
+// Layers that fail to load are logged and skipped so one bad file doesn't
+// hide the rest
+pub fn load_layers(configs: &[MapLayerConfig]) -> HashMap<PathBuf, MapLayer> {
+    configs
+        .iter()
+        .filter_map(|config| match MapLayer::load(&config.path) {
+            Ok(layer) => Some((config.path.clone(), layer)),
+            Err(e) => {
+                warn!("Failed to load map layer {}: {}", config.path.display(), e);
+                None
+            }
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use geo::{coord, Polygon};
+
+    fn wiggly_line() -> LineString<f64> {
+        // 1 km steps east with a 10 m zigzag
+        (0..100)
+            .map(|i| (i as f64 * 0.009, 51.0 + if i % 2 == 0 { 0.0 } else { 0.00009 }))
+            .collect::<Vec<_>>()
+            .into()
+    }
+
+    fn world() -> Rect<f64> {
+        Rect::new(coord! { x: -180.0, y: -90.0 }, coord! { x: 180.0, y: 90.0 })
+    }
+
+    #[test]
+    fn test_simplifies_per_zoom() {
+        let mut layer = MapLayer::from_geometries([Geometry::LineString(wiggly_line())]);
+
+        let zoomed_out = layer.lines_in_view(0.5, world()).next().unwrap().0.len();
+        let zoomed_in = layer.lines_in_view(500.0, world()).next().unwrap().0.len();
+        assert!(zoomed_out < 10);
+        assert_eq!(zoomed_in, 100);
+    }
+
+    #[test]
+    fn test_polygons_become_rings_and_view_culls() {
+        let square = Polygon::new(
+            LineString::from(vec![(10.0, 10.0), (11.0, 10.0), (11.0, 11.0), (10.0, 10.0)]),
+            Vec::new(),
+        );
+        let mut layer = MapLayer::from_geometries([
+            Geometry::Polygon(square),
+            Geometry::LineString(wiggly_line()),
+            Geometry::Point(geo::Point::new(0.0, 0.0)),
+        ]);
+        assert_eq!(layer.line_count(), 2);
+
+        let view = Rect::new(coord! { x: 9.0, y: 9.0 }, coord! { x: 12.0, y: 12.0 });
+        assert_eq!(layer.lines_in_view(10.0, view).count(), 1);
+    }
+}
@@ -0,0 +1,156 @@
+use crate::aircraft::Aircraft;
+use crate::cpa::velocity_km_s;
+use crate::format::format_duration;
+use crate::geo::Location;
+
+const KM_PER_NM: f64 = 1.852;
+
+// One end of a range-and-bearing line. Aircraft ends follow the aircraft as
+// it moves between updates.
+#[derive(Debug, Clone, PartialEq)]
+pub enum MeasureEnd {
+    Point(Location),
+    Aircraft(String),
+}
+
+impl MeasureEnd {
+    fn location(&self, aircraft: &[Aircraft]) -> Option<Location> {
+        match self {
+            MeasureEnd::Point(location) => Some(location.clone()),
+            MeasureEnd::Aircraft(icao24) => find(aircraft, icao24)?.position().map(Location::from_point),
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Measurement {
+    pub from: MeasureEnd,
+    pub to: MeasureEnd,
+}
+
+#[derive(Debug, Clone)]
+pub struct MeasureReading {
+    pub from: Location,
+    pub to: Location,
+    pub distance_km: f64,
+    pub bearing_deg: f64,
+    // Time for the `from` aircraft to cover the distance at its ground speed
+    pub time_to_go_secs: Option<f64>,
+    // Time until two aircraft meet at their current closure rate
+    pub closing_secs: Option<f64>,
+}
+
+impl MeasureReading {
+    pub fn distance_nm(&self) -> f64 {
+        self.distance_km / KM_PER_NM
+    }
+
+    pub fn summary(&self) -> String {
+        let mut text = format!(
+            "{:.1} km / {:.1} nm  {:03.0}°",
+            self.distance_km,
+            self.distance_nm(),
+            self.bearing_deg
+        );
+        if let Some(secs) = self.time_to_go_secs {
+            text.push_str(&format!("\nTTG {}", format_duration(secs)));
+        }
+        if let Some(secs) = self.closing_secs {
+            text.push_str(&format!("\nClosing {}", format_duration(secs)));
+        }
+        text
+    }
+}
+
+impl Measurement {
+    pub fn reading(&self, aircraft: &[Aircraft]) -> Option<MeasureReading> {
+        let from = self.from.location(aircraft)?;
+        let to = self.to.location(aircraft)?;
+        let distance_km = from.distance_to(&to);
+
+        let from_aircraft = match &self.from {
+            MeasureEnd::Aircraft(icao24) => find(aircraft, icao24),
+            MeasureEnd::Point(_) => None,
+        };
+        let to_aircraft = match &self.to {
+            MeasureEnd::Aircraft(icao24) => find(aircraft, icao24),
+            MeasureEnd::Point(_) => None,
+        };
+
+        let time_to_go_secs = from_aircraft
+            .and_then(|a| a.velocity)
+            .filter(|speed| *speed > 0.0)
+            .map(|speed| distance_km / (speed / 3600.0));
+
+        // Rate at which the range is shrinking, from the relative velocity
+        // along the line between the two
+        let closing_secs = from_aircraft.zip(to_aircraft).and_then(|(a, b)| {
+            let (ax, ay) = velocity_km_s(a)?;
+            let (bx, by) = velocity_km_s(b)?;
+            let (dx, dy) = from.offset_km_to(&to);
+            let closure = ((ax - bx) * dx + (ay - by) * dy) / distance_km.max(f64::EPSILON);
+            (closure > 0.0).then(|| distance_km / closure)
+        });
+
+        Some(MeasureReading {
+            bearing_deg: from.bearing_to(&to),
+            from,
+            to,
+            distance_km,
+            time_to_go_secs,
+            closing_secs,
+        })
+    }
+}
+
+fn find<'a>(aircraft: &'a [Aircraft], icao24: &str) -> Option<&'a Aircraft> {
+    aircraft.iter().find(|a| a.icao24 == icao24)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn aircraft(icao24: &str, location: &Location, track: f64) -> Aircraft {
+        let mut aircraft = Aircraft::new(icao24.to_string());
+        aircraft.latitude = Some(location.lat);
+        aircraft.longitude = Some(location.lon);
+        aircraft.velocity = Some(720.0); // 0.2 km/s
+        aircraft.true_track = Some(track);
+        aircraft
+    }
+
+    #[test]
+    fn test_point_to_point() {
+        let home = Location::london();
+        let east = home.point_at_distance(18.52, 90.0);
+        let measurement = Measurement {
+            from: MeasureEnd::Point(home),
+            to: MeasureEnd::Point(east),
+        };
+        let reading = measurement.reading(&[]).unwrap();
+
+        assert!((reading.distance_nm() - 10.0).abs() < 0.01);
+        assert!((reading.bearing_deg - 90.0).abs() < 0.1);
+        assert!(reading.time_to_go_secs.is_none());
+    }
+
+    #[test]
+    fn test_aircraft_pair_times() {
+        let home = Location::london();
+        let other = home.point_at_distance(20.0, 90.0);
+        let traffic = vec![aircraft("aaaaaa", &home, 90.0), aircraft("bbbbbb", &other, 270.0)];
+        let measurement = Measurement {
+            from: MeasureEnd::Aircraft("aaaaaa".to_string()),
+            to: MeasureEnd::Aircraft("bbbbbb".to_string()),
+        };
+        let reading = measurement.reading(&traffic).unwrap();
+
+        assert!((reading.time_to_go_secs.unwrap() - 100.0).abs() < 1.0);
+        // Head-on at 0.4 km/s combined
+        assert!((reading.closing_secs.unwrap() - 50.0).abs() < 1.0);
+
+        // Missing aircraft means no reading
+        assert!(measurement.reading(&traffic[..1]).is_none());
+    }
+}
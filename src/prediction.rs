@@ -0,0 +1,186 @@
+use crate::aircraft::{Aircraft, AircraftTrail};
+use crate::cpa::velocity_km_s;
+use crate::geo::Location;
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+
+// Standard rate turn; anything faster is treated as noise in the trail
+const MAX_TURN_RATE_DEG_S: f64 = 3.0;
+const MIN_TURN_RATE_DEG_S: f64 = 0.1;
+// Trail segments shorter than this give unreliable bearings
+const MIN_SEGMENT_KM: f64 = 0.2;
+const TURN_HISTORY_SEGMENTS: usize = 4;
+const STEP_SECS: f64 = 5.0;
+// Dead reckoning beyond this is more likely wrong than helpful
+const MAX_EXTRAPOLATION_SECS: f64 = 60.0;
+
+pub const TICK_MINUTES: [f64; 3] = [1.0, 2.0, 5.0];
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct SpeedLeaderSettings {
+    pub enabled: bool,
+    pub minutes: f64,
+    pub show_ticks: bool,
+    pub use_turn_rate: bool,
+}
+
+impl Default for SpeedLeaderSettings {
+    fn default() -> Self {
+        Self {
+            enabled: true,
+            minutes: 2.0,
+            show_ticks: true,
+            use_turn_rate: true,
+        }
+    }
+}
+
+// Turn rate in degrees per second (positive = right) from the change in
+// track between recent trail segments
+pub fn estimate_turn_rate(trail: &AircraftTrail) -> Option<f64> {
+    let fixes: Vec<(Location, f64)> = trail
+        .samples()
+        .iter()
+        .map(|s| (Location::from_point(s.pos), s.time.timestamp_millis() as f64 / 1000.0))
+        .collect();
+
+    // Segments long enough to have a meaningful bearing, most recent last
+    let mut segments = Vec::new();
+    let mut start = fixes.first()?;
+    for fix in &fixes[1..] {
+        if start.0.distance_to(&fix.0) >= MIN_SEGMENT_KM && fix.1 > start.1 {
+            segments.push((start.0.bearing_to(&fix.0), (start.1 + fix.1) / 2.0));
+            start = fix;
+        }
+    }
+    let recent = &segments[segments.len().saturating_sub(TURN_HISTORY_SEGMENTS)..];
+    if recent.len() < 2 {
+        return None;
+    }
+
+    let (first, last) = (recent[0], recent[recent.len() - 1]);
+    let turned: f64 = recent.windows(2).map(|w| heading_change(w[0].0, w[1].0)).sum();
+    let rate = turned / (last.1 - first.1);
+
+    (rate.abs() >= MIN_TURN_RATE_DEG_S).then(|| rate.clamp(-MAX_TURN_RATE_DEG_S, MAX_TURN_RATE_DEG_S))
+}
+
+// Signed smallest difference between two headings
+fn heading_change(from: f64, to: f64) -> f64 {
+    (to - from + 540.0).rem_euclid(360.0) - 180.0
+}
+
+// Positions every STEP_SECS along the predicted path, starting at the
+// current position, assuming constant speed and turn rate
+pub fn predict_path(aircraft: &Aircraft, turn_rate_deg_s: f64, seconds: f64) -> Option<Vec<Location>> {
+    let mut position = Location::from_point(aircraft.position()?);
+    let speed_km_s = aircraft.velocity? / 3600.0;
+    let mut heading = aircraft.true_track?;
+
+    let steps = (seconds / STEP_SECS).ceil().max(0.0) as usize;
+    let mut path = Vec::with_capacity(steps + 1);
+    path.push(position.clone());
+    for step in 0..steps {
+        let dt = STEP_SECS.min(seconds - step as f64 * STEP_SECS);
+        // Midpoint heading keeps the arc accurate with coarse steps
+        let mid_heading = (heading + turn_rate_deg_s * dt / 2.0).to_radians();
+        position = position.offset_by_km(speed_km_s * dt * mid_heading.sin(), speed_km_s * dt * mid_heading.cos());
+        heading += turn_rate_deg_s * dt;
+        path.push(position.clone());
+    }
+    Some(path)
+}
+
+// Dead-reckoned position at `now` from the last report, along the current
+// track at the current speed
+pub fn extrapolate(aircraft: &Aircraft, now: DateTime<Utc>) -> Option<Location> {
+    let position = Location::from_point(aircraft.position()?);
+    let Some(reported) = aircraft.time_position else {
+        return Some(position);
+    };
+    let elapsed = ((now - reported).num_milliseconds() as f64 / 1000.0).clamp(0.0, MAX_EXTRAPOLATION_SECS);
+    let (vx, vy) = velocity_km_s(aircraft).unwrap_or((0.0, 0.0));
+    Some(position.offset_by_km(vx * elapsed, vy * elapsed))
+}
+
+// Point on a path from `predict_path` after `seconds`
+pub fn path_position(path: &[Location], seconds: f64) -> Option<&Location> {
+    let index = (seconds / STEP_SECS).ceil() as usize;
+    path.get(index)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::Duration;
+
+    fn aircraft(location: &Location, track: f64) -> Aircraft {
+        let mut aircraft = Aircraft::new("a12345".to_string());
+        aircraft.latitude = Some(location.lat);
+        aircraft.longitude = Some(location.lon);
+        aircraft.velocity = Some(720.0); // 0.2 km/s
+        aircraft.true_track = Some(track);
+        aircraft
+    }
+
+    #[test]
+    fn test_straight_prediction() {
+        let home = Location::london();
+        let path = predict_path(&aircraft(&home, 90.0), 0.0, 120.0).unwrap();
+        let end = path.last().unwrap();
+        let (east, north) = home.offset_km_to(end);
+        assert!((east - 24.0).abs() < 0.05);
+        assert!(north.abs() < 0.05);
+
+        let one_minute = path_position(&path, 60.0).unwrap();
+        assert!((home.offset_km_to(one_minute).0 - 12.0).abs() < 0.05);
+    }
+
+    #[test]
+    fn test_extrapolate() {
+        let home = Location::london();
+        let mut traffic = aircraft(&home, 0.0);
+        let reported = Utc::now();
+        traffic.time_position = Some(reported);
+
+        let ahead = extrapolate(&traffic, reported + Duration::seconds(30)).unwrap();
+        assert!((home.offset_km_to(&ahead).1 - 6.0).abs() < 0.01);
+
+        // Capped for stale reports
+        let stale = extrapolate(&traffic, reported + Duration::seconds(600)).unwrap();
+        assert!((home.offset_km_to(&stale).1 - 12.0).abs() < 0.01);
+    }
+
+    #[test]
+    fn test_turning_prediction_curves() {
+        let home = Location::london();
+        // A full standard-rate 360 ends where it started
+        let path = predict_path(&aircraft(&home, 0.0), 3.0, 120.0).unwrap();
+        assert!(home.distance_to(path.last().unwrap()) < 0.1);
+    }
+
+    #[test]
+    fn test_estimate_turn_rate_from_trail() {
+        let home = Location::london();
+        let start = Utc::now();
+        let mut trail = AircraftTrail::new("a12345".to_string(), 10);
+
+        // Right turn at 1 deg/s sampled every 10 s at 0.2 km/s
+        let mut position = home.clone();
+        let mut heading: f64 = 0.0;
+        for i in 0..6 {
+            trail.add_position(position.to_point(), start + Duration::seconds(i * 10));
+            let rad = (heading + 5.0).to_radians();
+            position = position.offset_by_km(2.0 * rad.sin(), 2.0 * rad.cos());
+            heading += 10.0;
+        }
+        let rate = estimate_turn_rate(&trail).unwrap();
+        assert!((rate - 1.0).abs() < 0.05, "rate {}", rate);
+
+        let mut straight = AircraftTrail::new("a12345".to_string(), 10);
+        for i in 0..6 {
+            straight.add_position(home.offset_by_km(0.0, i as f64 * 2.0).to_point(), start + Duration::seconds(i * 10));
+        }
+        assert_eq!(estimate_turn_rate(&straight), None);
+    }
+}
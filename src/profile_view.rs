@@ -0,0 +1,265 @@
+use crate::aircraft::Aircraft;
+use crate::config::AppConfig;
+use crate::geo::Location;
+use crate::radar_view::RadarView;
+use egui::{Color32, Painter, Pos2, Rect, Sense, Shape, Stroke, Ui, Vec2};
+use geo::Point;
+use serde::{Deserialize, Serialize};
+
+const ALTITUDE_STEP_FT: f64 = 5000.0;
+const MIN_CEILING_FT: f64 = 15000.0;
+// Along-bearing mode only shows traffic within this fraction of the range
+// either side of the bearing line
+const CORRIDOR_FRACTION: f64 = 0.25;
+const MARGIN: Vec2 = Vec2::new(48.0, 18.0);
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Default)]
+pub enum ProfileAxis {
+    // Distance from home regardless of direction
+    #[default]
+    Distance,
+    // Signed distance along a bearing through home
+    Bearing(f64),
+}
+
+impl ProfileAxis {
+    pub fn name(&self) -> String {
+        match self {
+            ProfileAxis::Distance => "Distance from home".to_string(),
+            ProfileAxis::Bearing(bearing) => format!("Along {:03.0}°", bearing),
+        }
+    }
+
+    // Horizontal position in km, or None when off the profile
+    pub fn horizontal_km(&self, user_location: &Location, point: Point<f64>, range_km: f64) -> Option<f64> {
+        let (east_km, north_km) = user_location.offset_km_to(&Location::from_point(point));
+        match self {
+            ProfileAxis::Distance => {
+                let distance = east_km.hypot(north_km);
+                (distance <= range_km).then_some(distance)
+            }
+            ProfileAxis::Bearing(bearing) => {
+                let (sin, cos) = bearing.to_radians().sin_cos();
+                let along = east_km * sin + north_km * cos;
+                let cross = east_km * cos - north_km * sin;
+                (along.abs() <= range_km && cross.abs() <= range_km * CORRIDOR_FRACTION).then_some(along)
+            }
+        }
+    }
+
+    fn km_range(&self, range_km: f64) -> (f64, f64) {
+        match self {
+            ProfileAxis::Distance => (0.0, range_km),
+            ProfileAxis::Bearing(_) => (-range_km, range_km),
+        }
+    }
+}
+
+// Side view plotting altitude against horizontal distance. Colours, trails
+// and selection come from the plan view so both stay consistent.
+pub struct ProfileView {
+    plot: Rect,
+    km_range: (f64, f64),
+    ceiling_ft: f64,
+}
+
+impl Default for ProfileView {
+    fn default() -> Self {
+        Self {
+            plot: Rect::NOTHING,
+            km_range: (0.0, 1.0),
+            ceiling_ft: MIN_CEILING_FT,
+        }
+    }
+}
+
+impl ProfileView {
+    pub fn draw(
+        &mut self,
+        ui: &mut Ui,
+        aircraft: &[Aircraft],
+        radar: &RadarView,
+        config: &AppConfig,
+        selected: Option<&str>,
+    ) -> egui::Response {
+        let (response, painter) = ui.allocate_painter(
+            Vec2::new(ui.available_width(), ui.available_height()),
+            Sense::click(),
+        );
+        let axis = config.profile_axis;
+        let range_km = config.radar_radius_km;
+
+        self.plot = Rect::from_min_max(response.rect.min + MARGIN, response.rect.max - Vec2::new(MARGIN.y, MARGIN.y));
+        self.km_range = axis.km_range(range_km);
+        let highest = aircraft.iter().filter_map(|a| a.altitude).fold(0.0, f64::max);
+        self.ceiling_ft = ((highest / ALTITUDE_STEP_FT).ceil() * ALTITUDE_STEP_FT).max(MIN_CEILING_FT);
+
+        self.draw_grid(&painter, config, axis);
+
+        let painter = painter.with_clip_rect(self.plot.expand(4.0));
+        let location = &config.location;
+        let text_color = text_color(config);
+        for aircraft in aircraft {
+            let Some(pos) = self.aircraft_pos(aircraft, axis, location, range_km) else {
+                continue;
+            };
+            let color = radar.aircraft_color(aircraft, config);
+
+            if config.show_trails {
+                if let Some(trail) = radar.trail(&aircraft.icao24) {
+                    let points: Vec<Pos2> = trail
+                        .samples()
+                        .iter()
+                        .filter_map(|sample| {
+                            let km = axis.horizontal_km(location, sample.pos, range_km)?;
+                            Some(self.to_screen(km, sample.altitude?))
+                        })
+                        .collect();
+                    if points.len() >= 2 {
+                        painter.add(Shape::line(points, Stroke::new(1.5, color.gamma_multiply(0.5))));
+                    }
+                }
+            }
+
+            draw_trend_arrow(&painter, pos, aircraft.vertical_rate, color);
+            painter.circle_filled(pos, 4.0, color);
+            if selected == Some(aircraft.icao24.as_str()) {
+                painter.circle_stroke(pos, 9.0, Stroke::new(1.5, text_color));
+            }
+            painter.text(
+                pos + Vec2::new(6.0, -6.0),
+                egui::Align2::LEFT_BOTTOM,
+                aircraft.label(config.callsign_display),
+                egui::FontId::proportional(10.0),
+                text_color,
+            );
+        }
+
+        response
+    }
+
+    // Closest aircraft within a few pixels of `pos`
+    pub fn aircraft_at(&self, pos: Pos2, aircraft: &[Aircraft], config: &AppConfig) -> Option<String> {
+        aircraft
+            .iter()
+            .filter_map(|a| {
+                let screen_pos = self.aircraft_pos(a, config.profile_axis, &config.location, config.radar_radius_km)?;
+                Some((a, screen_pos.distance(pos)))
+            })
+            .filter(|(_, distance)| *distance <= 10.0)
+            .min_by(|a, b| a.1.total_cmp(&b.1))
+            .map(|(a, _)| a.icao24.clone())
+    }
+
+    fn aircraft_pos(&self, aircraft: &Aircraft, axis: ProfileAxis, location: &Location, range_km: f64) -> Option<Pos2> {
+        let km = axis.horizontal_km(location, aircraft.position()?, range_km)?;
+        Some(self.to_screen(km, aircraft.altitude?))
+    }
+
+    fn to_screen(&self, km: f64, altitude_ft: f64) -> Pos2 {
+        let (min_km, max_km) = self.km_range;
+        let x = (km - min_km) / (max_km - min_km);
+        let y = altitude_ft.max(0.0) / self.ceiling_ft;
+        Pos2::new(
+            self.plot.left() + x as f32 * self.plot.width(),
+            self.plot.bottom() - y as f32 * self.plot.height(),
+        )
+    }
+
+    fn draw_grid(&self, painter: &Painter, config: &AppConfig, axis: ProfileAxis) {
+        let (background, grid) = if config.theme.is_dark() {
+            (Color32::from_rgb(20, 20, 30), Color32::from_rgb(60, 60, 80))
+        } else {
+            (Color32::from_rgb(240, 240, 250), Color32::from_rgb(200, 200, 220))
+        };
+        let font = egui::FontId::proportional(10.0);
+        painter.rect_filled(self.plot, 2.0, background);
+
+        let mut altitude = 0.0;
+        while altitude <= self.ceiling_ft {
+            let y = self.to_screen(self.km_range.0, altitude).y;
+            painter.line_segment([Pos2::new(self.plot.left(), y), Pos2::new(self.plot.right(), y)], Stroke::new(1.0, grid));
+            painter.text(
+                Pos2::new(self.plot.left() - 4.0, y),
+                egui::Align2::RIGHT_CENTER,
+                format!("{:.0}k ft", altitude / 1000.0),
+                font.clone(),
+                grid,
+            );
+            altitude += ALTITUDE_STEP_FT;
+        }
+
+        let (min_km, max_km) = self.km_range;
+        for i in 0..=4 {
+            let km = min_km + (max_km - min_km) * i as f64 / 4.0;
+            let x = self.to_screen(km, 0.0).x;
+            painter.line_segment([Pos2::new(x, self.plot.top()), Pos2::new(x, self.plot.bottom())], Stroke::new(1.0, grid));
+            painter.text(
+                Pos2::new(x, self.plot.bottom() + 2.0),
+                egui::Align2::CENTER_TOP,
+                format!("{:.0} km", km),
+                font.clone(),
+                grid,
+            );
+        }
+
+        // Home is at 0 km in either mode
+        let home_x = self.to_screen(0.0, 0.0).x;
+        painter.line_segment(
+            [Pos2::new(home_x, self.plot.top()), Pos2::new(home_x, self.plot.bottom())],
+            Stroke::new(1.5, text_color(config).gamma_multiply(0.5)),
+        );
+        painter.text(
+            self.plot.left_top() + Vec2::new(4.0, 2.0),
+            egui::Align2::LEFT_TOP,
+            axis.name(),
+            font,
+            text_color(config),
+        );
+    }
+}
+
+// Labels drawn over the plot background
+fn text_color(config: &AppConfig) -> Color32 {
+    if config.theme.is_dark() {
+        Color32::WHITE
+    } else {
+        Color32::from_rgb(30, 30, 40)
+    }
+}
+
+// Arrow up or down scaled by vertical rate in ft/min
+fn draw_trend_arrow(painter: &Painter, pos: Pos2, vertical_rate: Option<f64>, color: Color32) {
+    let Some(rate) = vertical_rate.filter(|r| r.abs() >= 200.0) else {
+        return;
+    };
+    let length = (rate.abs() / 100.0).clamp(6.0, 24.0) as f32;
+    let direction = if rate > 0.0 { -1.0 } else { 1.0 };
+    let tip = pos + Vec2::new(0.0, direction * length);
+    let stroke = Stroke::new(1.5, color);
+
+    painter.line_segment([pos, tip], stroke);
+    painter.line_segment([tip, tip + Vec2::new(-3.0, -direction * 4.0)], stroke);
+    painter.line_segment([tip, tip + Vec2::new(3.0, -direction * 4.0)], stroke);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_horizontal_position() {
+        let home = Location::london();
+        let east = home.offset_by_km(10.0, 0.0).to_point();
+        let north_east = home.offset_by_km(10.0, 10.0).to_point();
+
+        let distance = ProfileAxis::Distance.horizontal_km(&home, east, 50.0).unwrap();
+        assert!((distance - 10.0).abs() < 0.01);
+        assert!(ProfileAxis::Distance.horizontal_km(&home, east, 5.0).is_none());
+
+        let along = ProfileAxis::Bearing(270.0).horizontal_km(&home, east, 50.0).unwrap();
+        assert!((along + 10.0).abs() < 0.01);
+        // 10 km off the bearing line is outside the 7.5 km corridor
+        assert!(ProfileAxis::Bearing(90.0).horizontal_km(&home, north_east, 30.0).is_none());
+    }
+}
@@ -0,0 +1,70 @@
+use egui::Pos2;
+
+// Maps local east/north kilometres (from `Location::offset_km_to`) to screen
+// space for a given centre, zoom and rotation. Copy so drawing closures can
+// take it without borrowing the view.
+#[derive(Debug, Clone, Copy)]
+pub struct ScreenTransform {
+    pub center: Pos2,
+    pub px_per_km: f32,
+    sin: f32,
+    cos: f32,
+}
+
+impl ScreenTransform {
+    // `rotation_deg` is the bearing shown at the top of the screen
+    pub fn new(center: Pos2, px_per_km: f32, rotation_deg: f64) -> Self {
+        let (sin, cos) = (rotation_deg.to_radians() as f32).sin_cos();
+        Self { center, px_per_km, sin, cos }
+    }
+
+    pub fn to_screen(self, east_km: f64, north_km: f64) -> Pos2 {
+        let (e, n) = (east_km as f32, north_km as f32);
+        let right = e * self.cos - n * self.sin;
+        let up = e * self.sin + n * self.cos;
+        Pos2::new(self.center.x + right * self.px_per_km, self.center.y - up * self.px_per_km)
+    }
+
+    pub fn to_local(self, pos: Pos2) -> (f64, f64) {
+        let right = (pos.x - self.center.x) / self.px_per_km;
+        let up = (self.center.y - pos.y) / self.px_per_km;
+        ((right * self.cos + up * self.sin) as f64, (up * self.cos - right * self.sin) as f64)
+    }
+
+    // Screen angle for a compass bearing
+    pub fn screen_bearing(self, bearing_deg: f64) -> f64 {
+        bearing_deg - self.sin.atan2(self.cos).to_degrees() as f64
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn close(a: Pos2, b: Pos2) -> bool {
+        a.distance(b) < 1e-3
+    }
+
+    #[test]
+    fn test_north_up_scale() {
+        let transform = ScreenTransform::new(Pos2::new(100.0, 100.0), 10.0, 0.0);
+
+        assert!(close(transform.to_screen(0.0, 0.0), Pos2::new(100.0, 100.0)));
+        assert!(close(transform.to_screen(2.0, 0.0), Pos2::new(120.0, 100.0)));
+        // Screen y grows downwards
+        assert!(close(transform.to_screen(0.0, 3.0), Pos2::new(100.0, 70.0)));
+        assert_eq!(transform.screen_bearing(45.0), 45.0);
+    }
+
+    #[test]
+    fn test_rotated_round_trip() {
+        // East at the top: a point due east is straight up the screen
+        let transform = ScreenTransform::new(Pos2::new(50.0, 50.0), 4.0, 90.0);
+        assert!(close(transform.to_screen(5.0, 0.0), Pos2::new(50.0, 30.0)));
+        assert!((transform.screen_bearing(90.0)).abs() < 1e-6);
+
+        let (east, north) = transform.to_local(transform.to_screen(-3.0, 7.5));
+        assert!((east + 3.0).abs() < 1e-4);
+        assert!((north - 7.5).abs() < 1e-4);
+    }
+}
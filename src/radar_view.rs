@@ -1,16 +1,98 @@
 use crate::aircraft::{Aircraft, AircraftTrail};
-use crate::config::AppConfig;
+use crate::airports::{AirportDatabase, AirportKind};
+use crate::airspace::AirspaceDatabase;
+use crate::basemap::Basemap;
+use crate::config::{AppConfig, SymbolColoring};
+use crate::conflict::Conflict;
+use crate::flight_phase::{FlightPhase, PhaseTracker};
+use crate::geofence::{Geofence, GeofenceShape};
 use crate::geo::Location;
+use crate::heatmap::{self, DensityGrid};
+use crate::labels::{self, LabelPlacement, LabelRequest};
+use crate::prediction::{self, TICK_MINUTES};
+use crate::projection::ScreenTransform;
+use crate::map_layers::{MapLayer, MapLayerConfig};
+use crate::measure::{MeasureEnd, Measurement};
+use crate::sweep::{self, Sweep};
 use egui::{Color32, Painter, Pos2, Rect, Sense, Shape, Stroke, Ui, Vec2};
-use geo::Point;
-use std::collections::HashMap;
+use geo::{coord, Point};
+use serde::{Deserialize, Serialize};
+use std::collections::{HashMap, HashSet};
 use std::f64::consts::PI;
+use std::path::PathBuf;
+use std::sync::Arc;
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Default)]
+pub enum RadarOrientation {
+    #[default]
+    NorthUp,
+    // Selected aircraft's track points up
+    TrackUp,
+    // Fixed bearing at the top of the scope, in degrees
+    Rotated(f64),
+}
+
+impl RadarOrientation {
+    pub fn name(&self) -> &'static str {
+        match self {
+            RadarOrientation::NorthUp => "North up",
+            RadarOrientation::TrackUp => "Track up",
+            RadarOrientation::Rotated(_) => "Rotated",
+        }
+    }
+}
 
 pub struct RadarView {
     center: Pos2,
     radius: f32,
     scale: f32,
+    range_km: f64,
     aircraft_trails: HashMap<String, AircraftTrail>,
+    airports: Option<Arc<AirportDatabase>>,
+    phases: HashMap<String, FlightPhase>,
+    conflicts: Vec<Conflict>,
+    geofences: Vec<Geofence>,
+    sketch: Vec<Location>,
+    airspaces: Option<Arc<AirspaceDatabase>>,
+    in_airspace: HashSet<String>,
+    watched: HashSet<String>,
+    // Aircraft excluded by the radar filter, whose trails and conflicts
+    // are hidden along with their symbols
+    hidden: HashSet<String>,
+    map_layers: HashMap<PathBuf, MapLayer>,
+    map_cache: Option<MapCache>,
+    basemap: Option<Basemap>,
+    selected: Option<String>,
+    label_offsets: HashMap<String, Vec2>,
+    label_slots: HashMap<String, usize>,
+    // Label being dragged and where it was grabbed relative to its corner
+    dragging_label: Option<(String, Vec2)>,
+    measuring: bool,
+    measurement: Option<Measurement>,
+    // Bearing shown at the top of the scope
+    rotation_deg: f64,
+    followed: Option<String>,
+    sweep: Sweep,
+    heatmap: Option<DensityGrid>,
+}
+
+// Everything the projected map shapes depend on; shapes are rebuilt only
+// when one of these changes. The view centre isn't part of it: moving the
+// centre only translates the shapes.
+#[derive(PartialEq)]
+struct MapCacheKey {
+    center: Pos2,
+    pixels_per_km: f32,
+    rotation_deg: f64,
+    layers: Vec<MapLayerConfig>,
+}
+
+// Map shapes projected around `anchor`, covering a margin beyond the scope
+// so following an aircraft doesn't rebuild them on every repaint
+struct MapCache {
+    key: MapCacheKey,
+    anchor: Location,
+    shapes: Vec<Shape>,
 }
 
 impl RadarView {
@@ -22,10 +104,106 @@ impl RadarView {
             center,
             radius,
             scale: 1.0,
+            range_km: 8.0,
             aircraft_trails: HashMap::new(),
+            airports: None,
+            phases: HashMap::new(),
+            conflicts: Vec::new(),
+            geofences: Vec::new(),
+            sketch: Vec::new(),
+            airspaces: None,
+            in_airspace: HashSet::new(),
+            watched: HashSet::new(),
+            hidden: HashSet::new(),
+            map_layers: HashMap::new(),
+            map_cache: None,
+            basemap: None,
+            selected: None,
+            label_offsets: HashMap::new(),
+            label_slots: HashMap::new(),
+            dragging_label: None,
+            measuring: false,
+            measurement: None,
+            rotation_deg: 0.0,
+            followed: None,
+            sweep: Sweep::default(),
+            heatmap: None,
+        }
+    }
+
+    pub fn set_rect(&mut self, rect: Rect) {
+        self.center = rect.center();
+        self.radius = rect.width().min(rect.height()) * 0.4;
+    }
+
+    pub fn set_airports(&mut self, airports: Arc<AirportDatabase>) {
+        self.airports = Some(airports);
+    }
+
+    pub fn set_airspaces(&mut self, airspaces: Arc<AirspaceDatabase>) {
+        self.airspaces = Some(airspaces);
+    }
+
+    // Aircraft currently inside at least one airspace volume
+    pub fn update_airspace_flags(&mut self, icao24s: impl IntoIterator<Item = String>) {
+        self.in_airspace = icao24s.into_iter().collect();
+    }
+
+    pub fn set_watched(&mut self, icao24s: impl IntoIterator<Item = String>) {
+        self.watched = icao24s.into_iter().collect();
+    }
+
+    pub fn set_hidden(&mut self, icao24s: impl IntoIterator<Item = String>) {
+        self.hidden = icao24s.into_iter().collect();
+    }
+
+    fn visible_trails(&self) -> impl Iterator<Item = &AircraftTrail> {
+        self.aircraft_trails.values().filter(|t| !self.hidden.contains(&t.icao24))
+    }
+
+    pub fn set_map_layers(&mut self, layers: HashMap<PathBuf, MapLayer>) {
+        self.map_layers = layers;
+        self.map_cache = None;
+    }
+
+    pub fn add_map_layer(&mut self, path: PathBuf, layer: MapLayer) {
+        self.map_layers.insert(path, layer);
+        self.map_cache = None;
+    }
+
+    pub fn set_selected(&mut self, icao24: Option<String>) {
+        self.selected = icao24;
+    }
+
+    // While measuring, dragging on the scope draws a range-and-bearing line
+    // instead of moving labels
+    pub fn set_measuring(&mut self, measuring: bool) {
+        self.measuring = measuring;
+        if !measuring {
+            self.measurement = None;
         }
     }
 
+    pub fn is_measuring(&self) -> bool {
+        self.measuring
+    }
+
+    pub fn set_heatmap(&mut self, heatmap: Option<DensityGrid>) {
+        self.heatmap = heatmap;
+    }
+
+    pub fn set_followed(&mut self, icao24: Option<String>) {
+        self.followed = icao24;
+    }
+
+    pub fn set_basemap(&mut self, basemap: Option<Basemap>) {
+        self.basemap = basemap;
+    }
+
+    pub fn basemap_name(&self) -> Option<&str> {
+        self.basemap.as_ref().map(Basemap::name)
+    }
+
     pub fn update_trails(&mut self, aircraft: &[Aircraft], config: &AppConfig) {
         for aircraft in aircraft {
             if let Some(position) = aircraft.position() {
@@ -33,31 +211,286 @@ impl RadarView {
                     .entry(aircraft.icao24.clone())
                     .or_insert_with(|| AircraftTrail::new(aircraft.icao24.clone(), config.trail_length));
                 
-                trail.add_position(position, aircraft.time_position.unwrap_or_else(chrono::Utc::now));
+                trail.add_sample(position, aircraft.altitude, aircraft.velocity, aircraft.time_position.unwrap_or_else(chrono::Utc::now));
             }
         }
     }
 
-    pub fn draw(&self, ui: &mut Ui, aircraft: &[Aircraft], config: &AppConfig, user_location: &Location) -> egui::Response {
+    pub fn update_phases(&mut self, tracker: &PhaseTracker) {
+        self.phases = tracker.phases();
+    }
+
+    pub fn update_conflicts(&mut self, conflicts: &[Conflict]) {
+        self.conflicts = conflicts.to_vec();
+    }
+
+    pub fn set_geofences(&mut self, geofences: &[Geofence]) {
+        self.geofences = geofences.to_vec();
+    }
+
+    // Vertices of a polygon being drawn by the user
+    pub fn set_sketch(&mut self, sketch: &[Location]) {
+        self.sketch = sketch.to_vec();
+    }
+
+    // `user_location` is the centre of the projection: home, or the
+    // followed aircraft in follow mode
+    pub fn draw(&mut self, ui: &mut Ui, aircraft: &[Aircraft], config: &AppConfig, user_location: &Location) -> egui::Response {
         let (response, painter) = ui.allocate_painter(
             Vec2::new(ui.available_width(), ui.available_height()),
             Sense::click_and_drag(),
         );
+        self.set_rect(response.rect);
+        self.range_km = config.radar_radius_km;
+        self.update_rotation(aircraft, config);
+        if config.sweep.enabled {
+            let frame_secs = ui.input(|i| i.stable_dt) as f64;
+            self.sweep.advance(frame_secs, &config.sweep);
+            ui.ctx().request_repaint();
+        }
 
         self.draw_radar_background(&painter, config);
+        if config.show_basemap {
+            self.draw_basemap(&painter, config, user_location);
+        }
+        self.draw_map_layers(&painter, config, user_location);
+        if config.heatmap.enabled {
+            self.draw_heatmap(&painter, config, user_location);
+        }
         self.draw_range_rings(&painter, config);
-        self.draw_compass_rose(&painter);
+        self.draw_compass_rose(&painter, config);
+
+        if config.show_airports {
+            self.draw_airports(&painter, config, user_location);
+        }
+
+        if config.show_airspace {
+            self.draw_airspaces(&painter, user_location);
+        }
+
+        self.draw_geofences(&painter, user_location);
         
+        if config.sweep.enabled {
+            self.draw_sweep(&painter, config);
+        }
+
         if config.show_trails {
-            self.draw_aircraft_trails(&painter, user_location);
+            if config.sweep.enabled {
+                self.draw_trail_blips(&painter, config, user_location);
+            } else {
+                self.draw_aircraft_trails(&painter, user_location);
+            }
         }
         
-        self.draw_aircraft(&painter, aircraft, user_location);
-        self.draw_center_marker(&painter, user_location);
+        self.draw_breadcrumbs(&painter, user_location);
+
+        if config.speed_leaders.enabled {
+            self.draw_speed_leaders(&painter, aircraft, config, user_location);
+        }
+
+        self.draw_conflicts(&painter, aircraft, config, user_location);
+        let blocks = self.place_data_blocks(&painter, aircraft, config, user_location);
+        if self.measuring {
+            self.handle_measure_drag(&response, aircraft, user_location);
+        } else {
+            self.handle_label_drag(&response, &blocks);
+        }
+        self.draw_aircraft(&painter, aircraft, config, user_location);
+        self.draw_data_blocks(&painter, aircraft, config, &blocks);
+        self.draw_measurement(&painter, aircraft, user_location);
+        self.draw_center_marker(&painter, config, user_location);
         
         response
     }
 
+    fn update_rotation(&mut self, aircraft: &[Aircraft], config: &AppConfig) {
+        self.rotation_deg = match config.orientation {
+            RadarOrientation::NorthUp => 0.0,
+            RadarOrientation::Rotated(bearing) => bearing,
+            // Keep the last rotation if the selected aircraft has no track
+            RadarOrientation::TrackUp => self
+                .followed
+                .as_ref()
+                .or(self.selected.as_ref())
+                .and_then(|id| aircraft.iter().find(|a| &a.icao24 == id))
+                .and_then(|a| a.true_track)
+                .unwrap_or(self.rotation_deg),
+        };
+    }
+
+    pub fn rotation_deg(&self) -> f64 {
+        self.rotation_deg
+    }
+
+    fn data_block_font() -> egui::FontId {
+        egui::FontId::monospace(10.0)
+    }
+
+    fn place_data_blocks(
+        &mut self,
+        painter: &Painter,
+        aircraft: &[Aircraft],
+        config: &AppConfig,
+        user_location: &Location,
+    ) -> Vec<LabelPlacement> {
+        let mut visible: Vec<(&Aircraft, Pos2)> = aircraft
+            .iter()
+            .filter_map(|a| Some((a, self.geo_to_screen(&a.position()?, user_location)?)))
+            .collect();
+        // The selected aircraft gets first pick, then conflicting traffic
+        visible.sort_by_key(|(a, _)| {
+            (
+                self.selected.as_ref() != Some(&a.icao24),
+                !self.conflicts.iter().any(|c| c.involves(&a.icao24)),
+            )
+        });
+
+        let texts: Vec<String> = visible
+            .iter()
+            .map(|(a, _)| config.data_blocks.text(a, config.callsign_display))
+            .collect();
+        let requests: Vec<LabelRequest> = visible
+            .iter()
+            .zip(&texts)
+            .filter(|(_, text)| !text.is_empty())
+            .map(|((a, pos), text)| LabelRequest {
+                id: &a.icao24,
+                anchor: *pos,
+                size: painter
+                    .layout_no_wrap(text.clone(), Self::data_block_font(), Color32::WHITE)
+                    .size(),
+                manual_offset: self.label_offsets.get(&a.icao24).copied(),
+                previous_slot: self.label_slots.get(&a.icao24).copied(),
+            })
+            .collect();
+        let symbols: Vec<Rect> = visible
+            .iter()
+            .map(|(_, pos)| Rect::from_center_size(*pos, Vec2::splat(16.0)))
+            .collect();
+
+        let placements = labels::place_labels(
+            &requests,
+            config.data_blocks.leader_px,
+            &symbols,
+            config.data_blocks.declutter,
+        );
+        self.label_slots = placements
+            .iter()
+            .filter_map(|p| Some((p.id.clone(), p.slot?)))
+            .collect();
+        placements
+    }
+
+    // Dragging a data block pins it at that offset from its aircraft;
+    // double-clicking it hands it back to automatic placement
+    fn handle_label_drag(&mut self, response: &egui::Response, blocks: &[LabelPlacement]) {
+        let Some(pointer) = response.interact_pointer_pos() else {
+            if !response.dragged() {
+                self.dragging_label = None;
+            }
+            return;
+        };
+        let hit = blocks.iter().rev().find(|b| b.rect.contains(pointer));
+
+        if response.double_clicked() {
+            if let Some(block) = hit {
+                self.label_offsets.remove(&block.id);
+            }
+        }
+        if response.drag_started() {
+            self.dragging_label = hit.map(|b| (b.id.clone(), pointer - b.rect.min));
+        }
+        if !response.dragged() {
+            self.dragging_label = None;
+            return;
+        }
+        if let Some((id, grab)) = &self.dragging_label {
+            if let Some(block) = blocks.iter().find(|b| &b.id == id) {
+                self.label_offsets.insert(id.clone(), pointer - *grab - block.anchor);
+            }
+        }
+    }
+
+    // Ends snap to an aircraft when dropped on one
+    fn measure_end_at(&self, pos: Pos2, aircraft: &[Aircraft], user_location: &Location) -> MeasureEnd {
+        match self.aircraft_at(pos, aircraft, user_location) {
+            Some(icao24) => MeasureEnd::Aircraft(icao24),
+            None => MeasureEnd::Point(Location::from_point(self.screen_to_geo(pos, user_location))),
+        }
+    }
+
+    fn handle_measure_drag(&mut self, response: &egui::Response, aircraft: &[Aircraft], user_location: &Location) {
+        let Some(pointer) = response.interact_pointer_pos() else {
+            return;
+        };
+        if response.drag_started() {
+            let start = self.measure_end_at(pointer, aircraft, user_location);
+            self.measurement = Some(Measurement {
+                from: start.clone(),
+                to: start,
+            });
+        } else if response.dragged() {
+            let end = self.measure_end_at(pointer, aircraft, user_location);
+            if let Some(measurement) = &mut self.measurement {
+                measurement.to = end;
+            }
+        }
+    }
+
+    fn draw_measurement(&self, painter: &Painter, aircraft: &[Aircraft], user_location: &Location) {
+        let Some(reading) = self.measurement.as_ref().and_then(|m| m.reading(aircraft)) else {
+            return;
+        };
+        if reading.distance_km <= 0.0 {
+            return;
+        }
+
+        let color = Color32::from_rgb(255, 230, 90);
+        let from = self.project(&reading.from.to_point(), user_location);
+        let to = self.project(&reading.to.to_point(), user_location);
+        let painter = painter.with_clip_rect(self.scope_rect());
+
+        painter.add(Shape::dashed_line(&[from, to], Stroke::new(1.5, color), 8.0, 4.0));
+        painter.circle_stroke(from, 4.0, Stroke::new(1.5, color));
+        painter.circle_stroke(to, 4.0, Stroke::new(1.5, color));
+
+        let text = painter.layout_no_wrap(reading.summary(), egui::FontId::monospace(11.0), color);
+        let box_rect = Rect::from_min_size(to + Vec2::new(10.0, 6.0), text.size()).expand(3.0);
+        painter.rect_filled(box_rect, 2.0, Color32::from_black_alpha(180));
+        painter.text(
+            box_rect.min + Vec2::splat(3.0),
+            egui::Align2::LEFT_TOP,
+            reading.summary(),
+            egui::FontId::monospace(11.0),
+            color,
+        );
+    }
+
+    fn draw_data_blocks(&self, painter: &Painter, aircraft: &[Aircraft], config: &AppConfig, blocks: &[LabelPlacement]) {
+        for block in blocks {
+            let Some(aircraft) = aircraft.iter().find(|a| a.icao24 == block.id) else {
+                continue;
+            };
+            let color = if self.selected.as_ref() == Some(&block.id) {
+                Color32::WHITE
+            } else {
+                Color32::from_gray(210)
+            };
+
+            let end = labels::leader_end(block);
+            if end.distance(block.anchor) > 8.0 {
+                painter.line_segment([block.anchor, end], Stroke::new(1.0, color.gamma_multiply(0.6)));
+            }
+            painter.text(
+                block.rect.min,
+                egui::Align2::LEFT_TOP,
+                config.data_blocks.text(aircraft, config.callsign_display),
+                Self::data_block_font(),
+                color,
+            );
+        }
+    }
+
     fn draw_radar_background(&self, painter: &Painter, config: &AppConfig) {
         let background_color = if config.theme.is_dark() {
             Color32::from_rgb(20, 20, 30)
@@ -72,6 +505,110 @@ impl RadarView {
         );
     }
 
+    fn draw_basemap(&mut self, painter: &Painter, config: &AppConfig, user_location: &Location) {
+        let scope = self.scope_rect();
+        let view = self.view_bounds(user_location);
+        let bounds = [(view.min().x, view.min().y), (view.max().x, view.max().y)];
+        let transform = self.transform();
+        let Some(basemap) = &mut self.basemap else {
+            return;
+        };
+
+        let painter = painter.with_clip_rect(scope);
+        let pending = basemap.draw(&painter, bounds, transform.px_per_km as f64, config.basemap_opacity, |lon, lat| {
+            let (east_km, north_km) = user_location.offset_km_to(&Location::new(lat, lon));
+            transform.to_screen(east_km, north_km)
+        });
+        if pending {
+            painter.ctx().request_repaint();
+        }
+    }
+
+    fn draw_map_layers(&mut self, painter: &Painter, config: &AppConfig, user_location: &Location) {
+        if config.map_layers.iter().all(|l| !l.visible) {
+            return;
+        }
+
+        let key = MapCacheKey {
+            center: self.center,
+            pixels_per_km: self.pixels_per_km(),
+            rotation_deg: self.rotation_deg,
+            layers: config.map_layers.clone(),
+        };
+        let transform = self.transform();
+        // Rebuilt once the centre has moved far enough to reach the margin
+        let stale = self.map_cache.as_ref().map_or(true, |cache| {
+            let moved_px = cache.anchor.distance_to(user_location) as f32 * transform.px_per_km;
+            cache.key != key || moved_px > self.radius * 0.5
+        });
+        if stale {
+            let shapes = self.build_map_shapes(config, user_location);
+            self.map_cache = Some(MapCache { key, anchor: user_location.clone(), shapes });
+        }
+
+        if let Some(cache) = &self.map_cache {
+            let (east_km, north_km) = user_location.offset_km_to(&cache.anchor);
+            let offset = transform.to_screen(east_km, north_km) - transform.center;
+            painter.with_clip_rect(self.scope_rect()).extend(cache.shapes.iter().cloned().map(|mut shape| {
+                shape.translate(offset);
+                shape
+            }));
+        }
+    }
+
+    fn draw_heatmap(&self, painter: &Painter, config: &AppConfig, user_location: &Location) {
+        let Some(grid) = &self.heatmap else {
+            return;
+        };
+        let transform = self.transform();
+        // Cells are anchored at home, which is off-centre while following
+        let (origin_east, origin_north) = user_location.offset_km_to(&grid.origin);
+        let reach_km = (self.radius / transform.px_per_km) as f64 * std::f64::consts::SQRT_2 + grid.cell_km;
+
+        let shapes = grid.cells().filter_map(|((east, north), density)| {
+            let (east, north) = (origin_east + east, origin_north + north);
+            if (east + grid.cell_km / 2.0).hypot(north + grid.cell_km / 2.0) > reach_km {
+                return None;
+            }
+            let size = grid.cell_km;
+            let corners = vec![
+                transform.to_screen(east, north),
+                transform.to_screen(east + size, north),
+                transform.to_screen(east + size, north + size),
+                transform.to_screen(east, north + size),
+            ];
+            let color = heatmap::color_ramp(density, config.heatmap.opacity);
+            Some(Shape::convex_polygon(corners, color, Stroke::NONE))
+        });
+        painter.with_clip_rect(self.scope_rect()).extend(shapes);
+    }
+
+    fn build_map_shapes(&mut self, config: &AppConfig, user_location: &Location) -> Vec<Shape> {
+        let view = self.bounds_of(self.scope_rect().expand(self.radius), user_location);
+        let transform = self.transform();
+
+        let mut shapes = Vec::new();
+        for layer_config in config.map_layers.iter().filter(|l| l.visible) {
+            let Some(layer) = self.map_layers.get_mut(&layer_config.path) else {
+                continue;
+            };
+            let [r, g, b] = layer_config.color;
+            let stroke = Stroke::new(layer_config.width, Color32::from_rgb(r, g, b));
+
+            for line in layer.lines_in_view(transform.px_per_km as f64, view) {
+                let points: Vec<Pos2> = line
+                    .points()
+                    .map(|p| {
+                        let (east_km, north_km) = user_location.offset_km_to(&Location::from_point(p));
+                        transform.to_screen(east_km, north_km)
+                    })
+                    .collect();
+                shapes.push(Shape::line(points, stroke));
+            }
+        }
+        shapes
+    }
+
     fn draw_range_rings(&self, painter: &Painter, config: &AppConfig) {
         let ring_color = if config.theme.is_dark() {
             Color32::from_rgb(60, 60, 80)
@@ -101,36 +638,129 @@ impl RadarView {
         }
     }
 
-    fn draw_compass_rose(&self, painter: &Painter) {
-        let directions = ["N", "E", "S", "W"];
-        let angles = [0.0, 90.0, 180.0, 270.0];
-        
-        for (direction, angle) in directions.iter().zip(angles.iter()) {
-            let angle_rad = angle * PI / 180.0;
-            let pos = Pos2::new(
-                self.center.x + (self.radius + 20.0) * angle_rad.sin() as f32,
-                self.center.y - (self.radius + 20.0) * angle_rad.cos() as f32,
+    // Degree ticks around the scope edge every 10°, numbered every 30° with
+    // the cardinal points lettered, all rotated with the projection
+    fn draw_compass_rose(&self, painter: &Painter, config: &AppConfig) {
+        let transform = self.transform();
+        let tick_color = Color32::from_gray(160);
+        let cardinal_color = if config.theme.is_dark() {
+            Color32::WHITE
+        } else {
+            Color32::from_rgb(30, 30, 40)
+        };
+
+        for bearing in (0..360).step_by(10) {
+            let angle = transform.screen_bearing(bearing as f64).to_radians();
+            let direction = Vec2::new(angle.sin() as f32, -angle.cos() as f32);
+            let length = if bearing % 30 == 0 { 8.0 } else { 4.0 };
+            painter.line_segment(
+                [self.center + direction * self.radius, self.center + direction * (self.radius + length)],
+                Stroke::new(1.0, tick_color),
             );
-            
+
+            if bearing % 30 != 0 {
+                continue;
+            }
+            let (label, size, color) = match bearing {
+                0 => ("N".to_string(), 14.0, cardinal_color),
+                90 => ("E".to_string(), 14.0, cardinal_color),
+                180 => ("S".to_string(), 14.0, cardinal_color),
+                270 => ("W".to_string(), 14.0, cardinal_color),
+                _ => (format!("{:03}", bearing), 10.0, tick_color),
+            };
             painter.text(
-                pos,
+                self.center + direction * (self.radius + 20.0),
                 egui::Align2::CENTER_CENTER,
-                direction,
-                egui::FontId::proportional(14.0),
-                Color32::WHITE,
+                label,
+                egui::FontId::proportional(size),
+                color,
             );
         }
+
+        if config.orientation != RadarOrientation::NorthUp {
+            painter.text(
+                Pos2::new(self.center.x + self.radius, self.center.y - self.radius),
+                egui::Align2::RIGHT_TOP,
+                format!("{} {:03.0}°", config.orientation.name().to_uppercase(), self.rotation_deg.rem_euclid(360.0)),
+                egui::FontId::monospace(11.0),
+                tick_color,
+            );
+        }
+    }
+
+    fn draw_airports(&self, painter: &Painter, config: &AppConfig, user_location: &Location) {
+        let Some(airports) = &self.airports else {
+            return;
+        };
+
+        let color = if config.theme.is_dark() {
+            Color32::from_rgb(150, 150, 170)
+        } else {
+            Color32::from_rgb(90, 90, 110)
+        };
+        let px_per_km = self.pixels_per_km();
+        let visible_km = self.radius as f64 / px_per_km as f64;
+
+        for (airport, _) in airports.within_radius(user_location, visible_km * 1.2) {
+            if !airport.is_airfield() {
+                continue;
+            }
+
+            // Small fields only once zoomed in, medium ones at regional scale
+            let (show, show_label) = match airport.kind {
+                AirportKind::Large => (true, true),
+                AirportKind::Medium => (visible_km < 300.0, visible_km < 150.0),
+                _ => (visible_km < 80.0, visible_km < 40.0),
+            };
+            if !show {
+                continue;
+            }
+
+            let mut drew_runway = false;
+            for runway in airport.runways.iter().filter(|r| !r.closed) {
+                let Some((le, he)) = runway.centerline() else {
+                    continue;
+                };
+                let (Some(a), Some(b)) = (
+                    self.geo_to_screen(&le.to_point(), user_location),
+                    self.geo_to_screen(&he.to_point(), user_location),
+                ) else {
+                    continue;
+                };
+
+                let width_km = runway.width_ft.unwrap_or(150.0) / 3280.84;
+                let width = (width_km as f32 * px_per_km).clamp(1.5, 6.0);
+                painter.line_segment([a, b], Stroke::new(width, color));
+                drew_runway = true;
+            }
+
+            let Some(pos) = self.geo_to_screen(&Point::new(airport.lon, airport.lat), user_location) else {
+                continue;
+            };
+            if !drew_runway {
+                painter.circle_stroke(pos, 4.0, Stroke::new(1.5, color));
+            }
+            if show_label {
+                painter.text(
+                    Pos2::new(pos.x, pos.y + 12.0),
+                    egui::Align2::CENTER_CENTER,
+                    airport.code(config.airport_code_style),
+                    egui::FontId::monospace(10.0),
+                    color,
+                );
+            }
+        }
     }
 
     fn draw_aircraft_trails(&self, painter: &Painter, user_location: &Location) {
-        for trail in self.aircraft_trails.values() {
-            if trail.positions.len() < 2 {
+        for trail in self.visible_trails() {
+            if trail.len() < 2 {
                 continue;
             }
 
             let mut points = Vec::new();
-            for (position, _) in &trail.positions {
-                if let Some(screen_pos) = self.geo_to_screen(position, user_location) {
+            for position in trail.positions() {
+                if let Some(screen_pos) = self.geo_to_screen(&position, user_location) {
                     points.push(screen_pos);
                 }
             }
@@ -143,36 +773,333 @@ impl RadarView {
         }
     }
 
-    fn draw_aircraft(&self, painter: &Painter, aircraft: &[Aircraft], user_location: &Location) {
+    // Sweep line with a fading afterglow behind it
+    fn draw_sweep(&self, painter: &Painter, config: &AppConfig) {
+        let color = if config.theme.is_dark() {
+            Color32::from_rgb(80, 255, 120)
+        } else {
+            Color32::from_rgb(0, 140, 60)
+        };
+        let angle = self.sweep.angle_deg();
+        let end = |angle: f64| {
+            let rad = angle.to_radians();
+            self.center + Vec2::new(rad.sin() as f32, -rad.cos() as f32) * self.radius
+        };
+
+        let glow_steps = 24;
+        for step in (1..=glow_steps).rev() {
+            let fade = 1.0 - step as f32 / glow_steps as f32;
+            let stroke = Stroke::new(2.0, color.gamma_multiply(0.25 * fade));
+            painter.line_segment([self.center, end(angle - step as f64 * 1.5)], stroke);
+        }
+        painter.line_segment([self.center, end(angle)], Stroke::new(2.0, color));
+    }
+
+    // Sweep-mode trails: one blip per fix, lit by the sweep and dimmer with age
+    fn draw_trail_blips(&self, painter: &Painter, config: &AppConfig, user_location: &Location) {
+        for trail in self.visible_trails() {
+            let count = trail.len();
+            for (idx, position) in trail.positions().enumerate() {
+                let Some(pos) = self.geo_to_screen(&position, user_location) else {
+                    continue;
+                };
+                let age = (idx + 1) as f32 / count as f32;
+                let intensity = self.sweep.intensity(sweep::screen_angle(pos - self.center), &config.sweep);
+                painter.circle_filled(pos, 2.0, Color32::from_rgb(100, 100, 255).gamma_multiply(intensity * age));
+            }
+        }
+    }
+
+    fn draw_speed_leaders(&self, painter: &Painter, aircraft: &[Aircraft], config: &AppConfig, user_location: &Location) {
+        let settings = &config.speed_leaders;
+        let seconds = settings.minutes * 60.0;
+        let painter = painter.with_clip_rect(self.scope_rect());
+
+        for aircraft in aircraft.iter().filter(|a| a.on_ground != Some(true)) {
+            let turn_rate = if settings.use_turn_rate {
+                self.aircraft_trails
+                    .get(&aircraft.icao24)
+                    .and_then(prediction::estimate_turn_rate)
+                    .unwrap_or(0.0)
+            } else {
+                0.0
+            };
+            let Some(path) = prediction::predict_path(aircraft, turn_rate, seconds) else {
+                continue;
+            };
+
+            let color = self.aircraft_color(aircraft, config);
+            let points: Vec<Pos2> = path.iter().map(|p| self.project(&p.to_point(), user_location)).collect();
+            painter.add(Shape::line(points, Stroke::new(1.0, color)));
+
+            if settings.show_ticks {
+                for minutes in TICK_MINUTES.iter().filter(|m| **m <= settings.minutes) {
+                    let Some(tick) = prediction::path_position(&path, minutes * 60.0) else {
+                        continue;
+                    };
+                    let pos = self.project(&tick.to_point(), user_location);
+                    let size = if *minutes >= 5.0 { 3.5 } else { 2.0 };
+                    painter.circle_filled(pos, size, color);
+                }
+            }
+        }
+    }
+
+    // Dotted history of the followed aircraft, shown even with trails off
+    fn draw_breadcrumbs(&self, painter: &Painter, user_location: &Location) {
+        let Some(trail) = self
+            .followed
+            .as_ref()
+            .filter(|id| !self.hidden.contains(*id))
+            .and_then(|id| self.aircraft_trails.get(id))
+        else {
+            return;
+        };
+        let color = Color32::from_rgb(120, 200, 255);
+        let count = trail.len();
+        for (idx, position) in trail.positions().enumerate() {
+            if let Some(pos) = self.geo_to_screen(&position, user_location) {
+                // Older crumbs fade out
+                let alpha = 0.3 + 0.7 * (idx + 1) as f32 / count as f32;
+                painter.circle_filled(pos, 2.5, color.gamma_multiply(alpha));
+            }
+        }
+    }
+
+    fn draw_airspaces(&self, painter: &Painter, user_location: &Location) {
+        let Some(airspaces) = &self.airspaces else {
+            return;
+        };
+        let scope = self.scope_rect();
+        let painter = painter.with_clip_rect(scope);
+
+        for airspace in airspaces.airspaces() {
+            let points: Vec<Pos2> = airspace
+                .polygon
+                .exterior()
+                .points()
+                .map(|p| self.project(&p, user_location))
+                .collect();
+            let bounds = Rect::from_points(&points);
+            if !bounds.intersects(scope) {
+                continue;
+            }
+
+            let color = airspace.class.color();
+            let stroke = Stroke::new(1.2, color.gamma_multiply(0.8));
+            if airspace.class.is_special_use() {
+                painter.add(Shape::dashed_line(&points, stroke, 8.0, 4.0));
+            } else {
+                painter.add(Shape::closed_line(points, stroke));
+            }
+
+            // Label only airspaces small enough on screen to place it sensibly
+            if bounds.width() < scope.width() * 2.0 {
+                painter.text(
+                    bounds.center(),
+                    egui::Align2::CENTER_CENTER,
+                    format!("{} {}\n{}", airspace.class.code(), airspace.name, airspace.vertical_label()),
+                    egui::FontId::proportional(9.0),
+                    color.gamma_multiply(0.6),
+                );
+            }
+        }
+    }
+
+    fn draw_geofences(&self, painter: &Painter, user_location: &Location) {
+        let painter = painter.with_clip_rect(self.scope_rect());
+        let color = Color32::from_rgb(255, 160, 60);
+        let px_per_km = self.pixels_per_km();
+
+        for fence in self.geofences.iter().filter(|f| f.enabled) {
+            let stroke = Stroke::new(1.5, color);
+            let label_pos = match &fence.shape {
+                GeofenceShape::Circle { center, radius_km } => {
+                    let pos = self.project(&center.to_point(), user_location);
+                    painter.circle_stroke(pos, *radius_km as f32 * px_per_km, stroke);
+                    pos
+                }
+                GeofenceShape::Polygon { vertices, holes } => {
+                    let points: Vec<Pos2> = vertices
+                        .iter()
+                        .map(|v| self.project(&v.to_point(), user_location))
+                        .collect();
+                    if points.is_empty() {
+                        continue;
+                    }
+                    let centroid = points.iter().fold(Vec2::ZERO, |acc, p| acc + p.to_vec2()) / points.len() as f32;
+                    painter.add(Shape::closed_line(points, stroke));
+                    for hole in holes {
+                        let points = hole.iter().map(|v| self.project(&v.to_point(), user_location)).collect();
+                        painter.add(Shape::closed_line(points, stroke));
+                    }
+                    centroid.to_pos2()
+                }
+            };
+
+            painter.text(
+                label_pos,
+                egui::Align2::CENTER_CENTER,
+                &fence.name,
+                egui::FontId::proportional(11.0),
+                color,
+            );
+        }
+
+        if !self.sketch.is_empty() {
+            let points: Vec<Pos2> = self
+                .sketch
+                .iter()
+                .map(|v| self.project(&v.to_point(), user_location))
+                .collect();
+            for point in &points {
+                painter.circle_filled(*point, 3.0, color);
+            }
+            painter.add(Shape::dashed_line(&points, Stroke::new(1.5, color), 6.0, 4.0));
+        }
+    }
+
+    fn draw_conflicts(&self, painter: &Painter, aircraft: &[Aircraft], config: &AppConfig, user_location: &Location) {
+        let visible: Vec<&Conflict> = self
+            .conflicts
+            .iter()
+            .filter(|c| c.icao24.iter().all(|id| !self.hidden.contains(id)))
+            .collect();
+        if visible.is_empty() {
+            return;
+        }
+        let label = |icao24: &str| {
+            aircraft
+                .iter()
+                .find(|a| a.icao24 == icao24)
+                .map(|a| a.label(config.callsign_display))
+                .unwrap_or_else(|| icao24.to_string())
+        };
+
+        let predicted = Color32::from_rgb(255, 170, 0);
+        let active = Color32::from_rgb(255, 50, 50);
+
+        for conflict in &visible {
+            let color = if conflict.is_active() { active } else { predicted };
+            let (Some(a), Some(b)) = (
+                self.geo_to_screen(&conflict.positions[0], user_location),
+                self.geo_to_screen(&conflict.positions[1], user_location),
+            ) else {
+                continue;
+            };
+
+            painter.add(Shape::dashed_line(&[a, b], Stroke::new(1.5, color), 6.0, 4.0));
+            painter.text(
+                a + (b - a) * 0.5,
+                egui::Align2::CENTER_BOTTOM,
+                format!("{:.0}s", conflict.time_to_loss_secs),
+                egui::FontId::monospace(10.0),
+                color,
+            );
+        }
+
+        // Alert list in the top-left corner of the scope
+        let origin = Pos2::new(self.center.x - self.radius, self.center.y - self.radius);
+        painter.text(
+            origin,
+            egui::Align2::LEFT_TOP,
+            format!("CONFLICTS ({})", visible.len()),
+            egui::FontId::monospace(12.0),
+            active,
+        );
+        for (row, conflict) in visible.iter().take(5).enumerate() {
+            let color = if conflict.is_active() { active } else { predicted };
+            painter.text(
+                origin + Vec2::new(0.0, 16.0 * (row + 1) as f32),
+                egui::Align2::LEFT_TOP,
+                format!(
+                    "{} / {}  {:.0}s  {:.1}nm {:.0}ft",
+                    label(&conflict.icao24[0]),
+                    label(&conflict.icao24[1]),
+                    conflict.time_to_loss_secs,
+                    conflict.min_lateral_nm,
+                    conflict.vertical_ft_at_min
+                ),
+                egui::FontId::monospace(10.0),
+                color,
+            );
+        }
+    }
+
+    pub fn aircraft_color(&self, aircraft: &Aircraft, config: &AppConfig) -> Color32 {
+        match config.symbol_coloring {
+            SymbolColoring::Altitude => aircraft.altitude_band().color(),
+            SymbolColoring::FlightPhase => self
+                .phases
+                .get(&aircraft.icao24)
+                .copied()
+                .unwrap_or_default()
+                .color(),
+        }
+    }
+
+    pub fn trail(&self, icao24: &str) -> Option<&AircraftTrail> {
+        self.aircraft_trails.get(icao24)
+    }
+
+    // Closest aircraft symbol within a few pixels of `pos`
+    pub fn aircraft_at(&self, pos: Pos2, aircraft: &[Aircraft], user_location: &Location) -> Option<String> {
+        aircraft
+            .iter()
+            .filter_map(|a| {
+                let screen_pos = self.geo_to_screen(&a.position()?, user_location)?;
+                Some((a, screen_pos.distance(pos)))
+            })
+            .filter(|(_, distance)| *distance <= 12.0)
+            .min_by(|a, b| a.1.total_cmp(&b.1))
+            .map(|(a, _)| a.icao24.clone())
+    }
+
+    fn draw_aircraft(&self, painter: &Painter, aircraft: &[Aircraft], config: &AppConfig, user_location: &Location) {
         for aircraft in aircraft {
             if let Some(position) = aircraft.position() {
                 if let Some(screen_pos) = self.geo_to_screen(&position, user_location) {
-                    self.draw_aircraft_icon(painter, aircraft, screen_pos);
+                    let mut color = self.aircraft_color(aircraft, config);
+                    if config.sweep.enabled {
+                        let angle = sweep::screen_angle(screen_pos - self.center);
+                        color = color.gamma_multiply(self.sweep.intensity(angle, &config.sweep));
+                    }
+                    if self.selected.as_ref() == Some(&aircraft.icao24) {
+                        painter.rect_stroke(
+                            Rect::from_center_size(screen_pos, Vec2::splat(22.0)),
+                            2.0,
+                            Stroke::new(1.5, Color32::WHITE),
+                        );
+                    }
+                    if self.in_airspace.contains(&aircraft.icao24) {
+                        painter.circle_stroke(screen_pos, 12.0, Stroke::new(1.0, Color32::from_rgb(200, 120, 255)));
+                    }
+                    if self.watched.contains(&aircraft.icao24) {
+                        let gold = Color32::from_rgb(255, 200, 40);
+                        let diamond = [(0.0, -15.0), (15.0, 0.0), (0.0, 15.0), (-15.0, 0.0)]
+                            .map(|(dx, dy)| screen_pos + Vec2::new(dx, dy));
+                        painter.add(Shape::closed_line(diamond.to_vec(), Stroke::new(1.5, gold)));
+                    }
+                    self.draw_aircraft_icon(painter, aircraft, color, screen_pos);
                 }
             }
         }
     }
 
-    fn draw_aircraft_icon(&self, painter: &Painter, aircraft: &Aircraft, pos: Pos2) {
-        let color = aircraft.altitude_band().color();
+    fn draw_aircraft_icon(&self, painter: &Painter, aircraft: &Aircraft, color: Color32, pos: Pos2) {
         let size = 8.0;
         
-        // Draw aircraft as a triangle pointing in the direction of travel
+        // Draw aircraft as a triangle pointing in the direction of travel,
+        // relative to whatever bearing is at the top of the scope
         if let Some(heading) = aircraft.true_track {
-            let heading_rad = heading * PI / 180.0;
+            let heading_rad = self.transform().screen_bearing(heading) * PI / 180.0;
+            let point = |angle: f64, length: f32| {
+                Pos2::new(pos.x + length * angle.sin() as f32, pos.y - length * angle.cos() as f32)
+            };
             let points = vec![
-                Pos2::new(
-                    pos.x + size * heading_rad.cos() as f32,
-                    pos.y - size * heading_rad.sin() as f32,
-                ),
-                Pos2::new(
-                    pos.x + size * 0.5 * (heading_rad + 2.5).cos() as f32,
-                    pos.y - size * 0.5 * (heading_rad + 2.5).sin() as f32,
-                ),
-                Pos2::new(
-                    pos.x + size * 0.5 * (heading_rad - 2.5).cos() as f32,
-                    pos.y - size * 0.5 * (heading_rad - 2.5).sin() as f32,
-                ),
+                point(heading_rad, size),
+                point(heading_rad + 2.5, size * 0.5),
+                point(heading_rad - 2.5, size * 0.5),
             ];
             
             painter.add(Shape::convex_polygon(
@@ -184,41 +1111,34 @@ impl RadarView {
             // Draw as a circle if no heading available
             painter.circle_filled(pos, size as f32, color);
         }
-
-        // Draw aircraft label
-        let label = aircraft.display_name();
-        if !label.is_empty() {
-            let label_pos = Pos2::new(pos.x, pos.y - 15.0);
-            painter.text(
-                label_pos,
-                egui::Align2::CENTER_CENTER,
-                &label,
-                egui::FontId::proportional(10.0),
-                Color32::WHITE,
-            );
-        }
     }
 
-    fn draw_center_marker(&self, painter: &Painter, user_location: &Location) {
+    // Home cross; only at the centre of the scope when not following
+    fn draw_center_marker(&self, painter: &Painter, config: &AppConfig, user_location: &Location) {
+        let home = &config.location;
+        let Some(center) = self.geo_to_screen(&home.to_point(), user_location) else {
+            return;
+        };
+
         // Draw center cross
         let cross_size = 10.0;
         let stroke = Stroke::new(2.0, Color32::WHITE);
         
         painter.line_segment(
-            [Pos2::new(self.center.x - cross_size, self.center.y), 
-             Pos2::new(self.center.x + cross_size, self.center.y)],
+            [Pos2::new(center.x - cross_size, center.y), 
+             Pos2::new(center.x + cross_size, center.y)],
             stroke,
         );
         painter.line_segment(
-            [Pos2::new(self.center.x, self.center.y - cross_size), 
-             Pos2::new(self.center.x, self.center.y + cross_size)],
+            [Pos2::new(center.x, center.y - cross_size), 
+             Pos2::new(center.x, center.y + cross_size)],
             stroke,
         );
 
         // Draw location label
-        let location_name = user_location.name.as_deref().unwrap_or("Your Location");
+        let location_name = home.name.as_deref().unwrap_or("Your Location");
         painter.text(
-            Pos2::new(self.center.x, self.center.y + 25.0),
+            Pos2::new(center.x, center.y + 25.0),
             egui::Align2::CENTER_CENTER,
             location_name,
             egui::FontId::proportional(12.0),
@@ -226,24 +1146,58 @@ impl RadarView {
         );
     }
 
+    fn pixels_per_km(&self) -> f32 {
+        self.radius / self.range_km.max(0.1) as f32 * self.scale
+    }
+
+    fn scope_rect(&self) -> Rect {
+        Rect::from_center_size(self.center, Vec2::splat(self.radius * 2.0))
+    }
+
+    fn transform(&self) -> ScreenTransform {
+        ScreenTransform::new(self.center, self.pixels_per_km(), self.rotation_deg)
+    }
+
+    // Lon/lat box covering the whole scope at any rotation
+    fn view_bounds(&self, user_location: &Location) -> geo::Rect<f64> {
+        self.bounds_of(self.scope_rect(), user_location)
+    }
+
+    fn bounds_of(&self, scope: Rect, user_location: &Location) -> geo::Rect<f64> {
+        let corners = [scope.left_top(), scope.right_top(), scope.left_bottom(), scope.right_bottom()]
+            .map(|corner| self.screen_to_geo(corner, user_location));
+        let (mut min, mut max) = (coord! { x: f64::MAX, y: f64::MAX }, coord! { x: f64::MIN, y: f64::MIN });
+        for corner in corners {
+            min = coord! { x: min.x.min(corner.x()), y: min.y.min(corner.y()) };
+            max = coord! { x: max.x.max(corner.x()), y: max.y.max(corner.y()) };
+        }
+        geo::Rect::new(min, max)
+    }
+
+    // Local equirectangular projection centred on the user location
+    fn project(&self, geo_point: &Point<f64>, user_location: &Location) -> Pos2 {
+        let (east_km, north_km) = user_location.offset_km_to(&Location::from_point(*geo_point));
+        self.transform().to_screen(east_km, north_km)
+    }
+
     fn geo_to_screen(&self, geo_point: &Point<f64>, user_location: &Location) -> Option<Pos2> {
-        // Simple projection - could be improved with proper map projection
-        let lat_diff = geo_point.y() - user_location.lat;
-        let lon_diff = geo_point.x() - user_location.lon;
-        
-        // Convert to screen coordinates (rough approximation)
-        let x = self.center.x + (lon_diff * 1000000.0) as f32 * self.scale;
-        let y = self.center.y - (lat_diff * 1000000.0) as f32 * self.scale;
-        
+        let pos = self.project(geo_point, user_location);
+
         // Check if within radar range
-        let distance = ((x - self.center.x).powi(2) + (y - self.center.y).powi(2)).sqrt();
+        let distance = pos.distance(self.center);
         if distance <= self.radius {
-            Some(Pos2::new(x, y))
+            Some(pos)
         } else {
             None
         }
     }
 
+    pub fn screen_to_geo(&self, pos: Pos2, user_location: &Location) -> Point<f64> {
+        let (east_km, north_km) = self.transform().to_local(pos);
+
+        user_location.offset_by_km(east_km, north_km).to_point()
+    }
+
     pub fn set_scale(&mut self, scale: f32) {
         self.scale = scale.clamp(0.1, 5.0);
     }
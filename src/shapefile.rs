@@ -0,0 +1,222 @@
+// Reader for the geometry part (.shp) of an ESRI Shapefile. Attributes in the
+// .dbf are not needed for overlays and are ignored. Coordinates are assumed
+// to be WGS84 longitude/latitude, which is what Natural Earth and most
+// OpenStreetMap extracts ship with.
+
+use anyhow::{anyhow, Context, Result};
+use geo::{Coord, Geometry, LineString, MultiLineString, MultiPoint, MultiPolygon, Point, Polygon};
+use std::path::Path;
+
+const FILE_CODE: i32 = 9994;
+const HEADER_LEN: usize = 100;
+
+pub fn read_file(path: &Path) -> Result<Vec<Geometry<f64>>> {
+    let bytes = std::fs::read(path).with_context(|| format!("Failed to read {}", path.display()))?;
+    parse(&bytes).with_context(|| format!("Invalid shapefile {}", path.display()))
+}
+
+pub fn parse(bytes: &[u8]) -> Result<Vec<Geometry<f64>>> {
+    if bytes.len() < HEADER_LEN || be_i32(bytes, 0)? != FILE_CODE {
+        return Err(anyhow!("Not a shapefile"));
+    }
+
+    let mut geometries = Vec::new();
+    let mut offset = HEADER_LEN;
+    while offset + 8 <= bytes.len() {
+        // Record length is counted in 16-bit words and excludes the record header
+        let words = count(be_i32(bytes, offset + 4)?, "record length")?;
+        let start = offset + 8;
+        let end = words
+            .checked_mul(2)
+            .and_then(|length| start.checked_add(length))
+            .filter(|end| *end <= bytes.len())
+            .ok_or_else(|| anyhow!("Truncated record at byte {}", offset))?;
+        if let Some(geometry) = parse_record(&bytes[start..end])? {
+            geometries.push(geometry);
+        }
+        offset = end;
+    }
+    Ok(geometries)
+}
+
+fn parse_record(content: &[u8]) -> Result<Option<Geometry<f64>>> {
+    // Z and M variants share the 2D layout and append their extra arrays
+    let geometry = match le_i32(content, 0)? {
+        0 => return Ok(None),
+        1 | 11 | 21 => Geometry::Point(Point::from(coord(content, 4)?)),
+        8 | 18 | 28 => {
+            let num_points = count(le_i32(content, 36)?, "point count")?;
+            array_end(content, 40, num_points, 16)?;
+            let points = (0..num_points)
+                .map(|i| coord(content, 40 + i * 16).map(Point::from))
+                .collect::<Result<_>>()?;
+            Geometry::MultiPoint(MultiPoint(points))
+        }
+        3 | 13 | 23 => Geometry::MultiLineString(MultiLineString(parts(content)?)),
+        5 | 15 | 25 => {
+            // Rings are not grouped into polygons; each becomes its own
+            // polygon, which is all an outline renderer needs
+            let polygons = parts(content)?
+                .into_iter()
+                .map(|ring| Polygon::new(ring, Vec::new()))
+                .collect();
+            Geometry::MultiPolygon(MultiPolygon(polygons))
+        }
+        other => return Err(anyhow!("Unsupported shape type {}", other)),
+    };
+    Ok(Some(geometry))
+}
+
+fn parts(content: &[u8]) -> Result<Vec<LineString<f64>>> {
+    let num_parts = count(le_i32(content, 36)?, "part count")?;
+    let num_points = count(le_i32(content, 40)?, "point count")?;
+    // Both arrays must fit in the record before anything is allocated for them
+    let points_start = array_end(content, 44, num_parts, 4)?;
+    array_end(content, points_start, num_points, 16)?;
+
+    let mut starts = (0..num_parts)
+        .map(|i| le_i32(content, 44 + i * 4).and_then(|s| count(s, "part index")))
+        .collect::<Result<Vec<_>>>()?;
+    starts.push(num_points);
+
+    starts
+        .windows(2)
+        .map(|range| {
+            if range[0] > range[1] || range[1] > num_points {
+                return Err(anyhow!("Invalid part index"));
+            }
+            (range[0]..range[1])
+                .map(|i| coord(content, points_start + i * 16))
+                .collect::<Result<Vec<_>>>()
+                .map(LineString::from)
+        })
+        .collect()
+}
+
+// Counts and lengths in the file are signed; negative ones mean corruption
+fn count(value: i32, what: &str) -> Result<usize> {
+    usize::try_from(value).map_err(|_| anyhow!("Invalid {} {}", what, value))
+}
+
+// End of an array of `len` items of `item_size` bytes starting at `start`,
+// checked against the data actually present
+fn array_end(bytes: &[u8], start: usize, len: usize, item_size: usize) -> Result<usize> {
+    len.checked_mul(item_size)
+        .and_then(|size| start.checked_add(size))
+        .filter(|end| *end <= bytes.len())
+        .ok_or_else(|| anyhow!("Array of {} items overruns the record", len))
+}
+
+fn coord(bytes: &[u8], offset: usize) -> Result<Coord<f64>> {
+    Ok(Coord {
+        x: le_f64(bytes, offset)?,
+        y: le_f64(bytes, offset + 8)?,
+    })
+}
+
+fn slice<const N: usize>(bytes: &[u8], offset: usize) -> Result<[u8; N]> {
+    bytes
+        .get(offset..offset + N)
+        .and_then(|b| b.try_into().ok())
+        .ok_or_else(|| anyhow!("Unexpected end of data at byte {}", offset))
+}
+
+fn be_i32(bytes: &[u8], offset: usize) -> Result<i32> {
+    slice(bytes, offset).map(i32::from_be_bytes)
+}
+
+fn le_i32(bytes: &[u8], offset: usize) -> Result<i32> {
+    slice(bytes, offset).map(i32::from_le_bytes)
+}
+
+fn le_f64(bytes: &[u8], offset: usize) -> Result<f64> {
+    slice(bytes, offset).map(f64::from_le_bytes)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn polyline_record(shape_type: i32, parts: &[&[(f64, f64)]]) -> Vec<u8> {
+        let num_points: usize = parts.iter().map(|p| p.len()).sum();
+        let mut content = Vec::new();
+        content.extend(shape_type.to_le_bytes());
+        content.extend([0u8; 32]); // bounding box
+        content.extend((parts.len() as i32).to_le_bytes());
+        content.extend((num_points as i32).to_le_bytes());
+        let mut start = 0;
+        for part in parts {
+            content.extend((start as i32).to_le_bytes());
+            start += part.len();
+        }
+        for &(x, y) in parts.iter().flat_map(|p| p.iter()) {
+            content.extend(x.to_le_bytes());
+            content.extend(y.to_le_bytes());
+        }
+
+        let mut record = Vec::new();
+        record.extend(1i32.to_be_bytes());
+        record.extend(((content.len() / 2) as i32).to_be_bytes());
+        record.extend(content);
+        record
+    }
+
+    fn shapefile(records: &[Vec<u8>]) -> Vec<u8> {
+        let mut bytes = vec![0u8; HEADER_LEN];
+        bytes[..4].copy_from_slice(&FILE_CODE.to_be_bytes());
+        for record in records {
+            bytes.extend(record);
+        }
+        bytes
+    }
+
+    #[test]
+    fn test_parse_polyline_and_polygon() {
+        let bytes = shapefile(&[
+            polyline_record(3, &[&[(0.0, 51.0), (1.0, 51.5)], &[(2.0, 52.0), (3.0, 52.0), (4.0, 53.0)]]),
+            polyline_record(5, &[&[(0.0, 0.0), (1.0, 0.0), (1.0, 1.0), (0.0, 0.0)]]),
+        ]);
+        let geometries = parse(&bytes).unwrap();
+        assert_eq!(geometries.len(), 2);
+
+        let Geometry::MultiLineString(lines) = &geometries[0] else {
+            panic!("expected lines");
+        };
+        assert_eq!(lines.0.len(), 2);
+        assert_eq!(lines.0[1].0.len(), 3);
+        assert_eq!(lines.0[0].0[1], Coord { x: 1.0, y: 51.5 });
+
+        assert!(matches!(&geometries[1], Geometry::MultiPolygon(p) if p.0.len() == 1));
+    }
+
+    #[test]
+    fn test_rejects_bad_input() {
+        assert!(parse(b"not a shapefile").is_err());
+
+        let mut truncated = shapefile(&[polyline_record(3, &[&[(0.0, 0.0), (1.0, 1.0)]])]);
+        truncated.truncate(truncated.len() - 8);
+        assert!(parse(&truncated).is_err());
+    }
+
+    #[test]
+    fn test_rejects_corrupt_counts() {
+        let record = polyline_record(3, &[&[(0.0, 0.0), (1.0, 1.0)]]);
+
+        // Negative record length
+        let mut negative = record.clone();
+        negative[4..8].copy_from_slice(&(-4i32).to_be_bytes());
+        assert!(parse(&shapefile(&[negative])).is_err());
+
+        // Huge record length
+        let mut huge = record.clone();
+        huge[4..8].copy_from_slice(&i32::MAX.to_be_bytes());
+        assert!(parse(&shapefile(&[huge])).is_err());
+
+        // Part and point counts beyond the record, and negative ones
+        for (offset, value) in [(8 + 36, i32::MAX), (8 + 36, -1), (8 + 40, i32::MAX), (8 + 40, -1)] {
+            let mut corrupt = record.clone();
+            corrupt[offset..offset + 4].copy_from_slice(&value.to_le_bytes());
+            assert!(parse(&shapefile(&[corrupt])).is_err(), "offset {} value {}", offset, value);
+        }
+    }
+}
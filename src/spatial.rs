@@ -0,0 +1,70 @@
+use geo::{BoundingRect, Geometry, Point};
+use std::collections::HashMap;
+
+// Uniform lat/lon grid over bounding boxes. Each item is registered in every
+// cell its box touches; point queries return the items registered in the
+// cell containing the point, which callers then test exactly.
+#[derive(Debug, Clone)]
+pub struct BoxGrid {
+    cell_deg: f64,
+    cells: HashMap<(i32, i32), Vec<usize>>,
+}
+
+impl BoxGrid {
+    pub fn new(cell_deg: f64) -> Self {
+        Self {
+            cell_deg,
+            cells: HashMap::new(),
+        }
+    }
+
+    // Bounds are (min lon, min lat, max lon, max lat)
+    pub fn insert(&mut self, id: usize, bounds: (f64, f64, f64, f64)) {
+        let (min_x, min_y, max_x, max_y) = bounds;
+        if min_x > max_x || min_y > max_y {
+            return;
+        }
+        for row in self.cell(min_y)..=self.cell(max_y) {
+            for col in self.cell(min_x)..=self.cell(max_x) {
+                self.cells.entry((row, col)).or_default().push(id);
+            }
+        }
+    }
+
+    pub fn insert_geometry(&mut self, id: usize, geometry: &Geometry<f64>) {
+        if let Some(rect) = geometry.bounding_rect() {
+            self.insert(id, (rect.min().x, rect.min().y, rect.max().x, rect.max().y));
+        }
+    }
+
+    pub fn query(&self, point: Point<f64>) -> &[usize] {
+        self.cells
+            .get(&(self.cell(point.y()), self.cell(point.x())))
+            .map(Vec::as_slice)
+            .unwrap_or(&[])
+    }
+
+    pub fn clear(&mut self) {
+        self.cells.clear();
+    }
+
+    fn cell(&self, degrees: f64) -> i32 {
+        (degrees / self.cell_deg).floor() as i32
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_box_grid_query() {
+        let mut grid = BoxGrid::new(0.5);
+        grid.insert(0, (-0.2, 51.3, 0.1, 51.7));
+        grid.insert(1, (2.0, 48.5, 2.6, 49.0));
+
+        assert_eq!(grid.query(Point::new(-0.1, 51.5)), &[0]);
+        assert_eq!(grid.query(Point::new(2.3, 48.8)), &[1]);
+        assert!(grid.query(Point::new(10.0, 10.0)).is_empty());
+    }
+}
@@ -0,0 +1,104 @@
+use egui::Vec2;
+use serde::{Deserialize, Serialize};
+
+// Long frames (window hidden, debugger) would otherwise make the sweep jump
+const MAX_FRAME_SECS: f64 = 0.25;
+// Targets never fade out completely so nothing disappears from the scope
+const MIN_INTENSITY: f32 = 0.08;
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct SweepSettings {
+    pub enabled: bool,
+    // Time for one full rotation
+    pub period_secs: f64,
+    // Time for a painted target to fade to about a third of its brightness
+    pub persistence_secs: f64,
+}
+
+impl Default for SweepSettings {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            period_secs: 4.0,
+            persistence_secs: 2.5,
+        }
+    }
+}
+
+// Classic rotating sweep, advanced by frame time so the animation is smooth
+// regardless of how often aircraft data refreshes
+#[derive(Debug, Clone, Default)]
+pub struct Sweep {
+    // Screen angle of the sweep line, clockwise from the top of the scope
+    angle_deg: f64,
+}
+
+impl Sweep {
+    pub fn angle_deg(&self) -> f64 {
+        self.angle_deg
+    }
+
+    pub fn advance(&mut self, frame_secs: f64, settings: &SweepSettings) {
+        let dt = frame_secs.clamp(0.0, MAX_FRAME_SECS);
+        self.angle_deg = (self.angle_deg + dt * 360.0 / settings.period_secs.max(0.1)).rem_euclid(360.0);
+    }
+
+    // Seconds since the sweep last passed `angle_deg`
+    pub fn secs_since_pass(&self, angle_deg: f64, settings: &SweepSettings) -> f64 {
+        (self.angle_deg - angle_deg).rem_euclid(360.0) / 360.0 * settings.period_secs
+    }
+
+    // Phosphor brightness of a target at a screen angle: full as the sweep
+    // paints it, then decaying exponentially until the next pass
+    pub fn intensity(&self, angle_deg: f64, settings: &SweepSettings) -> f32 {
+        let elapsed = self.secs_since_pass(angle_deg, settings);
+        let decay = (-elapsed / settings.persistence_secs.max(0.1)).exp() as f32;
+        decay.max(MIN_INTENSITY)
+    }
+}
+
+// Screen angle of an offset from the scope centre, clockwise from up
+pub fn screen_angle(offset: Vec2) -> f64 {
+    (offset.x as f64).atan2(-offset.y as f64).to_degrees().rem_euclid(360.0)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_advance_wraps() {
+        let settings = SweepSettings::default();
+        let mut sweep = Sweep::default();
+        for _ in 0..20 {
+            sweep.advance(0.25, &settings);
+        }
+        // 5 s at a 4 s period is a quarter past one full turn
+        assert!((sweep.angle_deg() - 90.0).abs() < 1e-6);
+
+        // A stalled frame only moves the sweep a little
+        sweep.advance(10.0, &settings);
+        assert!((sweep.angle_deg() - 112.5).abs() < 1e-6);
+    }
+
+    #[test]
+    fn test_intensity_decays_after_pass() {
+        let settings = SweepSettings::default();
+        let mut sweep = Sweep::default();
+        sweep.advance(0.25, &settings);
+
+        let just_painted = sweep.intensity(20.0, &settings);
+        let earlier = sweep.intensity(0.0, &settings);
+        let long_ago = sweep.intensity(40.0, &settings);
+        assert!(just_painted > earlier);
+        assert!(earlier > long_ago);
+        assert!(long_ago >= MIN_INTENSITY);
+    }
+
+    #[test]
+    fn test_screen_angle() {
+        assert!((screen_angle(Vec2::new(0.0, -1.0)) - 0.0).abs() < 1e-6);
+        assert!((screen_angle(Vec2::new(1.0, 0.0)) - 90.0).abs() < 1e-6);
+        assert!((screen_angle(Vec2::new(-1.0, 0.0)) - 270.0).abs() < 1e-6);
+    }
+}
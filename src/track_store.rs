@@ -0,0 +1,195 @@
+use crate::aircraft::Aircraft;
+use anyhow::{Context, Result};
+use chrono::{DateTime, Duration, Utc};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::io::{BufRead, BufReader, BufWriter, Write};
+use std::path::Path;
+
+// One position report kept for history views
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct TrackPoint {
+    pub icao24: String,
+    pub time: DateTime<Utc>,
+    pub lat: f64,
+    pub lon: f64,
+    pub altitude: Option<f64>,
+    pub velocity: Option<f64>,
+    pub true_track: Option<f64>,
+    pub vertical_rate: Option<f64>,
+}
+
+impl TrackPoint {
+    pub fn from_aircraft(aircraft: &Aircraft, now: DateTime<Utc>) -> Option<Self> {
+        Some(Self {
+            icao24: aircraft.icao24.clone(),
+            time: aircraft.time_position.unwrap_or(now),
+            lat: aircraft.latitude?,
+            lon: aircraft.longitude?,
+            altitude: aircraft.altitude,
+            velocity: aircraft.velocity,
+            true_track: aircraft.true_track,
+            vertical_rate: aircraft.vertical_rate,
+        })
+    }
+}
+
+// Position history for every aircraft seen, kept for a retention window.
+// Unlike radar trails this isn't capped per aircraft, so it can feed
+// heatmaps and charts. Sessions are saved as one JSON point per line.
+#[derive(Debug, Clone)]
+pub struct TrackStore {
+    tracks: HashMap<String, Vec<TrackPoint>>,
+    // None keeps everything, as for recorded sessions
+    retention: Option<Duration>,
+}
+
+impl TrackStore {
+    pub fn new(retention_minutes: u64) -> Self {
+        Self {
+            tracks: HashMap::new(),
+            retention: Some(Duration::minutes(retention_minutes as i64)),
+        }
+    }
+
+    pub fn set_retention(&mut self, retention_minutes: u64) {
+        self.retention = Some(Duration::minutes(retention_minutes as i64));
+    }
+
+    pub fn record(&mut self, aircraft: &[Aircraft], now: DateTime<Utc>) {
+        for point in aircraft.iter().filter_map(|a| TrackPoint::from_aircraft(a, now)) {
+            self.insert(point);
+        }
+        self.prune(now);
+    }
+
+    pub fn insert(&mut self, point: TrackPoint) {
+        let track = self.tracks.entry(point.icao24.clone()).or_default();
+        // Repeated reports of the same fix add nothing
+        if track.last().is_some_and(|last| last.time >= point.time) {
+            return;
+        }
+        track.push(point);
+    }
+
+    pub fn prune(&mut self, now: DateTime<Utc>) {
+        let Some(retention) = self.retention else {
+            return;
+        };
+        let cutoff = now - retention;
+        for track in self.tracks.values_mut() {
+            track.retain(|p| p.time >= cutoff);
+        }
+        self.tracks.retain(|_, track| !track.is_empty());
+    }
+
+    pub fn track(&self, icao24: &str) -> &[TrackPoint] {
+        self.tracks.get(icao24).map(Vec::as_slice).unwrap_or(&[])
+    }
+
+    // Per-aircraft tracks trimmed to points at or after `since`
+    pub fn tracks_since(&self, since: DateTime<Utc>) -> impl Iterator<Item = &[TrackPoint]> + '_ {
+        self.tracks.values().filter_map(move |track| {
+            let start = track.partition_point(|p| p.time < since);
+            (start < track.len()).then(|| &track[start..])
+        })
+    }
+
+    // Time of the newest point, for windows over recorded sessions
+    pub fn latest(&self) -> Option<DateTime<Utc>> {
+        self.tracks.values().filter_map(|t| t.last()).map(|p| p.time).max()
+    }
+
+    pub fn point_count(&self) -> usize {
+        self.tracks.values().map(Vec::len).sum()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.tracks.is_empty()
+    }
+
+    pub fn save_session(&self, path: &Path) -> Result<()> {
+        let file = std::fs::File::create(path).with_context(|| format!("Failed to create {}", path.display()))?;
+        let mut writer = BufWriter::new(file);
+        for point in self.tracks.values().flatten() {
+            serde_json::to_writer(&mut writer, point)?;
+            writer.write_all(b"\n")?;
+        }
+        writer.flush()?;
+        Ok(())
+    }
+
+    pub fn load_session(path: &Path) -> Result<Self> {
+        let file = std::fs::File::open(path).with_context(|| format!("Failed to read {}", path.display()))?;
+        let mut points = Vec::new();
+        for (number, line) in BufReader::new(file).lines().enumerate() {
+            let line = line?;
+            if line.trim().is_empty() {
+                continue;
+            }
+            let point: TrackPoint =
+                serde_json::from_str(&line).with_context(|| format!("{}:{}", path.display(), number + 1))?;
+            points.push(point);
+        }
+        points.sort_by_key(|p| p.time);
+
+        // Recorded sessions are kept whole regardless of age
+        let mut store = Self {
+            tracks: HashMap::new(),
+            retention: None,
+        };
+        for point in points {
+            store.insert(point);
+        }
+        Ok(store)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn aircraft(icao24: &str, lat: f64, time: DateTime<Utc>) -> Aircraft {
+        let mut aircraft = Aircraft::new(icao24.to_string());
+        aircraft.latitude = Some(lat);
+        aircraft.longitude = Some(0.0);
+        aircraft.time_position = Some(time);
+        aircraft
+    }
+
+    #[test]
+    fn test_record_and_prune() {
+        let start = Utc::now();
+        let mut store = TrackStore::new(10);
+        store.record(&[aircraft("aaaaaa", 51.0, start)], start);
+        // Same fix reported again is ignored
+        store.record(&[aircraft("aaaaaa", 51.0, start)], start);
+        store.record(&[aircraft("aaaaaa", 51.1, start + Duration::minutes(5))], start + Duration::minutes(5));
+        assert_eq!(store.track("aaaaaa").len(), 2);
+
+        let since = start + Duration::minutes(1);
+        assert_eq!(store.tracks_since(since).map(|t| t.len()).sum::<usize>(), 1);
+
+        store.prune(start + Duration::minutes(12));
+        assert_eq!(store.point_count(), 1);
+        store.prune(start + Duration::minutes(20));
+        assert!(store.is_empty());
+    }
+
+    #[test]
+    fn test_session_round_trip() {
+        let start = Utc::now();
+        let mut store = TrackStore::new(60);
+        store.record(&[aircraft("aaaaaa", 51.0, start), aircraft("bbbbbb", 52.0, start)], start);
+        store.record(&[aircraft("aaaaaa", 51.1, start + Duration::seconds(30))], start);
+
+        let path = std::env::temp_dir().join(format!("skyradar-session-{}.jsonl", std::process::id()));
+        store.save_session(&path).unwrap();
+        let loaded = TrackStore::load_session(&path).unwrap();
+        std::fs::remove_file(&path).ok();
+
+        assert_eq!(loaded.point_count(), 3);
+        assert_eq!(loaded.track("aaaaaa"), store.track("aaaaaa"));
+        assert_eq!(loaded.latest(), Some(start + Duration::seconds(30)));
+    }
+}
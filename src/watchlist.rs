@@ -0,0 +1,351 @@
+use crate::aircraft::Aircraft;
+use crate::geo::Location;
+use anyhow::{Context, Result};
+use chrono::{DateTime, Utc};
+use regex::{Regex, RegexBuilder};
+use serde::{Deserialize, Serialize};
+use std::collections::HashSet;
+use std::path::{Path, PathBuf};
+use tracing::warn;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum WatchKind {
+    Icao24,
+    Registration,
+    Callsign,
+    Type,
+}
+
+impl WatchKind {
+    pub const ALL: [WatchKind; 4] = [WatchKind::Icao24, WatchKind::Registration, WatchKind::Callsign, WatchKind::Type];
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            WatchKind::Icao24 => "ICAO24",
+            WatchKind::Registration => "Registration",
+            WatchKind::Callsign => "Callsign pattern",
+            WatchKind::Type => "Type",
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[serde(rename_all = "snake_case")]
+pub enum WatchMatch {
+    Icao24(String),
+    // The feed carries no tail numbers, so registrations are matched against
+    // the callsign, which is what most GA flights transmit
+    Registration(String),
+    // Case-insensitive regular expression
+    Callsign(String),
+    // Emitter category code as shown in data blocks, e.g. "H" or "ROT"
+    Type(String),
+}
+
+impl WatchMatch {
+    pub fn new(kind: WatchKind, value: &str) -> Self {
+        let value = value.trim().to_string();
+        match kind {
+            WatchKind::Icao24 => WatchMatch::Icao24(value.to_ascii_lowercase()),
+            WatchKind::Registration => WatchMatch::Registration(value.to_ascii_uppercase()),
+            WatchKind::Callsign => WatchMatch::Callsign(value),
+            WatchKind::Type => WatchMatch::Type(value.to_ascii_uppercase()),
+        }
+    }
+
+    pub fn kind(&self) -> WatchKind {
+        match self {
+            WatchMatch::Icao24(_) => WatchKind::Icao24,
+            WatchMatch::Registration(_) => WatchKind::Registration,
+            WatchMatch::Callsign(_) => WatchKind::Callsign,
+            WatchMatch::Type(_) => WatchKind::Type,
+        }
+    }
+
+    pub fn value(&self) -> &str {
+        match self {
+            WatchMatch::Icao24(v) | WatchMatch::Registration(v) | WatchMatch::Callsign(v) | WatchMatch::Type(v) => v,
+        }
+    }
+
+    fn pattern(&self) -> Result<Option<Regex>> {
+        match self {
+            WatchMatch::Callsign(pattern) => RegexBuilder::new(pattern)
+                .case_insensitive(true)
+                .build()
+                .map(Some)
+                .with_context(|| format!("Invalid callsign pattern `{}`", pattern)),
+            _ => Ok(None),
+        }
+    }
+
+    fn matches(&self, aircraft: &Aircraft, pattern: Option<&Regex>) -> bool {
+        let callsign = aircraft.callsign.as_deref().map(str::trim).unwrap_or("");
+        match self {
+            WatchMatch::Icao24(icao24) => aircraft.icao24.eq_ignore_ascii_case(icao24),
+            WatchMatch::Registration(registration) => {
+                let normalize = |s: &str| s.replace('-', "").to_ascii_uppercase();
+                !callsign.is_empty() && normalize(callsign) == normalize(registration)
+            }
+            WatchMatch::Callsign(_) => !callsign.is_empty() && pattern.is_some_and(|p| p.is_match(callsign)),
+            WatchMatch::Type(code) => aircraft.category_code().is_some_and(|c| c.eq_ignore_ascii_case(code)),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct WatchEntry {
+    pub matcher: WatchMatch,
+    #[serde(default)]
+    pub note: String,
+    pub added: DateTime<Utc>,
+}
+
+#[derive(Debug, Default, Serialize, Deserialize)]
+struct WatchlistFile {
+    #[serde(default)]
+    watch: Vec<WatchEntry>,
+}
+
+// Aircraft the user wants to hear about, saved next to the config file
+#[derive(Debug, Default)]
+pub struct Watchlist {
+    entries: Vec<WatchEntry>,
+    // Compiled callsign patterns, parallel to `entries`. Entries loaded with
+    // a pattern that no longer compiles are kept, so saving doesn't lose
+    // them, but never match.
+    patterns: Vec<Result<Option<Regex>, String>>,
+}
+
+impl Watchlist {
+    pub fn default_path() -> PathBuf {
+        crate::config::AppConfig::config_file_path().with_file_name("watchlist.toml")
+    }
+
+    pub fn load(path: &Path) -> Result<Self> {
+        if !path.exists() {
+            return Ok(Self::default());
+        }
+        let text = std::fs::read_to_string(path).with_context(|| format!("Failed to read {}", path.display()))?;
+        let file: WatchlistFile = toml::from_str(&text).with_context(|| format!("Failed to parse {}", path.display()))?;
+
+        let mut watchlist = Self::default();
+        for entry in file.watch {
+            let pattern = entry.matcher.pattern().map_err(|e| {
+                warn!("Watch entry `{}` will not match: {:#}", entry.matcher.value(), e);
+                format!("{:#}", e)
+            });
+            watchlist.entries.push(entry);
+            watchlist.patterns.push(pattern);
+        }
+        Ok(watchlist)
+    }
+
+    pub fn save(&self, path: &Path) -> Result<()> {
+        if let Some(parent) = path.parent() {
+            std::fs::create_dir_all(parent)?;
+        }
+        let file = WatchlistFile {
+            watch: self.entries.clone(),
+        };
+        std::fs::write(path, toml::to_string_pretty(&file)?)?;
+        Ok(())
+    }
+
+    pub fn entries(&self) -> &[WatchEntry] {
+        &self.entries
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+
+    pub fn add(&mut self, entry: WatchEntry) -> Result<()> {
+        let pattern = entry.matcher.pattern()?;
+        self.entries.push(entry);
+        self.patterns.push(Ok(pattern));
+        Ok(())
+    }
+
+    pub fn remove(&mut self, idx: usize) {
+        if idx < self.entries.len() {
+            self.entries.remove(idx);
+            self.patterns.remove(idx);
+        }
+    }
+
+    pub fn set_note(&mut self, idx: usize, note: String) {
+        if let Some(entry) = self.entries.get_mut(idx) {
+            entry.note = note;
+        }
+    }
+
+    // Why the entry at `idx` can't match, if it was loaded broken
+    pub fn error(&self, idx: usize) -> Option<&str> {
+        self.patterns.get(idx)?.as_ref().err().map(String::as_str)
+    }
+
+    pub fn contains(&self, matcher: &WatchMatch) -> bool {
+        self.entries.iter().any(|e| &e.matcher == matcher)
+    }
+
+    // First entry watching this aircraft
+    pub fn matching(&self, aircraft: &Aircraft) -> Option<&WatchEntry> {
+        self.entries
+            .iter()
+            .zip(&self.patterns)
+            .filter_map(|(entry, pattern)| Some((entry, pattern.as_ref().ok()?)))
+            .find(|(entry, pattern)| entry.matcher.matches(aircraft, pattern.as_ref()))
+            .map(|(entry, _)| entry)
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct WatchAlert {
+    pub icao24: String,
+    pub entry: WatchEntry,
+    pub distance_km: f64,
+}
+
+// Raises one alert when a watched aircraft comes into range. It can alert
+// again after leaving range or dropping out of the feed.
+#[derive(Debug, Default)]
+pub struct WatchMonitor {
+    in_range: HashSet<String>,
+}
+
+impl WatchMonitor {
+    pub fn update(
+        &mut self,
+        aircraft: &[Aircraft],
+        watchlist: &Watchlist,
+        home: &Location,
+        range_km: f64,
+    ) -> Vec<WatchAlert> {
+        let mut alerts = Vec::new();
+        let mut in_range = HashSet::new();
+        for aircraft in aircraft {
+            let Some(entry) = watchlist.matching(aircraft) else {
+                continue;
+            };
+            let Some(distance_km) = aircraft.position().map(|p| home.distance_to(&Location::from_point(p))) else {
+                continue;
+            };
+            if distance_km > range_km {
+                continue;
+            }
+            if !self.in_range.contains(&aircraft.icao24) {
+                alerts.push(WatchAlert {
+                    icao24: aircraft.icao24.clone(),
+                    entry: entry.clone(),
+                    distance_km,
+                });
+            }
+            in_range.insert(aircraft.icao24.clone());
+        }
+        self.in_range = in_range;
+        alerts
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn entry(kind: WatchKind, value: &str) -> WatchEntry {
+        WatchEntry {
+            matcher: WatchMatch::new(kind, value),
+            note: String::new(),
+            added: Utc::now(),
+        }
+    }
+
+    fn aircraft(icao24: &str, callsign: &str, location: &Location) -> Aircraft {
+        let mut aircraft = Aircraft::new(icao24.to_string());
+        aircraft.callsign = Some(format!("{:<8}", callsign));
+        aircraft.latitude = Some(location.lat);
+        aircraft.longitude = Some(location.lon);
+        aircraft
+    }
+
+    #[test]
+    fn test_matchers() {
+        let home = Location::london();
+        let mut helicopter = aircraft("400abc", "GBXYZ", &home);
+        helicopter.category = Some(8);
+
+        let mut watchlist = Watchlist::default();
+        watchlist.add(entry(WatchKind::Registration, "G-BXYZ")).unwrap();
+        assert!(watchlist.matching(&helicopter).is_some());
+
+        let mut watchlist = Watchlist::default();
+        watchlist.add(entry(WatchKind::Type, "rot")).unwrap();
+        watchlist.add(entry(WatchKind::Callsign, "^baw")).unwrap();
+        watchlist.add(entry(WatchKind::Icao24, "A12345")).unwrap();
+        assert_eq!(watchlist.matching(&helicopter).unwrap().matcher.kind(), WatchKind::Type);
+        assert!(watchlist.matching(&aircraft("400001", "BAW123", &home)).is_some());
+        assert!(watchlist.matching(&aircraft("a12345", "", &home)).is_some());
+        assert!(watchlist.matching(&aircraft("400002", "EZY1", &home)).is_none());
+
+        assert!(watchlist.add(entry(WatchKind::Callsign, "(")).is_err());
+        assert_eq!(watchlist.entries().len(), 3);
+    }
+
+    #[test]
+    fn test_alert_once_in_range() {
+        let home = Location::london();
+        let mut watchlist = Watchlist::default();
+        watchlist.add(entry(WatchKind::Icao24, "a12345")).unwrap();
+        let mut monitor = WatchMonitor::default();
+
+        let far = vec![aircraft("a12345", "N1", &home.offset_by_km(0.0, 50.0))];
+        assert!(monitor.update(&far, &watchlist, &home, 20.0).is_empty());
+
+        let near = vec![aircraft("a12345", "N1", &home.offset_by_km(0.0, 10.0))];
+        assert_eq!(monitor.update(&near, &watchlist, &home, 20.0).len(), 1);
+        assert!(monitor.update(&near, &watchlist, &home, 20.0).is_empty());
+
+        // Leaving and coming back alerts again
+        assert!(monitor.update(&[], &watchlist, &home, 20.0).is_empty());
+        assert_eq!(monitor.update(&near, &watchlist, &home, 20.0).len(), 1);
+    }
+
+    #[test]
+    fn test_save_and_load() {
+        let path = std::env::temp_dir().join(format!("skyradar-watchlist-{}.toml", std::process::id()));
+        let mut watchlist = Watchlist::default();
+        watchlist.add(entry(WatchKind::Callsign, "^UAL")).unwrap();
+        watchlist.add(entry(WatchKind::Icao24, "a12345")).unwrap();
+        watchlist.set_note(1, "Airshow display team".to_string());
+        watchlist.save(&path).unwrap();
+
+        let loaded = Watchlist::load(&path).unwrap();
+        std::fs::remove_file(&path).ok();
+        assert_eq!(loaded.entries(), watchlist.entries());
+        assert!(Watchlist::load(&path).unwrap().is_empty());
+    }
+
+    #[test]
+    fn test_load_keeps_invalid_entries() {
+        let path = std::env::temp_dir().join(format!("skyradar-watchlist-bad-{}.toml", std::process::id()));
+        std::fs::write(
+            &path,
+            "[[watch]]\nmatcher = { callsign = \"(\" }\nadded = \"2024-05-01T12:00:00Z\"\n\n\
+             [[watch]]\nmatcher = { icao24 = \"a12345\" }\nadded = \"2024-05-01T12:00:00Z\"\n",
+        )
+        .unwrap();
+
+        let loaded = Watchlist::load(&path).unwrap();
+        assert_eq!(loaded.entries().len(), 2);
+        assert!(loaded.error(0).is_some());
+        assert!(loaded.error(1).is_none());
+        let home = Location::london();
+        assert!(loaded.matching(&aircraft("a12345", "(", &home)).is_some());
+
+        // Saving writes the broken entry back rather than dropping it
+        loaded.save(&path).unwrap();
+        let reloaded = Watchlist::load(&path).unwrap();
+        std::fs::remove_file(&path).ok();
+        assert_eq!(reloaded.entries(), loaded.entries());
+    }
+}
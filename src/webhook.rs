@@ -0,0 +1,597 @@
+use crate::aircraft::Aircraft;
+use crate::alerts::{Alert, Severity};
+use crate::config::CallsignDisplay;
+use anyhow::{anyhow, Context, Result};
+use chrono::{DateTime, Duration, Utc};
+use reqwest::Client;
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Value};
+use std::collections::HashMap;
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex};
+use tokio::runtime::Runtime;
+use tracing::{debug, info, warn};
+
+// About a day of retries before a delivery is abandoned: the backoff reaches
+// its hourly cap after ten retries, and the rest are an hour apart
+const MAX_ATTEMPTS: u32 = 34;
+const FIRST_RETRY_SECS: i64 = 5;
+const MAX_RETRY_SECS: i64 = 3600;
+const POLL_INTERVAL: std::time::Duration = std::time::Duration::from_secs(5);
+
+pub const DEFAULT_TEMPLATE: &str =
+    r#"{"text": {{message}}, "event": {{event}}, "icao24": {{icao24}}, "callsign": {{callsign}}, "time": {{time}}}"#;
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq, Hash)]
+#[serde(rename_all = "snake_case")]
+pub enum EventKind {
+    Alert,
+    Appeared,
+    Lost,
+}
+
+impl EventKind {
+    pub const ALL: [EventKind; 3] = [EventKind::Alert, EventKind::Appeared, EventKind::Lost];
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            EventKind::Alert => "alert",
+            EventKind::Appeared => "appeared",
+            EventKind::Lost => "lost",
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct WebhookSettings {
+    pub name: String,
+    pub url: String,
+    pub enabled: bool,
+    pub events: Vec<EventKind>,
+    // JSON where each {{placeholder}} is replaced by a JSON value
+    pub template: String,
+}
+
+impl WebhookSettings {
+    pub fn new(name: &str, url: &str) -> Self {
+        Self {
+            name: name.to_string(),
+            url: url.trim().to_string(),
+            enabled: true,
+            events: vec![EventKind::Alert],
+            template: DEFAULT_TEMPLATE.to_string(),
+        }
+    }
+
+    pub fn accepts(&self, kind: EventKind) -> bool {
+        self.enabled && self.events.contains(&kind)
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct WebhookEvent {
+    pub kind: EventKind,
+    pub icao24: String,
+    pub callsign: Option<String>,
+    pub message: String,
+    pub rule: Option<String>,
+    pub severity: Option<Severity>,
+    pub time: DateTime<Utc>,
+    pub latitude: Option<f64>,
+    pub longitude: Option<f64>,
+    pub altitude: Option<f64>,
+}
+
+impl WebhookEvent {
+    pub fn from_alert(alert: &Alert, aircraft: Option<&Aircraft>) -> Self {
+        Self {
+            rule: Some(alert.rule.clone()),
+            severity: Some(alert.severity),
+            message: format!("{}: {}", alert.rule, alert.message),
+            time: alert.time,
+            ..Self::describe(EventKind::Alert, &alert.icao24, aircraft)
+        }
+    }
+
+    pub fn lifecycle(kind: EventKind, aircraft: &Aircraft, display: CallsignDisplay, time: DateTime<Utc>) -> Self {
+        let verb = if kind == EventKind::Lost { "left coverage" } else { "appeared" };
+        Self {
+            message: format!("{} {}", aircraft.label(display), verb),
+            time,
+            ..Self::describe(kind, &aircraft.icao24, Some(aircraft))
+        }
+    }
+
+    fn describe(kind: EventKind, icao24: &str, aircraft: Option<&Aircraft>) -> Self {
+        Self {
+            kind,
+            icao24: icao24.to_string(),
+            callsign: aircraft.and_then(|a| a.callsign.as_deref()).map(|c| c.trim().to_string()),
+            message: String::new(),
+            rule: None,
+            severity: None,
+            time: Utc::now(),
+            latitude: aircraft.and_then(|a| a.latitude),
+            longitude: aircraft.and_then(|a| a.longitude),
+            altitude: aircraft.and_then(|a| a.altitude),
+        }
+    }
+
+    fn value(&self, key: &str) -> Option<Value> {
+        let value = match key {
+            "event" => json!(self.kind.name()),
+            "icao24" => json!(self.icao24),
+            "callsign" => json!(self.callsign),
+            "message" => json!(self.message),
+            "rule" => json!(self.rule),
+            "severity" => json!(self.severity.map(|s| s.name())),
+            "time" => json!(self.time.to_rfc3339()),
+            "latitude" => json!(self.latitude),
+            "longitude" => json!(self.longitude),
+            "altitude" => json!(self.altitude),
+            _ => return None,
+        };
+        Some(value)
+    }
+}
+
+// Placeholders become complete JSON values (quoted strings, numbers or
+// null), so templates never need quoting and the output is always checked
+// to be valid JSON before it is queued
+pub fn render(template: &str, event: &WebhookEvent) -> Result<String> {
+    let mut output = String::with_capacity(template.len());
+    let mut rest = template;
+    while let Some(start) = rest.find("{{") {
+        output.push_str(&rest[..start]);
+        let end = rest[start..]
+            .find("}}")
+            .ok_or_else(|| anyhow!("Unclosed placeholder in template"))?;
+        let key = rest[start + 2..start + end].trim();
+        let value = event
+            .value(key)
+            .ok_or_else(|| anyhow!("Unknown placeholder `{{{{{}}}}}`", key))?;
+        output.push_str(&value.to_string());
+        rest = &rest[start + end + 2..];
+    }
+    output.push_str(rest);
+
+    serde_json::from_str::<Value>(&output).context("Template does not produce valid JSON")?;
+    Ok(output)
+}
+
+// Reports aircraft entering and leaving the feed. Aircraft present on the
+// first update were already there at startup and don't count as appearing.
+#[derive(Debug, Default)]
+pub struct LifecycleTracker {
+    known: HashMap<String, Aircraft>,
+    primed: bool,
+}
+
+impl LifecycleTracker {
+    pub fn update(&mut self, aircraft: &[Aircraft]) -> Vec<(EventKind, Aircraft)> {
+        let mut events = Vec::new();
+        let mut known = HashMap::with_capacity(aircraft.len());
+        for aircraft in aircraft {
+            if self.primed && !self.known.contains_key(&aircraft.icao24) {
+                events.push((EventKind::Appeared, aircraft.clone()));
+            }
+            known.insert(aircraft.icao24.clone(), aircraft.clone());
+        }
+        for (icao24, last_seen) in self.known.drain() {
+            if !known.contains_key(&icao24) {
+                events.push((EventKind::Lost, last_seen));
+            }
+        }
+        self.known = known;
+        self.primed = true;
+        events
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct OutboxEntry {
+    pub id: u64,
+    pub webhook: String,
+    pub url: String,
+    pub body: String,
+    pub attempts: u32,
+    pub next_attempt: DateTime<Utc>,
+    #[serde(default)]
+    pub last_error: Option<String>,
+}
+
+// Deliveries waiting to be sent, kept in a JSON lines file so nothing
+// queued is lost when the app exits while an endpoint is down. Changes only
+// mark the queue dirty; the delivery worker writes it out once per pass so
+// the UI thread never waits on the disk.
+#[derive(Debug, Default)]
+pub struct Outbox {
+    path: Option<PathBuf>,
+    entries: Vec<OutboxEntry>,
+    next_id: u64,
+    dirty: bool,
+}
+
+impl Outbox {
+    pub fn default_path() -> PathBuf {
+        crate::config::AppConfig::config_file_path().with_file_name("outbox.jsonl")
+    }
+
+    pub fn in_memory() -> Self {
+        Self::default()
+    }
+
+    pub fn open(path: &Path) -> Result<Self> {
+        let mut outbox = Self {
+            path: Some(path.to_path_buf()),
+            ..Self::default()
+        };
+        if !path.exists() {
+            return Ok(outbox);
+        }
+
+        let text = std::fs::read_to_string(path).with_context(|| format!("Failed to read {}", path.display()))?;
+        for (number, line) in text.lines().enumerate().filter(|(_, l)| !l.trim().is_empty()) {
+            match serde_json::from_str::<OutboxEntry>(line) {
+                Ok(entry) => outbox.entries.push(entry),
+                Err(e) => warn!("Skipping outbox line {}: {}", number + 1, e),
+            }
+        }
+        outbox.next_id = outbox.entries.iter().map(|e| e.id + 1).max().unwrap_or(0);
+        Ok(outbox)
+    }
+
+    pub fn save(&mut self) -> Result<()> {
+        if let Some(path) = &self.path {
+            write_entries(path, &self.entries)?;
+        }
+        self.dirty = false;
+        Ok(())
+    }
+
+    // Copy of the queue to write out, if it changed since the last save
+    fn take_changes(&mut self) -> Option<(PathBuf, Vec<OutboxEntry>)> {
+        if !self.dirty {
+            return None;
+        }
+        self.dirty = false;
+        self.path.clone().map(|path| (path, self.entries.clone()))
+    }
+
+    pub fn entries(&self) -> &[OutboxEntry] {
+        &self.entries
+    }
+
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+
+    pub fn enqueue(&mut self, webhook: &WebhookSettings, body: String, now: DateTime<Utc>) {
+        self.entries.push(OutboxEntry {
+            id: self.next_id,
+            webhook: webhook.name.clone(),
+            url: webhook.url.clone(),
+            body,
+            attempts: 0,
+            next_attempt: now,
+            last_error: None,
+        });
+        self.next_id += 1;
+        self.dirty = true;
+    }
+
+    // Queues the event for every webhook subscribed to it, returning how
+    // many deliveries were queued
+    pub fn enqueue_event(&mut self, webhooks: &[WebhookSettings], event: &WebhookEvent) -> usize {
+        let mut queued = 0;
+        for webhook in webhooks.iter().filter(|w| w.accepts(event.kind)) {
+            match render(&webhook.template, event) {
+                Ok(body) => {
+                    self.enqueue(webhook, body, event.time);
+                    queued += 1;
+                }
+                Err(e) => warn!("Webhook {} skipped: {:#}", webhook.name, e),
+            }
+        }
+        queued
+    }
+
+    pub fn due(&self, now: DateTime<Utc>) -> Vec<OutboxEntry> {
+        self.entries.iter().filter(|e| e.next_attempt <= now).cloned().collect()
+    }
+
+    pub fn delivered(&mut self, id: u64) {
+        self.entries.retain(|e| e.id != id);
+        self.dirty = true;
+    }
+
+    pub fn failed(&mut self, id: u64, error: String, now: DateTime<Utc>) {
+        let Some(idx) = self.entries.iter().position(|e| e.id == id) else {
+            return;
+        };
+        let entry = &mut self.entries[idx];
+        entry.attempts += 1;
+        if entry.attempts >= MAX_ATTEMPTS {
+            warn!("Giving up on webhook {} after {} attempts: {}", entry.webhook, entry.attempts, error);
+            self.entries.remove(idx);
+        } else {
+            entry.next_attempt = now + retry_delay(entry.attempts);
+            entry.last_error = Some(error);
+        }
+        self.dirty = true;
+    }
+
+    pub fn clear(&mut self) {
+        self.entries.clear();
+        self.dirty = true;
+    }
+}
+
+fn write_entries(path: &Path, entries: &[OutboxEntry]) -> Result<()> {
+    if let Some(parent) = path.parent() {
+        std::fs::create_dir_all(parent)?;
+    }
+    // Write then rename so a crash mid-write can't truncate the queue
+    let temp = path.with_extension("jsonl.tmp");
+    let mut file = std::fs::File::create(&temp)?;
+    for entry in entries {
+        writeln!(file, "{}", serde_json::to_string(entry)?)?;
+    }
+    file.sync_all()?;
+    std::fs::rename(&temp, path)?;
+    Ok(())
+}
+
+// Writes the queue if it changed, without holding the lock during the I/O
+pub fn persist(outbox: &Mutex<Outbox>) {
+    let Some((path, entries)) = outbox.lock().unwrap().take_changes() else {
+        return;
+    };
+    if let Err(e) = write_entries(&path, &entries) {
+        warn!("Failed to save webhook outbox: {}", e);
+        outbox.lock().unwrap().dirty = true;
+    }
+}
+
+// Exponential backoff: 5 s, 10 s, 20 s... up to an hour between attempts
+pub fn retry_delay(attempts: u32) -> Duration {
+    let secs = FIRST_RETRY_SECS.saturating_mul(1 << attempts.saturating_sub(1).min(20));
+    Duration::seconds(secs.min(MAX_RETRY_SECS))
+}
+
+pub async fn post(client: &Client, url: &str, body: &str) -> Result<()> {
+    let response = client
+        .post(url)
+        .header(reqwest::header::CONTENT_TYPE, "application/json")
+        .body(body.to_string())
+        .send()
+        .await?;
+    if !response.status().is_success() {
+        return Err(anyhow!("{} returned {}", url, response.status()));
+    }
+    Ok(())
+}
+
+// Sends every delivery that is due and reschedules the ones that fail. The
+// lock is only held between requests so the UI can keep queueing.
+pub async fn flush(outbox: &Mutex<Outbox>, client: &Client, now: DateTime<Utc>) -> usize {
+    let due = outbox.lock().unwrap().due(now);
+    let mut delivered = 0;
+    for entry in due {
+        let result = post(client, &entry.url, &entry.body).await;
+        let mut outbox = outbox.lock().unwrap();
+        match result {
+            Ok(()) => {
+                debug!("Delivered webhook {} to {}", entry.webhook, entry.url);
+                outbox.delivered(entry.id);
+                delivered += 1;
+            }
+            Err(e) => {
+                debug!("Webhook {} attempt {} failed: {}", entry.webhook, entry.attempts + 1, e);
+                outbox.failed(entry.id, e.to_string(), now);
+            }
+        }
+    }
+    delivered
+}
+
+pub fn spawn_worker(runtime: &Runtime, outbox: Arc<Mutex<Outbox>>) {
+    let pending = outbox.lock().unwrap().len();
+    if pending > 0 {
+        info!("Resuming {} queued webhook deliveries", pending);
+    }
+    runtime.spawn(async move {
+        let client = Client::builder()
+            .timeout(std::time::Duration::from_secs(10))
+            .build()
+            .unwrap_or_default();
+        loop {
+            flush(&outbox, &client, Utc::now()).await;
+            persist(&outbox);
+            tokio::time::sleep(POLL_INTERVAL).await;
+        }
+    });
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::io::{BufRead, BufReader, Read};
+    use std::net::TcpListener;
+
+    fn event() -> WebhookEvent {
+        let mut aircraft = Aircraft::new("a12345".to_string());
+        aircraft.callsign = Some("BAW1  ".to_string());
+        aircraft.altitude = Some(1800.0);
+        WebhookEvent::lifecycle(EventKind::Appeared, &aircraft, CallsignDisplay::Callsign, Utc::now())
+    }
+
+    // Minimal HTTP endpoint answering each connection with the next status
+    // and handing back the request bodies it received
+    fn serve(statuses: Vec<u16>) -> (String, std::thread::JoinHandle<Vec<String>>) {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let url = format!("http://{}/hook", listener.local_addr().unwrap());
+        let handle = std::thread::spawn(move || {
+            let mut bodies = Vec::new();
+            for status in statuses {
+                let (stream, _) = listener.accept().unwrap();
+                let mut reader = BufReader::new(stream);
+                let mut length = 0;
+                let mut json = false;
+                loop {
+                    let mut line = String::new();
+                    reader.read_line(&mut line).unwrap();
+                    let line = line.trim().to_ascii_lowercase();
+                    if line.is_empty() {
+                        break;
+                    }
+                    if let Some(value) = line.strip_prefix("content-length:") {
+                        length = value.trim().parse().unwrap();
+                    }
+                    json |= line == "content-type: application/json";
+                }
+                let mut body = vec![0; length];
+                reader.read_exact(&mut body).unwrap();
+                assert!(json, "missing JSON content type");
+                bodies.push(String::from_utf8(body).unwrap());
+
+                let response = format!("HTTP/1.1 {} Status\r\nContent-Length: 0\r\nConnection: close\r\n\r\n", status);
+                reader.get_mut().write_all(response.as_bytes()).unwrap();
+            }
+            bodies
+        });
+        (url, handle)
+    }
+
+    #[test]
+    fn test_render() {
+        let event = event();
+        let body = render(r#"{"who": {{callsign}}, "alt": {{ altitude }}, "rule": {{rule}}}"#, &event).unwrap();
+        let value: Value = serde_json::from_str(&body).unwrap();
+        assert_eq!(value, json!({"who": "BAW1", "alt": 1800.0, "rule": null}));
+
+        let message = render(DEFAULT_TEMPLATE, &event).unwrap();
+        assert!(message.contains(r#""text": "BAW1 appeared""#));
+
+        assert!(render(r#"{"x": {{speed}}}"#, &event).is_err());
+        assert!(render(r#"{"x": {{icao24}}"#, &event).is_err());
+        assert!(render(r#"{"x": {{icao24"#, &event).is_err());
+    }
+
+    #[test]
+    fn test_backoff_and_give_up() {
+        assert_eq!(retry_delay(1), Duration::seconds(5));
+        assert_eq!(retry_delay(3), Duration::seconds(20));
+        assert_eq!(retry_delay(40), Duration::seconds(3600));
+        let schedule: i64 = (1..MAX_ATTEMPTS).map(|a| retry_delay(a).num_seconds()).sum();
+        assert!(schedule >= 24 * 3600 && schedule < 25 * 3600);
+
+        let now = Utc::now();
+        let mut outbox = Outbox::in_memory();
+        outbox.enqueue(&WebhookSettings::new("ops", "http://localhost/"), "{}".to_string(), now);
+        outbox.failed(0, "refused".to_string(), now);
+        assert!(outbox.due(now).is_empty());
+        assert_eq!(outbox.due(now + Duration::seconds(5)).len(), 1);
+
+        for _ in 1..MAX_ATTEMPTS {
+            outbox.failed(0, "refused".to_string(), now);
+        }
+        assert!(outbox.is_empty());
+    }
+
+    #[test]
+    fn test_outbox_survives_restart() {
+        let path = std::env::temp_dir().join(format!("skyradar-outbox-{}.jsonl", std::process::id()));
+        let webhooks = vec![WebhookSettings::new("ops", "http://localhost/"), {
+            let mut lifecycle = WebhookSettings::new("lifecycle", "http://localhost/other");
+            lifecycle.events = vec![EventKind::Appeared];
+            lifecycle
+        }];
+
+        let mut outbox = Outbox::open(&path).unwrap();
+        assert_eq!(outbox.enqueue_event(&webhooks, &event()), 1);
+        outbox.enqueue(&webhooks[0], "{}".to_string(), Utc::now());
+        let outbox = Mutex::new(outbox);
+        persist(&outbox);
+        let outbox = outbox.into_inner().unwrap();
+
+        let mut reopened = Outbox::open(&path).unwrap();
+        assert_eq!(reopened.entries(), outbox.entries());
+        assert_eq!(reopened.entries()[0].webhook, "lifecycle");
+
+        // Ids keep counting up after a restart
+        reopened.enqueue(&webhooks[0], "{}".to_string(), Utc::now());
+        reopened.delivered(0);
+        reopened.save().unwrap();
+        let ids: Vec<u64> = Outbox::open(&path).unwrap().entries().iter().map(|e| e.id).collect();
+        std::fs::remove_file(&path).ok();
+        assert_eq!(ids, vec![1, 2]);
+    }
+
+    #[test]
+    fn test_exit_persists_without_worker() {
+        let path = std::env::temp_dir().join(format!("skyradar-outbox-exit-{}.jsonl", std::process::id()));
+        std::fs::remove_file(&path).ok();
+        let outbox = Arc::new(Mutex::new(Outbox::open(&path).unwrap()));
+        let webhooks = vec![WebhookSettings::new("ops", "http://localhost/")];
+        let mut alert = event();
+        alert.kind = EventKind::Alert;
+
+        // Queued just before exit, with no worker pass in between
+        assert_eq!(outbox.lock().unwrap().enqueue_event(&webhooks, &alert), 1);
+        assert!(!path.exists());
+        persist(&outbox);
+
+        let reopened = Outbox::open(&path).unwrap();
+        std::fs::remove_file(&path).ok();
+        assert_eq!(reopened.entries(), outbox.lock().unwrap().entries());
+    }
+
+    #[test]
+    fn test_delivery_retries_until_accepted() {
+        let (url, server) = serve(vec![503, 200]);
+        let outbox = Mutex::new(Outbox::in_memory());
+        let webhook = WebhookSettings::new("ops", &url);
+        let now = Utc::now();
+        outbox.lock().unwrap().enqueue(&webhook, r#"{"text": "hello"}"#.to_string(), now);
+
+        let runtime = Runtime::new().unwrap();
+        let client = Client::new();
+        assert_eq!(runtime.block_on(flush(&outbox, &client, now)), 0);
+        {
+            let outbox = outbox.lock().unwrap();
+            let entry = &outbox.entries()[0];
+            assert_eq!(entry.attempts, 1);
+            assert!(entry.last_error.as_deref().unwrap().contains("503"));
+            assert!(outbox.due(now).is_empty());
+        }
+
+        // Nothing is sent again before the backoff expires
+        assert_eq!(runtime.block_on(flush(&outbox, &client, now)), 0);
+        assert_eq!(runtime.block_on(flush(&outbox, &client, now + retry_delay(1))), 1);
+        assert!(outbox.lock().unwrap().is_empty());
+
+        let bodies = server.join().unwrap();
+        assert_eq!(bodies, vec![r#"{"text": "hello"}"#; 2]);
+    }
+
+    #[test]
+    fn test_lifecycle() {
+        let mut tracker = LifecycleTracker::default();
+        let first = Aircraft::new("aaaaaa".to_string());
+        let second = Aircraft::new("bbbbbb".to_string());
+
+        assert!(tracker.update(&[first.clone()]).is_empty());
+        let events = tracker.update(&[first, second.clone()]);
+        assert_eq!(events.len(), 1);
+        assert_eq!((events[0].0, events[0].1.icao24.as_str()), (EventKind::Appeared, "bbbbbb"));
+
+        let events = tracker.update(&[second]);
+        assert_eq!((events[0].0, events[0].1.icao24.as_str()), (EventKind::Lost, "aaaaaa"));
+    }
+}
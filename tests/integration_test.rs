@@ -96,4 +96,38 @@ fn test_aircraft_activity() {
     // Test with old timestamp
     aircraft.time_position = Some(chrono::Utc::now() - chrono::Duration::minutes(10));
     assert!(!aircraft.is_active());
-} 
\ No newline at end of file
+}
+
+#[test]
+fn test_aircraft_country_resolution() {
+    let mut aircraft = Aircraft::new("406a3b".to_string());
+
+    // Falls back to the ICAO address allocation
+    assert_eq!(aircraft.country(), Some("United Kingdom"));
+    assert_eq!(aircraft.country_code(), Some("GB"));
+
+    // Feed-supplied country wins
+    aircraft.origin_country = Some("Ireland".to_string());
+    assert_eq!(aircraft.country(), Some("Ireland"));
+
+    let anonymous = Aircraft::new("~1a2b3c".to_string());
+    assert_eq!(anonymous.address_kind(), skyradar::icao::AddressKind::NonIcao);
+    assert!(anonymous.country().is_none());
+}
+
+#[test]
+fn test_aircraft_label_styles() {
+    use skyradar::config::CallsignDisplay;
+
+    let mut aircraft = Aircraft::new("406a3b".to_string());
+    aircraft.callsign = Some("BAW123  ".to_string());
+
+    assert_eq!(aircraft.label(CallsignDisplay::Callsign), "BAW123");
+    assert_eq!(aircraft.label(CallsignDisplay::Airline), "BAW123 / British Airways 123");
+    assert_eq!(aircraft.label(CallsignDisplay::Iata), "BA123");
+    assert_eq!(aircraft.label(CallsignDisplay::Telephony), "SPEEDBIRD 123");
+
+    // Unknown operators fall back to the raw callsign
+    aircraft.callsign = Some("N123AB".to_string());
+    assert_eq!(aircraft.label(CallsignDisplay::Airline), "N123AB");
+}